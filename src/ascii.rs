@@ -1,72 +1,289 @@
-use std::cmp::min;
+use std::{cmp::min, env, fs, path::{Path, PathBuf}, str::FromStr};
 
-use colored::{ColoredString, Colorize};
+use colored::Colorize;
 use serde::Deserialize;
+use toml_edit::{ImDocument, Item, Value};
+use unicode_width::UnicodeWidthChar;
 
-use crate::{ascii_art, config_manager::{self, Configuration}, formatter::CrabFetchColor};
+use crate::{ascii_art, config_manager::{self, Configuration}, formatter::{self, CrabFetchColor}, image::ImageProtocol, util};
 
 #[derive(Deserialize)]
 pub struct AsciiConfiguration {
     pub display: bool,
     pub side: String,
     pub margin: u16,
+    // Below this many columns, the ASCII's moved to narrow_side rather than squashing the modules
+    pub min_terminal_width: u16,
+    pub narrow_side: String,
     pub bold: bool,
     pub mode: AsciiMode,
     // Coloring options 
     // Done this way because I kid you not I could not find a way to make an multi-type thing for
     // the Config crate
     pub solid_color: CrabFetchColor,
-    pub band_colors: Vec<CrabFetchColor>
+    pub band_colors: Vec<CrabFetchColor>,
+    // Overrides the distro's colors for ${c1}..${c6}
+    #[serde(default)]
+    pub colors: Vec<CrabFetchColor>,
+    // An image to show instead of the ASCII, if the terminal can display it
+    pub image: Option<String>,
+    pub image_protocol: ImageProtocol,
+    // In terminal cells, the height comes from the image's aspect ratio
+    pub image_width: u16,
+    pub size: AsciiSize
 }
 #[derive(Debug, Deserialize, PartialEq)]
 pub enum AsciiMode {
     Raw,
     OS,
     Solid,
-    Band
+    Band,
+    BandHorizontal,
+    BandDiagonal,
+    Gradient
 }
 
-// Return type is the ascii & the maximum length of it
-pub fn find_ascii(os: &str, ignore_custom: bool) -> (String, u16) {
-    // Will first confirm if theres a ascii override file
+// A built in ASCII & it's width
+type BuiltInAscii = (&'static str, u16);
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub enum AsciiSize {
+    Auto,
+    Small,
+    Large
+}
+
+// Return type is the ascii, the maximum length of it & the colors for it's ${c1}..${c6} markers, if
+// it came with it's own
+// os_ids are tried in order, e.g the distro's ID then it's ID_LIKE's, see OSInfo::distro_ids
+// module_lines is how many lines the modules take up, used to pick the size in auto
+pub fn find_ascii(os_ids: &[&str], ignore_custom: bool, config: &Configuration, module_lines: usize) -> (String, u16, Option<Vec<CrabFetchColor>>) {
+    let os_ids: Vec<String> = os_ids.iter()
+        .map(|x| x.replace('"', "").to_lowercase())
+        .collect();
+
+    // Logo files are per distro, so they win over the ascii override which is used for everything
+    let logo_dirs: Vec<PathBuf> = logo_dirs(!ignore_custom);
+    for os in &os_ids {
+        if let Some(logo) = find_logo_file(&logo_dirs, os) {
+            return logo;
+        }
+    }
+    // Deprecated in favour of logo files, but still used over the built in ASCII
     if !ignore_custom {
         if let Some(user_override) = config_manager::check_for_ascii_override() {
-            let mut length: u16 = 0; // TODO: Use usize for fucks sake
-            user_override.split('\n').for_each(|x| {
-                let stripped = strip_ansi_escapes::strip_str(x);
-                let len: usize = stripped.chars().count();
-                if len > length as usize { length = u16::try_from(len).expect("Unable to convert length to u16") }
-            });
-            return (user_override, length)
+            let length: u16 = ascii_width(&user_override);
+            return (user_override, length, None)
+        }
+    }
+    for os in &os_ids {
+        if let Some((large, small)) = built_in_ascii(os) {
+            let ascii: BuiltInAscii = if use_small_ascii(large.0, config, module_lines) { small } else { large };
+            // I blame rust not letting me make const strings
+            return (ascii.0.to_string(), ascii.1, None);
+        }
+    }
+
+    (String::new(), 0, None)
+}
+
+// The widest line in some ASCII, ignoring any escape codes or color markers
+fn ascii_width(ascii: &str) -> u16 {
+    let mut length: u16 = 0; // TODO: Use usize for fucks sake
+    ascii.split('\n').for_each(|x| {
+        let len: usize = formatter::display_width(&color_markers(x, None));
+        if len > length as usize { length = u16::try_from(len).expect("Unable to convert length to u16") }
+    });
+    length
+}
+
+// The folders logos can be dropped into as <id>.txt, instead of needing to be built in
+// The user's own come first, then the system's, e.g ones shipped by a distro's package
+fn logo_dirs(include_user: bool) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if include_user {
+        dirs.extend(config_manager::find_logos_dir());
+    }
+    let data_dirs: String = env::var("XDG_DATA_DIRS").unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(data_dirs.split(':')
+        .filter(|x| !x.is_empty())
+        .map(|x| Path::new(x).join("crabfetch/logos"))
+        .filter(|x| x.is_dir()));
+
+    dirs
+}
+
+// Looks for <id>.txt in each logo folder, or any logo in them that lists the ID in it's aliases
+fn find_logo_file(logo_dirs: &[PathBuf], os: &str) -> Option<(String, u16, Option<Vec<CrabFetchColor>>)> {
+    // IDs come from os-release, so make sure one can't go poking around outside the folder
+    if os.is_empty() || os.contains('/') || os.starts_with('.') {
+        return None;
+    }
+
+    for dir in logo_dirs {
+        if let Ok(contents) = fs::read_to_string(dir.join(format!("{os}.txt"))) {
+            let logo: LogoFile = parse_logo_file(&contents);
+            return Some((logo.ascii, logo.width, logo.colors));
+        }
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue
+        };
+        for entry in entries.flatten() {
+            if entry.path().extension().is_none_or(|x| x != "txt") {
+                continue;
+            }
+            let Ok(contents) = fs::read_to_string(entry.path()) else {
+                continue
+            };
+            let logo: LogoFile = parse_logo_file(&contents);
+            if logo.aliases.iter().any(|x| x.eq_ignore_ascii_case(os)) {
+                return Some((logo.ascii, logo.width, logo.colors));
+            }
         }
     }
-    let os: &str = &os.replace('"', "").to_lowercase();
-
-    let ascii: (&str, u16) = match os {
-        "arch" => ascii_art::ARCH,
-        "debian" => ascii_art::DEBIAN,
-        "ubuntu" => ascii_art::UBUNTU,
-        "fedora" => ascii_art::FEDORA,
-        "void" => ascii_art::VOID,
-        "endeavouros" => ascii_art::ENDEAVOUR,
-        "linuxmint" => ascii_art::MINT,
-        "elementary" => ascii_art::ELEMENTARY,
-        "zorin" => ascii_art::ZORIN,
-        "manjaro" => ascii_art::MANJARO,
-        "pop" => ascii_art::POPOS,
-        "opensuse-leap" | "opensuse-tumbleweed" => ascii_art::OPENSUSE,
-        "bazzite" => ascii_art::BAZZITE,
-        "rocky" => ascii_art::ROCKYLINUX,
-        "kali" => ascii_art::KALI,
-        "almalinux" => ascii_art::ALMA,
-        "android" => ascii_art::ANDROID,
-        "garuda" => ascii_art::GARUDA,
-        _ => ("", 0)
+
+    None
+}
+
+struct LogoFile {
+    ascii: String,
+    width: u16,
+    colors: Option<Vec<CrabFetchColor>>,
+    aliases: Vec<String>
+}
+// Logo files can start with a TOML header between two "---" lines, e.g;
+// ---
+// colors = ["bright_red", "white"]
+// width = 28
+// aliases = ["pop-os"]
+// ---
+// Anything in the header that doesn't parse is ignored, so the logo still shows
+fn parse_logo_file(contents: &str) -> LogoFile {
+    let contents: String = contents.replace("\r\n", "\n");
+    let (header, ascii): (&str, &str) = contents.strip_prefix("---\n")
+        .and_then(|x| match x.strip_prefix("---\n") {
+            Some(ascii) => Some(("", ascii)),
+            None => x.split_once("\n---\n").or_else(|| x.strip_suffix("\n---").map(|header| (header, ""))),
+        })
+        .unwrap_or(("", &contents));
+
+    let header: Option<ImDocument<&str>> = ImDocument::parse(header).ok();
+    let get = |key: &str| -> Option<&Item> { header.as_ref().and_then(|x| x.get(key)) };
+    let strings = |key: &str| -> Vec<String> {
+        get(key).and_then(Item::as_array)
+            .map(|x| x.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default()
     };
 
-    // I blame rust not letting me make const strings
-    let ascii_string: String = ascii.0.to_string();
-    (ascii_string, ascii.1)
+    let colors: Vec<CrabFetchColor> = strings("colors").iter()
+        .filter_map(|x| CrabFetchColor::from_str(x).ok())
+        .collect();
+    let width: u16 = get("width").and_then(Item::as_integer)
+        .and_then(|x| u16::try_from(x).ok())
+        .unwrap_or_else(|| ascii_width(ascii));
+
+    LogoFile {
+        ascii: ascii.to_string(),
+        width,
+        colors: if colors.is_empty() { None } else { Some(colors) },
+        aliases: strings("aliases")
+    }
+}
+
+// The large & small ASCII for a distro ID
+fn built_in_ascii(os: &str) -> Option<(BuiltInAscii, BuiltInAscii)> {
+    match os {
+        "arch" => Some((ascii_art::ARCH, ascii_art::ARCH_SMALL)),
+        "debian" => Some((ascii_art::DEBIAN, ascii_art::DEBIAN_SMALL)),
+        "ubuntu" => Some((ascii_art::UBUNTU, ascii_art::UBUNTU_SMALL)),
+        "fedora" => Some((ascii_art::FEDORA, ascii_art::FEDORA_SMALL)),
+        "void" => Some((ascii_art::VOID, ascii_art::VOID_SMALL)),
+        "endeavouros" => Some((ascii_art::ENDEAVOUR, ascii_art::ENDEAVOUR_SMALL)),
+        "linuxmint" => Some((ascii_art::MINT, ascii_art::MINT_SMALL)),
+        "elementary" => Some((ascii_art::ELEMENTARY, ascii_art::ELEMENTARY_SMALL)),
+        "zorin" => Some((ascii_art::ZORIN, ascii_art::ZORIN_SMALL)),
+        "manjaro" => Some((ascii_art::MANJARO, ascii_art::MANJARO_SMALL)),
+        "pop" => Some((ascii_art::POPOS, ascii_art::POPOS_SMALL)),
+        "opensuse-leap" | "opensuse-tumbleweed" => Some((ascii_art::OPENSUSE, ascii_art::OPENSUSE_SMALL)),
+        "bazzite" => Some((ascii_art::BAZZITE, ascii_art::BAZZITE_SMALL)),
+        "rocky" => Some((ascii_art::ROCKYLINUX, ascii_art::ROCKYLINUX_SMALL)),
+        "kali" => Some((ascii_art::KALI, ascii_art::KALI_SMALL)),
+        "almalinux" => Some((ascii_art::ALMA, ascii_art::ALMA_SMALL)),
+        "android" => Some((ascii_art::ANDROID, ascii_art::ANDROID_SMALL)),
+        "garuda" => Some((ascii_art::GARUDA, ascii_art::GARUDA_SMALL)),
+        _ => None
+    }
+}
+
+// Auto picks the small ASCII if the large one would be taller than the modules next to it, or
+// wouldn't fit in the terminal
+fn use_small_ascii(large: &str, config: &Configuration, module_lines: usize) -> bool {
+    match config.ascii.size {
+        AsciiSize::Small => true,
+        AsciiSize::Large => false,
+        AsciiSize::Auto => {
+            let large_lines: usize = large.lines().count();
+            let beside_modules: bool = config.ascii.side == "left" || config.ascii.side == "right";
+            (beside_modules && module_lines < large_lines)
+                || util::terminal_size().is_some_and(|x| usize::from(x.ws_row) < large_lines)
+        },
+    }
+}
+
+// The colors used for the ${c1}..${c6} markers, either from the config or the distro's defaults
+pub fn logo_colors(os_ids: &[&str], ansi_color: Option<&str>, config: &Configuration) -> Vec<CrabFetchColor> {
+    if !config.ascii.colors.is_empty() {
+        return config.ascii.colors.clone();
+    }
+
+    for os in os_ids {
+        let os: &str = &os.replace('"', "").to_lowercase();
+        if let Some(x) = ascii_art::LOGO_COLORS.iter().find(|x| x.0 == os) {
+            return x.1.to_vec();
+        }
+    }
+    vec![formatter::find_os_color(os_ids, ansi_color)]
+}
+
+// Replaces the ${c1}..${c6} markers with the colors given, or just strips them if there's none
+// The color carries on over new lines, same as neofetch
+pub fn color_markers(ascii: &str, colors: Option<&[CrabFetchColor]>) -> String {
+    let mut result: String = String::with_capacity(ascii.len());
+    let mut current: Option<&CrabFetchColor> = None;
+
+    for (i, line) in ascii.split('\n').enumerate() {
+        if i != 0 {
+            result.push('\n');
+        }
+
+        let mut rest: &str = line;
+        loop {
+            let marker: Option<(usize, usize)> = find_marker(rest);
+            let segment: &str = marker.map_or(rest, |(start, _)| &rest[..start]);
+            match current {
+                Some(color) if !segment.is_empty() => result.push_str(&color.color_string(segment).to_string()),
+                _ => result.push_str(segment)
+            }
+
+            let Some((start, index)) = marker else {
+                break
+            };
+            current = colors.and_then(|x| x.get(index));
+            rest = &rest[start + 5..];
+        }
+    }
+
+    result
+}
+
+// Position of the next marker & which color it's for
+fn find_marker(text: &str) -> Option<(usize, usize)> {
+    text.match_indices("${c").find_map(|(start, _)| {
+        match text.as_bytes().get(start + 3..start + 5) {
+            Some([digit @ b'1'..=b'6', b'}']) => Some((start, (digit - b'1') as usize)),
+            _ => None
+        }
+    })
 }
 
 pub fn get_ascii_line(current_line: usize, ascii_split: &[&str], target_length: u16, config: &Configuration) -> String {
@@ -76,16 +293,17 @@ pub fn get_ascii_line(current_line: usize, ascii_split: &[&str], target_length:
         line.push_str(ascii_split[current_line]);
     }
 
-    let ansi_stripped = strip_ansi_escapes::strip_str(&line);
-    if ansi_stripped.chars().count() < target_length as usize {
-        line.push_str(&" ".repeat(target_length as usize - ansi_stripped.chars().count()));
+    let line_width: usize = formatter::display_width(&line);
+    if line_width < target_length as usize {
+        line.push_str(&" ".repeat(target_length as usize - line_width));
     }
 
     if config.ascii.mode != AsciiMode::Raw {
         line = match config.ascii.mode {
             AsciiMode::Raw => line,
             AsciiMode::OS | AsciiMode::Solid => color_solid(&line, config), // main func sets the solid color to be the os color in the OS's case
-            AsciiMode::Band => color_band_vertical(&line, current_line, ascii_split.len(), config)
+            AsciiMode::Band => color_band_vertical(&line, current_line, ascii_split.len(), config),
+            AsciiMode::BandHorizontal | AsciiMode::BandDiagonal | AsciiMode::Gradient => color_per_character(&line, current_line, ascii_split, config)
         }
     }
 
@@ -100,12 +318,173 @@ fn color_solid(line: &str, config: &Configuration) -> String {
     config.ascii.solid_color.color_string(line).to_string()
 }
 
-#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+#[allow(clippy::cast_precision_loss)]
 fn color_band_vertical(line: &str, current_line: usize, ascii_length: usize, config: &Configuration) -> String {
-    let percentage: f32 = current_line as f32 / (ascii_length - 1) as f32;
-    let total_colors: usize = config.ascii.band_colors.len();
-    let index: usize = min((((total_colors - 1) as f32) * percentage).round() as usize, total_colors - 1);
+    let percentage: f32 = current_line as f32 / ascii_length.saturating_sub(1).max(1) as f32;
+    band_color(percentage, &config.ascii.band_colors).color_string(line).to_string()
+}
+
+// The modes that change color along the line, so each character needs it's own color
+#[allow(clippy::cast_precision_loss)]
+fn color_per_character(line: &str, current_line: usize, ascii_split: &[&str], config: &Configuration) -> String {
+    let width: usize = ascii_split.iter()
+        .map(|x| formatter::display_width(x))
+        .max()
+        .unwrap_or(0);
+    // Terminal cells are about twice as tall as they are wide, so lines count double to keep the
+    // diagonal at 45 degrees
+    let diagonal_length: usize = width + ascii_split.len() * 2;
 
-    let colored: ColoredString = config.ascii.band_colors.get(index as usize).unwrap().color_string(line);
-    colored.to_string()
+    let mut colored: String = String::new();
+    let mut segment: String = String::new();
+    let mut segment_color: Option<CrabFetchColor> = None;
+    // x is the column, so the colors line up with wide characters too
+    let mut x: usize = 0;
+    for c in strip_ansi_escapes::strip_str(line).chars() {
+        let column: usize = x;
+        x += c.width().unwrap_or(0);
+        let percentage: f32 = match config.ascii.mode {
+            AsciiMode::BandDiagonal => (column + current_line * 2) as f32 / diagonal_length.saturating_sub(3).max(1) as f32,
+            _ => column as f32 / width.saturating_sub(1).max(1) as f32
+        }.min(1.0);
+        let color: CrabFetchColor = if config.ascii.mode == AsciiMode::Gradient {
+            gradient_color(percentage, &config.ascii.band_colors)
+        } else {
+            band_color(percentage, &config.ascii.band_colors)
+        };
+
+        // Spaces don't show any color, so there's no point splitting the segment for them
+        if c == ' ' {
+            if segment_color.is_some() {
+                segment.push(c);
+            } else {
+                colored.push(c);
+            }
+            continue;
+        }
+        if segment_color.as_ref() != Some(&color) {
+            if let Some(segment_color) = segment_color {
+                colored.push_str(&segment_color.color_string(&segment).to_string());
+                segment.clear();
+            }
+            segment_color = Some(color);
+        }
+        segment.push(c);
+    }
+    if let Some(segment_color) = segment_color {
+        colored.push_str(&segment_color.color_string(&segment).to_string());
+    }
+
+    colored
+}
+
+// The band color at a certain % through the ASCII
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn band_color(percentage: f32, colors: &[CrabFetchColor]) -> CrabFetchColor {
+    if colors.is_empty() {
+        return CrabFetchColor::Clear;
+    }
+    let index: usize = min((((colors.len() - 1) as f32) * percentage).round() as usize, colors.len() - 1);
+    colors[index].clone()
+}
+
+// Blends between the two band colors either side of a certain % through the ASCII
+// Clear can't be blended, so that just falls back to the closest band
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn gradient_color(percentage: f32, colors: &[CrabFetchColor]) -> CrabFetchColor {
+    if colors.len() < 2 {
+        return band_color(percentage, colors);
+    }
+    let position: f32 = (colors.len() - 1) as f32 * percentage;
+    let index: usize = min(position.floor() as usize, colors.len() - 2);
+    let progress: f32 = position - index as f32;
+
+    let (Some(from), Some(to)) = (colors[index].rgb(), colors[index + 1].rgb()) else {
+        return band_color(percentage, colors);
+    };
+    let blend = |from: u8, to: u8| -> u8 {
+        (f32::from(from) + (f32::from(to) - f32::from(from)) * progress).round() as u8
+    };
+    CrabFetchColor::Rgb(blend(from.0, to.0), blend(from.1, to.1), blend(from.2, to.2))
+}
+
+mod tests {
+    #[test]
+    fn test_color_markers() {
+        use crate::{ascii::color_markers, formatter::CrabFetchColor};
+
+        let colors: &[CrabFetchColor] = &[CrabFetchColor::Red, CrabFetchColor::Blue];
+        let red = |x: &str| CrabFetchColor::Red.color_string(x).to_string();
+        let blue = |x: &str| CrabFetchColor::Blue.color_string(x).to_string();
+
+        assert_eq!(color_markers("${c1}ab${c2}cd\nef", None), "abcd\nef");
+        assert_eq!(color_markers("${c1}ab${c2}cd\nef", Some(colors)), format!("{}{}\n{}", red("ab"), blue("cd"), blue("ef")));
+        // Unused slots go uncolored, anything that isn't a marker is left alone
+        assert_eq!(color_markers("${c3}ab${c7}${c", Some(colors)), "ab${c7}${c");
+    }
+    #[test]
+    fn test_ascii_sizes() {
+        use crate::{ascii::{color_markers, BuiltInAscii}, ascii_art};
+
+        let logos: &[(BuiltInAscii, BuiltInAscii)] = &[
+            (ascii_art::ARCH, ascii_art::ARCH_SMALL), (ascii_art::DEBIAN, ascii_art::DEBIAN_SMALL), (ascii_art::UBUNTU, ascii_art::UBUNTU_SMALL),
+            (ascii_art::FEDORA, ascii_art::FEDORA_SMALL), (ascii_art::VOID, ascii_art::VOID_SMALL), (ascii_art::ENDEAVOUR, ascii_art::ENDEAVOUR_SMALL),
+            (ascii_art::MINT, ascii_art::MINT_SMALL), (ascii_art::ELEMENTARY, ascii_art::ELEMENTARY_SMALL), (ascii_art::ZORIN, ascii_art::ZORIN_SMALL),
+            (ascii_art::MANJARO, ascii_art::MANJARO_SMALL), (ascii_art::POPOS, ascii_art::POPOS_SMALL), (ascii_art::OPENSUSE, ascii_art::OPENSUSE_SMALL),
+            (ascii_art::ROCKYLINUX, ascii_art::ROCKYLINUX_SMALL), (ascii_art::KALI, ascii_art::KALI_SMALL), (ascii_art::ALMA, ascii_art::ALMA_SMALL),
+            (ascii_art::GARUDA, ascii_art::GARUDA_SMALL), (ascii_art::BAZZITE, ascii_art::BAZZITE_SMALL), (ascii_art::ANDROID, ascii_art::ANDROID_SMALL),
+        ];
+        for (large, small) in logos {
+            for logo in [large, small] {
+                let width: usize = color_markers(logo.0, None).lines().map(|x| x.chars().count()).max().unwrap();
+                assert_eq!(width, logo.1 as usize, "{}", logo.0);
+            }
+            assert!(small.0.lines().count() <= 8, "{}", small.0);
+            assert!(small.0.lines().count() < large.0.lines().count(), "{}", small.0);
+        }
+    }
+    #[test]
+    fn test_logo_colors() {
+        use crate::{ascii::built_in_ascii, ascii_art};
+
+        // Every color set is for a built in logo, and has a color for each marker the logo uses
+        for (os, colors) in ascii_art::LOGO_COLORS {
+            let (large, small) = built_in_ascii(os).unwrap();
+            for logo in [large, small] {
+                assert!(logo.0.trim_start().starts_with("${c"), "{os}");
+                for marker in 1..=6 {
+                    assert!(marker <= colors.len() || !logo.0.contains(&format!("${{c{marker}}}")), "{os}");
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_logo_files() {
+        use crate::{ascii::parse_logo_file, formatter::CrabFetchColor};
+
+        let logo = parse_logo_file("---\ncolors = [\"bright_red\", \"nope\", \"#ffffff\"]\naliases = [\"pop-os\"]\n---\n${c1}ab\n${c2}abcd\n");
+        assert_eq!(logo.ascii, "${c1}ab\n${c2}abcd\n");
+        assert_eq!(logo.width, 4);
+        assert_eq!(logo.colors, Some(vec![CrabFetchColor::BrightRed, CrabFetchColor::Rgb(255, 255, 255)]));
+        assert_eq!(logo.aliases, vec!["pop-os"]);
+
+        // No header, or one that's broken
+        let logo = parse_logo_file("abc\n---\nab");
+        assert_eq!((logo.ascii.as_str(), logo.width, logo.colors), ("abc\n---\nab", 3, None));
+        let logo = parse_logo_file("---\nwidth = [\n---\nab");
+        assert_eq!((logo.ascii.as_str(), logo.width), ("ab", 2));
+        let logo = parse_logo_file("---\nwidth = 10\n---\nab");
+        assert_eq!(logo.width, 10);
+    }
+    #[test]
+    fn test_gradient_color() {
+        use crate::{ascii::gradient_color, formatter::CrabFetchColor};
+
+        let colors: &[CrabFetchColor] = &[CrabFetchColor::Rgb(0, 0, 0), CrabFetchColor::Rgb(200, 100, 0), CrabFetchColor::BrightWhite];
+        assert_eq!(gradient_color(0.0, colors), CrabFetchColor::Rgb(0, 0, 0));
+        assert_eq!(gradient_color(0.25, colors), CrabFetchColor::Rgb(100, 50, 0));
+        assert_eq!(gradient_color(0.5, colors), CrabFetchColor::Rgb(200, 100, 0));
+        assert_eq!(gradient_color(1.0, colors), CrabFetchColor::Rgb(255, 255, 255));
+        assert_eq!(gradient_color(0.75, &[CrabFetchColor::Red, CrabFetchColor::Clear]), CrabFetchColor::Clear);
+    }
 }
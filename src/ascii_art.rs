@@ -1,6 +1,22 @@
+use crate::formatter::CrabFetchColor;
+
 // Define art down below here
 // All distro ASCII's are generated from here; https://www.text-image.com/convert/ascii.html
 // I suck at ASCII art, and want to use smaller ones than the other fetch defaults.
+// The _SMALL ones are hand drawn, for when the full size ones would be too tall.
+//
+// ${c1} to ${c6} mark where the logo switches color, using the distro's colors from LOGO_COLORS
+// below, which go along with the distro's OS color in formatter::OS_COLOR so the logo still matches
+// the titles. Logos without any just get the one OS color.
+pub const LOGO_COLORS: &[(&str, &[CrabFetchColor])] = &[
+    ("debian", &[CrabFetchColor::BrightRed, CrabFetchColor::Red]),
+    ("ubuntu", &[CrabFetchColor::Rgb(233, 84, 32), CrabFetchColor::BrightWhite]),
+    ("void", &[CrabFetchColor::Green, CrabFetchColor::BrightWhite]),
+    ("endeavouros", &[CrabFetchColor::BrightRed, CrabFetchColor::BrightMagenta, CrabFetchColor::BrightBlue]),
+    ("bazzite", &[CrabFetchColor::BrightCyan, CrabFetchColor::Cyan, CrabFetchColor::BrightWhite]),
+    ("android", &[CrabFetchColor::BrightGreen, CrabFetchColor::BrightWhite]),
+];
+
 pub const ARCH: (&str, u16) =  (
 "             ~!
             ^YY^
@@ -15,44 +31,66 @@ pub const ARCH: (&str, u16) =  (
   .?YYYYYJ7      7JJYYYYJ:
  ^JY?7~^..        ..^~7?YY^
 ^7~:                    :~7^", 28);
+pub const ARCH_SMALL: (&str, u16) = (
+"      /\\
+     /  \\
+    /\\   \\
+   /      \\
+  /   ,,   \\
+ /   |  |  -\\
+/_-''    ''-_\\", 14);
 
 pub const DEBIAN: (&str, u16) = (
-"         .^!7!~~~^:
+"${c1}         .^!7!~~~^:
       :!JPPP55YY5555Y?!:
     ^YPG5?!:.    .:~?PGPJ:
    7G57^.            .7PG5~
- .YP7.       :^^^:.    !PY^
- JG7       .!~:....     YP:
-.PY       .?.       .   YP^
-.P?       :Y       .   :P7
-.P?       .??.   .   .~Y!
- JP.      ..~?7~^:^~77!.
- ^PY~        .^^~~^:.
+ .YP7.       ${c2}:^^^:.${c1}    !PY^
+ JG7       ${c2}.!~:....${c1}     YP:
+.PY       ${c2}.?.       .${c1}   YP^
+.P?       ${c2}:Y       .${c1}   :P7
+.P?       ${c2}.??.   .${c1}   .~Y!
+ JP.      ${c2}..~?7~^:^~77!.${c1}
+ ^PY~        ${c2}.^^~~^:.${c1}
   ~PP^
    :YP^
      !Y?^
        ~??~^.
          .^~~:..            ", 28);
+pub const DEBIAN_SMALL: (&str, u16) = (
+"${c1}  _____
+ /  __ \\
+|  /    |
+|  \\___-
+-_
+  --_", 9);
 pub const UBUNTU: (&str, u16) = (
-"           .^~7?JJYYYYJJ?7~^.
+"${c1}           .^~7?JJYYYYJJ?7~^.
         :!?JYYYYYYYYYYYYY55YY?!:
      .~?YYYYYYYYYYYYYYYYY?7?JYYY?~.
     ~JYYYYYYYYYYYYYJJYYY^   .?YYYYJ~
-  .?YYYYYYYYYYY!::.....7!.  :?YYYYYY?.
- .JYYYYYYYY?^.7?. .::.  ^~~~?YYYYYYYYJ.
- ?YYYYYYYY~   .JYJYYYYJ?~.   ~YYYYYYYY?
-~YYYYYYYY^   ~YYYYYYYYYYYY~   ^YYYYYYYY~
-JYYY7^:^7~  ~5YYYYYYYYYYYY5~   7YYYYYYYJ
-JYYJ     J: ?YYYYYYYYYYYYYYJ!77?YYYYYYYJ
-JYYY7^:^7~  ~5YYYYYYYYYYYY5~   7YYYYYYYJ
-~YYYYYYYY^   ~YYYYYYYYYYYY~   ^YYYYYYYY~
- ?YYYYYYYY~   .JYJYYYYJ?~.   ~YYYYYYYY?
- .JYYYYYYYY?^.7?. .::.  ^~~~?YYYYYYYYJ.
-  .?YYYYYYYYYYY!::.....7!.  :?YYYYYY?.
+  .?YYYYYYYYYYY${c2}!::.....7!.${c1}  :?YYYYYY?.
+ .JYYYYYYYY${c2}?^.7?. .::.  ^~~~?${c1}YYYYYYYYJ.
+ ?YYYYYYYY${c2}~   .${c1}JYJYYYYJ${c2}?~.   ~${c1}YYYYYYYY?
+~YYYYYYYY${c2}^   ~${c1}YYYYYYYYYYYY${c2}~   ^${c1}YYYYYYYY~
+JYYY${c2}7^:^7~  ~${c1}5YYYYYYYYYYYY5${c2}~   7${c1}YYYYYYYJ
+JYYJ${c2}     J: ?${c1}YYYYYYYYYYYYYYJ${c2}!77?${c1}YYYYYYYJ
+JYYY${c2}7^:^7~  ~${c1}5YYYYYYYYYYYY5${c2}~   7${c1}YYYYYYYJ
+~YYYYYYYY${c2}^   ~${c1}YYYYYYYYYYYY${c2}~   ^${c1}YYYYYYYY~
+ ?YYYYYYYY${c2}~   .${c1}JYJYYYYJ${c2}?~.   ~${c1}YYYYYYYY?
+ .JYYYYYYYY${c2}?^.7?. .::.  ^~~~?${c1}YYYYYYYYJ.
+  .?YYYYYYYYYYY${c2}!::.....7!.${c1}  :?YYYYYY?.
     ~JYYYYYYYYYYYYYJJYYY^   .?YYYYJ~
      .~?YYYYYYYYYYYYYYYYY?7?JYYY?~.
         :!?JYYYYYYYYYYYYY55YY?!:
            .^~7?JJYYYYJJ?7~^.           ", 40);
+pub const UBUNTU_SMALL: (&str, u16) = (
+"${c1}         _
+     ---(_)
+ _/  ---  \\
+(_) |   |
+  \\  --- _/
+     ---(_)", 11);
 pub const FEDORA: (&str, u16) = ( 
 "      .^7J5PGGGGGPY7~.
     ^JPGGGGGGGP5J??J5PJ^
@@ -67,17 +105,26 @@ GPY55GGGGGB!  !BGGGGGGGGGG!
 GG5Y55Y55J~  :5GGGGGGGBGY^
 PGGP5!     :7PGGGGGGGPJ^
 ~YGGGPYJJY5GGGGGP5J7^.      ", 28);
+pub const FEDORA_SMALL: (&str, u16) = (
+"      _____
+     /   __)\\
+     |  /  \\ \\
+  ___|  |__/ /
+ / (_    _)_/
+/ /  |  |
+\\ \\__/  |
+ \\(_____/", 14);
 
 pub const VOID: (&str, u16) = (
-"             ..::::::::::..             
+"             ${c1}..::::::::::..             
            ::::::::::::::::::.          
             .:::::....::::::::::.       
       !^      .          ..::::::.      
      ?5Y?^                  .::::::     
     75YY5J.       ...         .::::.    
 J555B5YYY:.~7!7Y5YY5PP?::Y557^5PP5YY55Y!
-^B@@@#555?7!G@@&?!!B@@@!G@@#7#@@B7!Y@@@@
- .G@@@#GY. J@@@J!7P@@#JG@@#7#@@#7!J@@@B!
+^B${c2}@@@#${c1}555?7!G${c2}@@&${c1}?!!B${c2}@@@${c1}!G${c2}@@#${c1}7${c2}#@@${c1}B7!Y${c2}@@@@
+ ${c1}.G${c2}@@@#${c1}GY. J${c2}@@@${c1}J!7P${c2}@@#${c1}JG${c2}@@#${c1}7${c2}#@@#${c1}7!J${c2}@@@${c1}B!
   .YBP5YY: :?YYJY5PY7^!5Y5^75Y5YYY55?~  
     75YY5J.        ...        .::::.    
     .?5YY5Y~                  .::::     
@@ -85,20 +132,35 @@ J555B5YYY:.~7!7Y5YY5PP?::Y557^5PP5YY55Y!
        :?Y5555YY?7!!!!7?YYY!.           
          :!JY55555555555555Y?.          
             :~!?JYYYYYYJ?!^:            ", 40);
+pub const VOID_SMALL: (&str, u16) = (
+"${c1}    _______
+ _ \\______ -
+| \\  ___  \\ |
+| | /   \\ | |
+| | \\___/ | |
+| \\______ \\_|
+ -_______\\", 13);
 
 pub const ENDEAVOUR: (&str, u16) = (
 "                            
-              .!J^          
-             ^J555J^        
-           :755555557:      
-         :!J555555555Y!.    
-       :~7Y55555555555Y?^   
-     .~7?55555555555555Y?!. 
-   .^!7J5555555555555555J?7.
-  ^!77Y555555555555555555??!
-:!77?5555555555555555555Y???
+              ${c1}.!J^          
+             ^J${c2}555${c3}J^        
+           ${c1}:7${c2}5555555${c3}7:      
+         ${c1}:!J${c2}555555555${c3}Y!.    
+       ${c1}:~7Y${c2}55555555555${c3}Y?^   
+     ${c1}.~7?${c2}55555555555555${c3}Y?!. 
+   ${c1}.^!7J${c2}5555555555555555${c3}J?7.
+  ${c1}^!77Y${c2}555555555555555555${c3}??!
+${c1}:!77?${c2}5555555555555555555${c3}Y???
 ..:7JJJYYYYYYYYY55555YYJ??7^
   ~777777777777!!!!!~~~^:.  ", 28);
+pub const ENDEAVOUR_SMALL: (&str, u16) = (
+"${c2}       /\\
+     //  \\\\
+   //     \\ \\
+ / /     _) )
+/_/___-- __-
+ /____--", 13);
 
 pub const MINT: (&str, u16) = (
 "              .::^^^^^^::.              
@@ -118,6 +180,14 @@ pub const MINT: (&str, u16) = (
         ^!????????????????????!^        
           .^!7????????????7!^.          
               .::^^^^^^::.              ", 40);
+pub const MINT_SMALL: (&str, u16) = (
+" ___________
+|_          \\
+  | | _____ |
+  | | | | | |
+  | | | | | |
+  | \\_____/ |
+  \\_________/", 13);
 
 pub const ELEMENTARY: (&str, u16) = (
 "         :!J5PPP555PPPP5?~.         
@@ -137,6 +207,13 @@ Y&:   ~@#.            J@^        B@^
     .?G5!:                ^JGP!     
       .!YPPJ7~^:....:^!?YPP?^       
           ^!JY5PPPPPP5Y?~.          ", 36);
+pub const ELEMENTARY_SMALL: (&str, u16) = (
+"  _______
+ / ____  \\
+/  |  /  /\\
+|__\\ /  / |
+\\   /__/  /
+ \\_______/", 11);
 
 pub const ZORIN: (&str, u16) = (
 "        !JJJJJJJJJJJJJJJJJJ!        
@@ -154,6 +231,13 @@ pub const ZORIN: (&str, u16) = (
       .^::::::::::::::::::::^.      
       .7JJJJJJJJJJJJJJJJJJJJ7.      
         !JJJJJJJJJJJJJJJJJJ!        ", 36);
+pub const ZORIN_SMALL: (&str, u16) = (
+" _______
+|____  /
+    / /
+   / /
+  / /__
+ /_____|", 8);
 
 pub const MANJARO: (&str, u16) = (
 "???????????????????????. :J?????????
@@ -173,6 +257,14 @@ pub const MANJARO: (&str, u16) = (
 ?????????J: .??????????. :J?????????
 ?????????J: .??????????. :J?????????
 ?????????J: .??????????. :J?????????", 36);
+pub const MANJARO_SMALL: (&str, u16) = (
+"||||||||| ||||
+||||||||| ||||
+||||      ||||
+|||| |||| ||||
+|||| |||| ||||
+|||| |||| ||||
+|||| |||| ||||", 14);
 
 pub const POPOS: (&str, u16) = (
 "           .:~!!77777777!!~:.           
@@ -194,6 +286,15 @@ pub const POPOS: (&str, u16) = (
      .^7??7!!!!!!!!!!!!!!!!!!7??7^.     
         :~!7????????????????7!~:        
            .:~!!77777777!!~:.           ", 40);
+pub const POPOS_SMALL: (&str, u16) = (
+"______
+\\   _ \\        __
+ \\ \\ \\ \\      / /
+  \\ \\_\\ \\    / /
+   \\  ___\\  /_/
+    \\ \\    _
+   __\\_\\__(_)_
+  (___________)", 17);
 
 pub const OPENSUSE: (&str, u16) = (
 "           ^7YG#&@@@@@@&#GY7^           
@@ -215,6 +316,14 @@ Y@@@@@@@@@@@@@#5?~^~?YPGB##BPJ!~:   :&@Y
      :J#@#Y!.              .~Y#@#J:     
        .~5#@&BPJ7!~~~~!7JPB&@#5~.       
            ^7YG#&@@@@@@&#GY7^           ", 40);
+pub const OPENSUSE_SMALL: (&str, u16) = (
+"  _______
+__|   __ \\
+     / .\\ \\
+     \\__/ |
+   _______|
+   \\_______
+__________/", 11);
 
 pub const ROCKYLINUX: (&str, u16) = (
 "      :!YG#&@@@@&#GY!:      
@@ -230,6 +339,13 @@ pub const ROCKYLINUX: (&str, u16) = (
   ~GG7:   .~5#@@@@@@@@&P^   
    ..   .!P&@@@@@@@@@#5!.   
         ~P#&@@@@&#GY!:      ", 28);
+pub const ROCKYLINUX_SMALL: (&str, u16) = (
+"  .-----.
+ /       \\
+|    /\\   |
+|   /  \\/\\|
+ \\ /      /
+  '-----'", 11);
 
 pub const KALI: (&str, u16) = (
 "   ....::^^~~!!77!~^:                   
@@ -246,6 +362,14 @@ pub const KALI: (&str, u16) = (
                                     !7 ^
                                      !: 
                                      .: ", 40);
+pub const KALI_SMALL: (&str, u16) = (
+"  _______
+ /  ___  `.
+|__/   \\   \\
+        |   |__
+       /  __   `.
+      /  /  `.   \\
+     |  |     \\___\\", 19);
 
 pub const ALMA: (&str, u16) = (
 "       .5&@&G~ ..       ..   ~??~       
@@ -266,6 +390,15 @@ P@@@@GB@@^    :7G&7    P@J  .JG&@#5GBBP!
               ^~7PGGPYG&BY:             
                 B@@@@#..                
                 7B&@B7                  ", 40);
+pub const ALMA_SMALL: (&str, u16) = (
+" .---.    .---.
+/  o  \\  /  o  \\
+\\     /  \\     /
+ `---'    `---'
+    .---.
+   /  o  \\
+   \\     /
+    `---'", 16);
 
 pub const GARUDA: (&str, u16) = (
 "             ^Y#&&&&&&&&&&#J:       
@@ -280,30 +413,45 @@ pub const GARUDA: (&str, u16) = (
     .7G@@#?:         .!G@@#Y^       
        ~P@@&P55555555B@@B?:         
          ^5#&&&&&&&&&&G7.           ", 36);
+pub const GARUDA_SMALL: (&str, u16) = (
+"    _______
+ __/       \\_
+/ /  _____   \\
+| | /   __|  |
+| | \\___  \\  |
+\\  \\____/  _/
+ \\________/", 14);
 
 pub const BAZZITE: (&str, u16) = (
-"     %%%%%%====%%%%%%%%%%            
+"     ${c1}%%%%%%${c3}====${c1}%%%%%%%%%%            
    %%%%%%%%    %%%%%%%%%%%%%%        
   %%%%%%%%%    %%%%%%%%%%%%%%%%      
-  %%%%%%%%%    %%%%%%%%%%%%%%%###    
-  %%%%%%%%%    %%%%%%%%%%%%%######   
-  ==                  =======######  
-  ==                  =========##### 
-  %%%%%%%%%    %%%%%%%####======#####
-  %%%%%%%%%    %%%%%#######=====#####
-  %%%%%%%%%    %%%#########=====#####
-  %%%%%%%%%    %%##########=====#####
-  %%%%%%%%%====###########=====######
-   %%%%%%%%====#########======###### 
-    %%%%%%%=====#####========######  
-     %%%%###===============#######   
-      %#######==========#########    
+  %%%%%%%%%    %%%%%%%%%%%%%%%${c2}###    
+  ${c1}%%%%%%%%%    %%%%%%%%%%%%%${c2}######   
+  ${c3}==                  =======${c2}######  
+  ${c3}==                  =========${c2}##### 
+  ${c1}%%%%%%%%%    %%%%%%%${c2}####${c3}======${c2}#####
+  ${c1}%%%%%%%%%    %%%%%${c2}#######${c3}=====${c2}#####
+  ${c1}%%%%%%%%%    %%%${c2}#########${c3}=====${c2}#####
+  ${c1}%%%%%%%%%    %%${c2}##########${c3}=====${c2}#####
+  ${c1}%%%%%%%%%${c3}====${c2}###########${c3}=====${c2}######
+   ${c1}%%%%%%%%${c3}====${c2}#########${c3}======${c2}###### 
+    ${c1}%%%%%%%${c3}=====${c2}#####${c3}========${c2}######  
+     ${c1}%%%%${c2}###${c3}===============${c2}#######   
+      ${c1}%${c2}#######${c3}==========${c2}#########    
         #######################      
           ###################        
-              ###########           ", 36);
+              ###########           ", 37);
+pub const BAZZITE_SMALL: (&str, u16) = (
+"${c3} ____
+|  _ \\
+| |_) | ____
+|  _ < |_  /
+| |_) | / /
+|____/ /___|", 12);
 
 pub const ANDROID: (&str, u16) = (
-"          .^^  ........  ^:  
+"${c1}          .^^  ........  ^:  
            .~~~!!!!!!!!~~~.   
           :~!!77!!!!!!!!7!!!~:  
       .~!7!!^  !!!!!!!  ^77!^. 
@@ -324,3 +472,10 @@ pub const ANDROID: (&str, u16) = (
            ^7!!!!.  .!!!!7:  
            ^7!!!!.  .!!!!7: 
            .~!!!^   .^!!!^. ", 38);
+pub const ANDROID_SMALL: (&str, u16) = (
+"${c1}  ;,           ,;
+   ';,.-----.,;'
+  ,'           ',
+ /    ${c2}O${c1}     ${c2}O${c1}    \\
+|                 |
+'-----------------'", 19);
@@ -0,0 +1,265 @@
+// Keeps the info of modules that are slow to detect but rarely change on disk between runs
+//
+// Each module gets a file of its own in $XDG_CACHE_HOME/CrabFetch, holding its detected info along
+// with what it was detected under. An entry is only used while it's within the module's TTL and
+// nothing it's invalidated by (e.g the kernel release) has changed since
+
+use std::{collections::HashMap, env, fs, path::{Path, PathBuf}, process, sync::Mutex, time::{SystemTime, UNIX_EPOCH}};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{common_sources::syscalls::SyscallCache, config_manager::Configuration, module::{ModuleOutput, RegisteredModule}, modules, util};
+
+#[derive(Deserialize)]
+pub struct CacheConfiguration {
+    pub enabled: bool,
+    pub ttl: HashMap<String, u64>
+}
+
+// What, besides its TTL, makes a module's cached info stale
+#[derive(Clone, Copy)]
+pub enum CacheSignal {
+    // Hardware info, which is re-detected whenever a different kernel is booted
+    KernelRelease,
+    // Anything depending on what's installed, re-detected when a package database changes
+    PackageDatabases
+}
+
+// How a module's detected info should be cached, given in its registry entry
+pub struct CachePolicy {
+    pub invalidated_by: CacheSignal,
+    // The config the detection depends on, so that changing it re-detects instead of using the old info
+    // None if the config asks for something that can't be cached, e.g the CPU's current clock
+    pub config_key: fn(&Configuration) -> Option<String>,
+    // Turns the cached JSON back into the module's info, see module::restore
+    pub restore: fn(Value) -> Option<Box<dyn ModuleOutput>>
+}
+
+// Everything that changes when a package is installed or removed
+// Only the modified time of these is checked, they're never read
+const PACKAGE_DATABASES: &[&str] = &[
+    "/var/lib/pacman/local",
+    "/var/lib/dpkg/status",
+    "/var/db/xbps/pkgdb-0.38.plist",
+    "/var/lib/rpm/rpmdb.sqlite",
+    "/var/lib/flatpak/app",
+    "/var/lib/flatpak/runtime",
+    "/home/linuxbrew/.linuxbrew/Cellar"
+];
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    // When this was detected, in seconds since the unix epoch
+    stored_at: u64,
+    // What this was detected under; the version of CrabFetch, the root, the signal and the config key
+    // Has to match exactly for the entry to be used
+    key: String,
+    info: Value
+}
+
+pub struct DetectionCache {
+    dir: PathBuf,
+    now: u64,
+    kernel_release: String,
+    package_databases: String
+}
+impl DetectionCache {
+    // Returns None if caching is turned off, or there's nowhere to put the cache
+    pub fn open(config: &Configuration, syscall_cache: &mut SyscallCache) -> Option<Self> {
+        if !config.cache.enabled {
+            return None;
+        }
+
+        Some(Self {
+            dir: find_cache_dir()?,
+            now: unix_time(SystemTime::now()),
+            kernel_release: syscall_cache.get_uname_cached().release,
+            package_databases: PACKAGE_DATABASES.iter()
+                .map(|x| modified_time(&util::root_path(&config.root, x)))
+                .collect::<Vec<String>>()
+                .join(",")
+        })
+    }
+
+    // Brings back a module's info from a previous run, if it's still valid
+    pub fn load(&self, config: &Configuration, module: &RegisteredModule) -> Option<Box<dyn ModuleOutput>> {
+        let policy: &CachePolicy = module.cache.as_ref()?;
+        let ttl: u64 = cache_ttl(config, module.name)?;
+
+        let entry: CacheEntry = serde_json::from_str(&fs::read_to_string(self.dir.join(format!("{}.json", module.name))).ok()?).ok()?;
+        if Some(entry.key) != self.entry_key(config, policy) || self.now.saturating_sub(entry.stored_at) >= ttl {
+            return None;
+        }
+
+        (policy.restore)(entry.info)
+    }
+
+    // Stores a freshly detected module's info for the next runs
+    pub fn store(&self, config: &Configuration, name: &str, output: &dyn ModuleOutput) {
+        let Some(policy) = modules::find_module(name).and_then(|x| x.cache.as_ref()) else {
+            return;
+        };
+        if cache_ttl(config, name).is_none() {
+            return;
+        }
+        let Some(key) = self.entry_key(config, policy) else {
+            return;
+        };
+
+        let entry: CacheEntry = CacheEntry {
+            stored_at: self.now,
+            key,
+            info: output.to_json()
+        };
+        if let Ok(contents) = serde_json::to_string(&entry) {
+            write_cache_file(&self.dir, &format!("{name}.json"), &contents);
+        }
+    }
+
+    // The cache for versions found by versions::find_version, see VersionCache
+    pub fn versions(&self, config: &Configuration) -> Option<VersionCache> {
+        let ttl: u64 = cache_ttl(config, "versions")?;
+        let key: String = format!("{}|{}|{}", env!("CARGO_PKG_VERSION"), config.root, self.package_databases);
+
+        // Anything stored under a different key is thrown out along with anything that's expired
+        let mut stored: HashMap<String, CachedVersion> = fs::read_to_string(self.dir.join("versions.json")).ok()
+            .and_then(|x| serde_json::from_str::<VersionCacheFile>(&x).ok())
+            .filter(|x| x.key == key)
+            .map(|x| x.versions)
+            .unwrap_or_default();
+        stored.retain(|_, x| self.now.saturating_sub(x.stored_at) < ttl);
+
+        Some(VersionCache {
+            dir: self.dir.clone(),
+            now: self.now,
+            key,
+            stored,
+            found: Mutex::new(HashMap::new())
+        })
+    }
+
+    fn entry_key(&self, config: &Configuration, policy: &CachePolicy) -> Option<String> {
+        let signal: &str = match policy.invalidated_by {
+            CacheSignal::KernelRelease => &self.kernel_release,
+            CacheSignal::PackageDatabases => &self.package_databases,
+        };
+        let config_key: String = (policy.config_key)(config)?;
+        Some(format!("{}|{}|{}|{}", env!("CARGO_PKG_VERSION"), config.root, signal, config_key))
+    }
+}
+
+// Versions are cached apart from the modules they're for, as the terminal or shell itself can be
+// different between runs while the version of a given program won't be
+// Each version is keyed by the program's path, and is thrown out if the program's been modified
+#[derive(Serialize, Deserialize)]
+struct VersionCacheFile {
+    key: String,
+    versions: HashMap<String, CachedVersion>
+}
+#[derive(Serialize, Deserialize, Clone)]
+struct CachedVersion {
+    stored_at: u64,
+    modified: String,
+    version: String
+}
+pub struct VersionCache {
+    dir: PathBuf,
+    now: u64,
+    key: String,
+    stored: HashMap<String, CachedVersion>,
+    // Found during this run, as the modules using this run across multiple threads
+    found: Mutex<HashMap<String, CachedVersion>>
+}
+impl VersionCache {
+    pub fn get(&self, exe_path: &str) -> Option<String> {
+        let cached: &CachedVersion = self.stored.get(exe_path)?;
+        if cached.modified != modified_time(Path::new(exe_path)) {
+            return None;
+        }
+
+        Some(cached.version.clone())
+    }
+
+    pub fn insert(&self, exe_path: &str, version: &str) {
+        let Ok(mut found) = self.found.lock() else {
+            return;
+        };
+        found.insert(exe_path.to_string(), CachedVersion {
+            stored_at: self.now,
+            modified: modified_time(Path::new(exe_path)),
+            version: version.to_string()
+        });
+    }
+
+    // Writes out anything new that was found this run
+    pub fn save(&self) {
+        let Ok(found) = self.found.lock() else {
+            return;
+        };
+        if found.is_empty() {
+            return;
+        }
+
+        let mut versions: HashMap<String, CachedVersion> = self.stored.clone();
+        versions.extend(found.iter().map(|(path, version)| (path.clone(), version.clone())));
+        let file: VersionCacheFile = VersionCacheFile {
+            key: self.key.clone(),
+            versions
+        };
+        if let Ok(contents) = serde_json::to_string(&file) {
+            write_cache_file(&self.dir, "versions.json", &contents);
+        }
+    }
+}
+
+fn cache_ttl(config: &Configuration, name: &str) -> Option<u64> {
+    // No TTL, or a TTL of 0, means it's never cached
+    config.cache.ttl.get(name)
+        .copied()
+        .filter(|x| *x != 0)
+}
+
+fn find_cache_dir() -> Option<PathBuf> {
+    // Tries $XDG_CACHE_HOME/CrabFetch before backing up to $HOME/.cache/CrabFetch
+    if let Ok(cache_home) = env::var("XDG_CACHE_HOME") {
+        if !cache_home.is_empty() {
+            return Some(Path::new(&cache_home).join("CrabFetch"));
+        }
+    }
+    if let Ok(user_home) = env::var("HOME") {
+        return Some(Path::new(&user_home).join(".cache/CrabFetch"));
+    }
+
+    None
+}
+
+fn write_cache_file(dir: &Path, file_name: &str, contents: &str) {
+    // Failing to cache isn't worth bothering the user about, it just means detecting again next time
+    if fs::create_dir_all(dir).is_err() {
+        return;
+    }
+    // Written to a temporary file first so that another run reading this never sees half a file
+    let temp_path: PathBuf = dir.join(format!(".{file_name}.{}", process::id()));
+    if fs::write(&temp_path, contents).is_err() {
+        return;
+    }
+    if fs::rename(&temp_path, dir.join(file_name)).is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs())
+}
+
+// The modified time of a path as a string, or "-" if it doesn't exist
+fn modified_time(path: &Path) -> String {
+    match fs::metadata(path).and_then(|x| x.modified()) {
+        Ok(r) => r.duration_since(UNIX_EPOCH)
+            .map(|x| x.as_nanos().to_string())
+            .unwrap_or_default(),
+        Err(_) => "-".to_string(),
+    }
+}
@@ -1,9 +1,9 @@
 // Queries and caches package manager entries to prevent duplicate work between Packages module and
 // Version detection 
 
-use std::{collections::HashMap, ffi::OsStr, fs::{read_dir, DirEntry, File, ReadDir}, io::{BufRead, BufReader}, path::PathBuf};
+use std::{collections::HashMap, ffi::OsStr, fs::{read_dir, DirEntry, File, ReadDir}, io::{BufRead, BufReader}, path::PathBuf, sync::OnceLock};
 
-use crate::config_manager::Configuration;
+use crate::{cache::VersionCache, config_manager::Configuration, util};
 
 pub struct PackageInfo {
     pub name: String,
@@ -21,43 +21,56 @@ impl PackageInfo {
 }
 
 pub struct ManagerInfo {
-    pub available_managers: u8,
-    pub packages: HashMap<String, PackageInfo>
+    root: String,
+    // Only probed once something first asks for it, as everything needing it may have been cached
+    probed: OnceLock<ProbedPackages>,
+    // Versions found in previous runs, only present if caching is enabled
+    pub version_cache: Option<VersionCache>
 }
 impl ManagerInfo {
-    pub fn new() -> Self {
+    pub fn new(config: &Configuration) -> Self {
         Self {
-            available_managers: 0,
-            packages: HashMap::new()
+            root: config.root.clone(),
+            probed: OnceLock::new(),
+            version_cache: None
         }
     }
 
-    pub fn probe_and_cache(&mut self, config: &Configuration) {
-        // Check if we even need to run this 
-        // TODO optimise this with a key/value arr somewhere so we can check modules without
-        // contains's O(n)?
-        if config.modules.contains(&"packages".to_string()) 
-            || (config.modules.contains(&"terminal".to_string()) && config.terminal.format.contains("{version}")) 
-            || (config.modules.contains(&"shell".to_string()) && config.shell.format.contains("{version}")) 
-            || (config.modules.contains(&"editor".to_string()) && config.editor.format.contains("{version}")) 
-            || (config.modules.contains(&"initsys".to_string()) && config.initsys.format.contains("{version}")) 
-        {
-                self.process_pacman_packages();
-                self.process_dpkg_packages();
-                self.process_xbps_packages();
-                self.process_homebrew_packages();
-        }
+    pub fn packages(&self) -> &HashMap<String, PackageInfo> {
+        &self.probe().packages
+    }
+    pub fn available_managers(&self) -> u8 {
+        self.probe().available_managers
     }
 
     pub fn find_all_packages_from(&self, manager: u8) -> HashMap<&String, &PackageInfo> {
-        self.packages.iter()
+        self.packages().iter()
             .filter(|x| x.1.manager & manager > 0)
             .collect()
     }
 
+    fn probe(&self) -> &ProbedPackages {
+        // Anything else asking while this is running waits on it to finish
+        self.probed.get_or_init(|| {
+            let mut probed: ProbedPackages = ProbedPackages::default();
+            probed.process_pacman_packages(&self.root);
+            probed.process_dpkg_packages(&self.root);
+            probed.process_xbps_packages(&self.root);
+            probed.process_homebrew_packages(&self.root);
+            probed
+        })
+    }
+}
+
+#[derive(Default)]
+struct ProbedPackages {
+    available_managers: u8,
+    packages: HashMap<String, PackageInfo>
+}
+impl ProbedPackages {
     // Credit for Pacman, Flatpak and DPKG detection goes to FastFetch, they were big brain while I was running pacman -Q like a dummy
-    fn process_pacman_packages(&mut self) {
-        let dir: ReadDir = match read_dir("/var/lib/pacman/local") {
+    fn process_pacman_packages(&mut self, root: &str) {
+        let dir: ReadDir = match read_dir(util::root_path(root, "/var/lib/pacman/local")) {
             Ok(r) => r,
             Err(_) => return,
         };
@@ -93,13 +106,13 @@ impl ManagerInfo {
 
         self.available_managers += MANAGER_PACMAN;
     }
-    fn process_dpkg_packages(&mut self) {
+    fn process_dpkg_packages(&mut self, root: &str) {
         let file_path: &str = if cfg!(not(feature = "android")) { 
             "/var/lib/dpkg/status"
         } else {
             "/data/data/com.termux/files/usr/var/lib/dpkg/status"
         };
-        let file: File = match File::open(file_path) {
+        let file: File = match File::open(util::root_path(root, file_path)) {
             Ok(r) => r,
             Err(_) => return,
         };
@@ -143,8 +156,8 @@ impl ManagerInfo {
 
         self.available_managers += MANAGER_DPKG;
     }
-    fn process_xbps_packages(&mut self) {
-        let file: File = match File::open("/var/db/xbps/pkgdb-0.38.plist") {
+    fn process_xbps_packages(&mut self, root: &str) {
+        let file: File = match File::open(util::root_path(root, "/var/db/xbps/pkgdb-0.38.plist")) {
             Ok(r) => r,
             Err(_) => return,
         };
@@ -177,6 +190,7 @@ impl ManagerInfo {
                     Some(r) => r.to_string(),
                     None => continue, // Nvm, move on
                 };
+                cur_package_info.name.clone_from(&cur_package);
                 // Nothing else useful here
                 continue
             }
@@ -193,7 +207,10 @@ impl ManagerInfo {
                 dict_level -= 1;
                 if dict_level == 1 {
                     // Exiting package
-                    self.add_package_to_cache(cur_package_info);
+                    // Keys starting with _ are xbps' own metadata, e.g _XBPS_ALTERNATIVES_
+                    if !cur_package.starts_with('_') {
+                        self.add_package_to_cache(cur_package_info);
+                    }
                     cur_package = String::new();
                     cur_package_info = PackageInfo::new("", "", MANAGER_XBPS);
                     next_line_is_key = true;
@@ -229,10 +246,10 @@ impl ManagerInfo {
         self.available_managers += MANAGER_XBPS;
     }
 
-    pub fn process_homebrew_packages(&mut self) {
+    fn process_homebrew_packages(&mut self, root: &str) {
         let homebrew_dirs = vec![
-            PathBuf::from("/home/linuxbrew/.linuxbrew/Cellar"), 
-            PathBuf::from("/home/linuxbrew/.linuxbrew/Caskroom")
+            util::root_path(root, "/home/linuxbrew/.linuxbrew/Cellar"), 
+            util::root_path(root, "/home/linuxbrew/.linuxbrew/Caskroom")
         ];
 
         let homebrew_dirs: Vec<PathBuf> = homebrew_dirs.into_iter().filter(|it| it.exists() && it.is_dir()).collect();
@@ -8,6 +8,7 @@ use libc::{geteuid, getpwuid};
 
 use crate::util;
 
+#[derive(Default)]
 pub struct SyscallCache {
     // https://man7.org/linux/man-pages/man2/sysinfo.2.html
     sysinfo: Option<libc::sysinfo>,
@@ -0,0 +1,704 @@
+// Checks a config for anything that'd be silently ignored or go wrong at runtime, for --check-config
+//
+// The keys a config can have are taken from Configuration's own Deserialize impl (see SchemaProbe)
+// rather than being listed out here, so that new options are picked up without any extra work
+
+use std::{collections::{HashMap, HashSet}, fmt::Display, fs, iter, ops::Range, path::Path, str::FromStr};
+
+use config::{Config, FileFormat};
+use serde::{de::{self, value::{MapDeserializer, StrDeserializer}, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor}, forward_to_deserialize_any, Deserialize, Deserializer};
+use toml_edit::{ImDocument, Item, TableLike};
+
+use crate::{config_manager::{self, ConfigSource, Configuration, ConfigurationError}, formatter::{self, CrabFetchColor}, module::RegisteredModule, modules, template::Template};
+
+// Entries in the module list that aren't registered modules, but are handled while styling
+const BUILTIN_MODULES: &[&str] = &["space", "underline", "segment", "end_segment", "column_break", "colors", "bright_colors", "command"];
+// Modules that are only built in with a feature flag, along with the feature
+const FEATURE_MODULES: &[(&str, &str)] = &[("player", "player")];
+
+// A single problem found in a config, along with the line it's on if it's known
+pub struct ConfigProblem {
+    pub line: Option<usize>,
+    pub message: String
+}
+impl Display for ConfigProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// Checks the config that'd be used with the given location override, see config_manager::find_config
+// Returns where the config came from, along with every problem found in it
+pub fn check_config(location_override: &Option<String>) -> Result<(String, Vec<ConfigProblem>), ConfigurationError> {
+    let (source, contents): (String, String) = match config_manager::find_config(location_override)? {
+        // Nothing to check, it's all defaults
+        ConfigSource::Defaults => return Ok(("the default config".to_string(), Vec::new())),
+        ConfigSource::BuiltinPreset(r) => (location_override.clone().unwrap_or_default(), r.to_string()),
+        ConfigSource::File(r) => match fs::read_to_string(&r) {
+            Ok(contents) => (r, contents),
+            Err(e) => return Err(ConfigurationError::new(Some(r), format!("Unable to read config file: {e}"))),
+        },
+    };
+
+    Ok((source, check_contents(&contents)))
+}
+
+// Checks the contents of a config file, returning every problem found in the order they're in
+pub fn check_contents(contents: &str) -> Vec<ConfigProblem> {
+    let document: ImDocument<&str> = match ImDocument::parse(contents) {
+        Ok(r) => r,
+        Err(e) => return vec![ConfigProblem {
+            line: line_of(contents, e.span()),
+            message: e.message().trim().to_string()
+        }],
+    };
+
+    let mut checker: Checker = Checker {
+        contents,
+        schema: Schema::probe(),
+        palette: palette_names(document.as_table()),
+        problems: Vec::new()
+    };
+    checker.check_table(document.as_table(), "");
+    checker.check_module_list(document.as_table());
+    checker.check_thresholds(document.as_table());
+    checker.check_palette(document.as_table());
+
+    // Anything else, e.g a number where there should be a string, is left to the config crate
+    // Only done if nothing else was found, as most of the above would also make this fail
+    if checker.problems.is_empty() {
+        let builder = Config::builder().add_source(config::File::from_str(contents, FileFormat::Toml));
+        let result: Result<Configuration, String> = config_manager::fill_builder_defaults(builder).build()
+            .map_err(|e| e.to_string())
+            .and_then(config_manager::deserialize);
+        if let Err(e) = result {
+            checker.problems.push(ConfigProblem {
+                line: None,
+                message: e
+            });
+        }
+    }
+
+    checker.problems.sort_by_key(|x| x.line);
+    checker.problems
+}
+
+struct Checker<'a> {
+    contents: &'a str,
+    schema: Schema,
+    // The names of the colors in the palette in use
+    palette: Vec<String>,
+    problems: Vec<ConfigProblem>
+}
+impl Checker<'_> {
+    fn problem(&mut self, span: Option<Range<usize>>, message: String) {
+        self.problems.push(ConfigProblem {
+            line: line_of(self.contents, span),
+            message
+        });
+    }
+
+    fn check_table(&mut self, table: &dyn TableLike, prefix: &str) {
+        for (key, item) in table.iter() {
+            let path: String = join_path(prefix, key);
+            let span: Option<Range<usize>> = table.get_key_value(key)
+                .and_then(|x| x.0.span())
+                .or_else(|| item.span());
+
+            // The section of a module that's not in this build is just left unused
+            if prefix.is_empty() && missing_feature(key).is_some() {
+                continue;
+            }
+            if !self.schema.keys.contains(&path) {
+                let sibling_prefix: String = join_path(prefix, "");
+                let siblings: Vec<&str> = self.schema.keys.iter()
+                    .filter_map(|x| x.strip_prefix(&sibling_prefix))
+                    .filter(|x| !x.contains('.'))
+                    .collect();
+                let message: String = match suggest(key, siblings.into_iter()) {
+                    Some(r) => format!("Unknown key `{path}`, did you mean `{}`?", join_path(prefix, r)),
+                    None => format!("Unknown key `{path}`"),
+                };
+                self.problem(span, message);
+                continue;
+            }
+
+            if path == "palette" {
+                self.check_palette_sections(item);
+                continue;
+            }
+            if let Some(allowed) = map_keys(&path) {
+                self.check_map_keys(item, &path, &allowed);
+                continue;
+            }
+            if let Some(sub_table) = item.as_table_like() {
+                self.check_table(sub_table, &path);
+                continue;
+            }
+            self.check_value(item, &path, prefix);
+        }
+    }
+
+    fn check_map_keys(&mut self, item: &Item, path: &str, allowed: &[&str]) {
+        let Some(table) = item.as_table_like() else {
+            return;
+        };
+        for (key, _) in table.iter() {
+            if allowed.contains(&key) {
+                continue;
+            }
+            let span: Option<Range<usize>> = table.get_key_value(key).and_then(|x| x.0.span());
+            let message: String = match suggest(key, allowed.iter().copied()) {
+                Some(r) => format!("Unknown module `{key}` in `{path}`, did you mean `{r}`?"),
+                None => format!("Unknown module `{key}` in `{path}`"),
+            };
+            self.problem(span, message);
+        }
+    }
+
+    fn check_value(&mut self, item: &Item, path: &str, section: &str) {
+        // Arrays are checked item by item, e.g the band colors
+        let values: Vec<(&str, Option<Range<usize>>)> = match item.as_array() {
+            Some(array) => array.iter()
+                .filter_map(|x| x.as_str().map(|s| (s, x.span())))
+                .collect(),
+            None => item.as_str().map(|x| (x, item.span())).into_iter().collect(),
+        };
+
+        for (value, span) in values {
+            if self.schema.colors.contains(path) {
+                if !self.is_color(value) {
+                    let names: Vec<&str> = formatter::COLOR_NAMES.iter().map(|x| x.0).chain(self.palette.iter().map(String::as_str)).collect();
+                    let message: String = match suggest(value, names.into_iter()) {
+                        Some(r) => format!("Invalid value \"{value}\" for `{path}`, did you mean \"{r}\"?"),
+                        None => format!("Invalid value \"{value}\" for `{path}`, colors can be one of; {}, or \"#rrggbb\", \"rgb(r, g, b)\" or a palette index from 0 to 255", formatter::COLOR_NAMES.iter().map(|x| x.0).collect::<Vec<&str>>().join(", ")),
+                    };
+                    self.problem(span, message);
+                }
+                continue;
+            }
+            if let Some(variants) = self.schema.enums.get(path) {
+                if !variants.iter().any(|x| x.eq_ignore_ascii_case(value)) {
+                    // Lowercased to match how the default config writes them, rather than the enum's own names
+                    let variants: Vec<String> = variants.iter().map(|x| x.to_lowercase()).collect();
+                    let message: String = match suggest(value, variants.iter().map(String::as_str)) {
+                        Some(r) => format!("Invalid value \"{value}\" for `{path}`, did you mean \"{r}\"?"),
+                        None => format!("Invalid value \"{value}\" for `{path}`, it can be one of; {}", variants.join(", ")),
+                    };
+                    self.problem(span, message);
+                }
+                continue;
+            }
+
+            // Titles and formats of modules, anything else can only use color placeholders
+            let key: &str = path.rsplit('.').next().unwrap_or(path);
+            let module: Option<&RegisteredModule> = modules::MODULES.iter()
+                .find(|x| x.name == section || x.name.replace('_', "") == section)
+                .copied();
+            let placeholders: Option<&[&str]> = module.filter(|_| key == "title" || key.ends_with("format"))
+                .map(|x| x.placeholders);
+            self.check_placeholders(value, span.as_ref(), path, module.map(|x| x.name), placeholders);
+        }
+    }
+
+    fn check_placeholders(&mut self, text: &str, span: Option<&Range<usize>>, path: &str, module: Option<&str>, placeholders: Option<&[&str]>) {
+        // These aren't looked for through the template, as colors like #ff0000 aren't valid placeholder names
+        for (start, _) in text.match_indices("{color-") {
+            let Some(length) = text[start..].find('}') else {
+                continue;
+            };
+            let color: &str = &text[start + "{color-".len()..start + length];
+            if color != "title" && !self.is_color(color) {
+                let mut colors: Vec<String> = color_names();
+                colors.extend(self.palette.iter().cloned());
+                let message: String = match suggest(color, colors.iter().map(String::as_str)) {
+                    Some(r) => format!("Unknown color `{{color-{color}}}` in `{path}`, did you mean `{{color-{r}}}`?"),
+                    None => format!("Unknown color `{{color-{color}}}` in `{path}`"),
+                };
+                self.problem(span.cloned(), message);
+            }
+        }
+
+        for placeholder in Template::parse(text).placeholders() {
+            if placeholder.starts_with("color-") {
+                continue;
+            }
+
+            let (Some(module), Some(placeholders)) = (module, placeholders) else {
+                continue;
+            };
+            if placeholders.contains(&placeholder) {
+                continue;
+            }
+            let message: String = if placeholders.is_empty() {
+                format!("Placeholder `{{{placeholder}}}` in `{path}` isn't supported, the {module} module doesn't have any")
+            } else {
+                match suggest(placeholder, placeholders.iter().copied()) {
+                    Some(r) => format!("Placeholder `{{{placeholder}}}` in `{path}` isn't supported by the {module} module, did you mean `{{{r}}}`?"),
+                    None => format!("Placeholder `{{{placeholder}}}` in `{path}` isn't supported by the {module} module, it supports; {}", placeholders.iter().map(|x| format!("{{{x}}}")).collect::<Vec<String>>().join(", ")),
+                }
+            };
+            self.problem(span.cloned(), message);
+        }
+    }
+
+    fn check_module_list(&mut self, root: &dyn TableLike) {
+        let Some(list) = root.get("modules").and_then(Item::as_array) else {
+            return;
+        };
+        let unknown_as_text: bool = root.get("unknown_as_text").and_then(Item::as_bool).unwrap_or(false);
+        let allow_commands: bool = root.get("allow_commands").and_then(Item::as_bool).unwrap_or(false);
+
+        for value in list {
+            let Some(entry) = value.as_str() else {
+                continue;
+            };
+            let (name, argument): (&str, Option<&str>) = match entry.split_once(':') {
+                Some((name, argument)) => (name, Some(argument)),
+                None => (entry, None),
+            };
+
+            let feature: Option<&str> = missing_feature(name);
+            let message: Option<String> = match (name, argument) {
+                ("underline", Some(r)) if r.parse::<usize>().is_ok() => None,
+                ("underline", _) => Some(format!("`{entry}` needs a length to underline with, e.g \"underline:16\"")),
+                ("segment", Some(r)) if !r.is_empty() => None,
+                ("segment", _) => Some(format!("`{entry}` needs a name for the segment, e.g \"segment:Hardware\"")),
+                ("command", Some(r)) if !r.is_empty() => (!allow_commands).then(|| format!("`{entry}` won't be ran as allow_commands is off")),
+                ("command", _) => Some(format!("`{entry}` needs a command to run, e.g \"command:uname -r\"")),
+                _ if BUILTIN_MODULES.contains(&name) || modules::find_module(name).is_some() => None,
+                _ if feature.is_some() => feature.map(|x| format!("`{name}` isn't available, as CrabFetch was built without the {x} feature")),
+                // Shown as-is, so anything goes
+                _ if unknown_as_text => None,
+                _ => {
+                    let known = modules::MODULES.iter().map(|x| x.name).chain(BUILTIN_MODULES.iter().copied());
+                    Some(match suggest(name, known) {
+                        Some(r) => format!("Unknown module `{name}`, did you mean `{r}`?"),
+                        None => format!("Unknown module `{name}`"),
+                    })
+                },
+            };
+            if let Some(message) = message {
+                self.problem(value.span(), message);
+            }
+        }
+    }
+
+    fn check_thresholds(&mut self, root: &dyn TableLike) {
+        let Some(list) = root.get("percentage_color_thresholds").and_then(Item::as_array) else {
+            return;
+        };
+        for value in list {
+            let Some(entry) = value.as_str() else {
+                continue;
+            };
+            let valid: bool = entry.split_once(':')
+                .is_some_and(|(threshold, color)| threshold.parse::<u8>().is_ok() && self.is_color(color));
+            if !valid {
+                self.problem(value.span(), format!("Invalid threshold \"{entry}\" in `percentage_color_thresholds`, these should be \"{{percentage}}:{{color}}\", e.g \"75:brightgreen\""));
+            }
+        }
+    }
+
+    // The palettes can only use colors as they are, rather than each other's names
+    fn check_palette_sections(&mut self, item: &Item) {
+        let Some(palettes) = item.as_table_like() else {
+            return;
+        };
+        for (name, palette) in palettes.iter() {
+            let Some(palette) = palette.as_table_like() else {
+                continue;
+            };
+            for (key, value) in palette.iter() {
+                let Some(color) = value.as_str() else {
+                    continue;
+                };
+                if CrabFetchColor::from_str(color).is_err() {
+                    self.problem(value.span(), format!("Invalid color \"{color}\" for `{key}` in the {name} palette"));
+                }
+            }
+        }
+    }
+
+    fn check_palette(&mut self, root: &dyn TableLike) {
+        let Some(item) = root.get("use_palette") else {
+            return;
+        };
+        let Some(name) = item.as_str() else {
+            return;
+        };
+        let palettes: Vec<&str> = root.get("palette")
+            .and_then(Item::as_table_like)
+            .map(|x| x.iter().map(|(key, _)| key).collect())
+            .unwrap_or_default();
+
+        let Some(path) = config_manager::find_theme_file(name) else {
+            // Config lowercases the keys, so the palette's name can be in any case
+            if !palettes.iter().any(|x| x.eq_ignore_ascii_case(name)) {
+                let message: String = match suggest(name, palettes.into_iter()) {
+                    Some(r) => format!("Unknown palette \"{name}\", did you mean \"{r}\"?"),
+                    None => format!("Unknown palette \"{name}\", it needs a [palette.{name}] section or a themes/{name}.toml file in the config folder"),
+                };
+                self.problem(item.span(), message);
+            }
+            return;
+        };
+        // Problems in the theme file are put against use_palette, as that's the line in this file that uses it
+        let theme: Vec<(String, String)> = match theme_colors(&path) {
+            Ok(r) => r,
+            Err(e) => {
+                self.problem(item.span(), format!("Unable to read theme file {}: {e}", path.display()));
+                return;
+            },
+        };
+        for (key, color) in theme {
+            if CrabFetchColor::from_str(&color).is_err() {
+                self.problem(item.span(), format!("Invalid color \"{color}\" for `{key}` in the theme file {}", path.display()));
+            }
+        }
+    }
+
+    fn is_color(&self, color: &str) -> bool {
+        self.palette.iter().any(|x| x.eq_ignore_ascii_case(color.trim())) || CrabFetchColor::from_str(color).is_ok()
+    }
+}
+
+// The names colors go by in color placeholders, which are the same as the config's without the underscores
+fn color_names() -> Vec<String> {
+    formatter::COLOR_NAMES.iter()
+        .map(|x| x.0.replace('_', ""))
+        .collect()
+}
+
+// The names of the colors in the palette chosen with use_palette, see config_manager::load_palette
+fn palette_names(root: &dyn TableLike) -> Vec<String> {
+    let Some(name) = root.get("use_palette").and_then(Item::as_str) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = root.get("palette")
+        .and_then(Item::as_table_like)
+        .and_then(|x| x.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)))
+        .and_then(|(_, x)| x.as_table_like())
+        .map(|x| x.iter().map(|(key, _)| key.to_lowercase()).collect())
+        .unwrap_or_default();
+    if let Some(theme) = config_manager::find_theme_file(name).and_then(|x| theme_colors(&x).ok()) {
+        names.extend(theme.into_iter().map(|x| x.0.to_lowercase()));
+    }
+    names
+}
+fn theme_colors(path: &Path) -> Result<Vec<(String, String)>, String> {
+    let contents: String = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let theme: ImDocument<&str> = ImDocument::parse(contents.as_str()).map_err(|e| e.message().trim().to_string())?;
+    Ok(theme.iter()
+        .map(|(key, value)| (key.to_string(), value.as_str().map_or_else(|| value.to_string().trim().to_string(), str::to_string)))
+        .collect())
+}
+
+fn missing_feature(module: &str) -> Option<&'static str> {
+    FEATURE_MODULES.iter()
+        .find(|x| x.0 == module && modules::find_module(module).is_none())
+        .map(|x| x.1)
+}
+
+// The keys maps in the config can use, where they're limited
+fn map_keys(path: &str) -> Option<Vec<&'static str>> {
+    match path {
+        "cache.ttl" => Some(modules::MODULES.iter()
+            .filter(|x| x.cache.is_some())
+            .map(|x| x.name)
+            .chain(iter::once("versions"))
+            .collect()),
+        _ => None,
+    }
+}
+
+// Finds the closest match to a misspelled name, if there's one that's close enough to be what was meant
+fn suggest<'a>(name: &str, options: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance: usize = (name.chars().count() / 3).max(1);
+    options.map(|x| (x, strsim::damerau_levenshtein(&name.to_lowercase(), &x.to_lowercase())))
+        .filter(|x| x.1 <= max_distance)
+        .min_by_key(|x| x.1)
+        .map(|x| x.0)
+}
+
+fn line_of(contents: &str, span: Option<Range<usize>>) -> Option<usize> {
+    let start: usize = span?.start;
+    Some(contents.get(..start)?.matches('\n').count() + 1)
+}
+
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+// Every key the config can have, found through SchemaProbe
+#[derive(Default)]
+struct Schema {
+    // Every key as its full dotted path, e.g "cpu.title"
+    keys: HashSet<String>,
+    // Keys that can only be one of a set of values, along with the values
+    enums: HashMap<String, &'static [&'static str]>,
+    // Keys that are a CrabFetchColor
+    colors: HashSet<String>
+}
+impl Schema {
+    fn probe() -> Schema {
+        let mut schema: Schema = Schema::default();
+        // The result's just empty values, it's only the schema that's wanted
+        let _ = Configuration::deserialize(SchemaProbe {
+            path: String::new(),
+            schema: &mut schema
+        });
+        // Module sections aren't part of Configuration's own fields, see module::ConfigSection
+        for module in modules::MODULES {
+            schema.keys.insert(module.name.to_string());
+            schema.keys.insert(format!("{}.timeout_ms", module.name));
+            (module.config.probe)(SchemaProbe {
+                path: module.name.to_string(),
+                schema: &mut schema
+            });
+        }
+        // The timeout for command: modules, see scheduler::run_command_module
+        schema.keys.insert("command".to_string());
+        schema.keys.insert("command.timeout_ms".to_string());
+        schema
+    }
+}
+// Probes the type of a module's config section, see module::ConfigSection
+pub fn probe<T: DeserializeOwned>(probe: SchemaProbe) {
+    let _ = T::deserialize(probe);
+}
+
+// A deserializer that doesn't have any data, but instead notes down the shape of whatever's being
+// deserialized from it; the fields of each struct and the variants of each enum
+// Everything asked of it gets an empty value so that it can carry on to the next field
+pub struct SchemaProbe<'a> {
+    path: String,
+    schema: &'a mut Schema
+}
+impl<'de> Deserializer<'de> for SchemaProbe<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_bool(false)
+    }
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_u8(0)
+    }
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_u16(0)
+    }
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_u32(0)
+    }
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_u64(0)
+    }
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_i8(0)
+    }
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_i16(0)
+    }
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_i32(0)
+    }
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_i64(0)
+    }
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_f32(0.0)
+    }
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_f64(0.0)
+    }
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_char(' ')
+    }
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str("")
+    }
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str("")
+    }
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        // A single item, so that the type it's a list of is noted down too
+        visitor.visit_seq(ProbeSeq {
+            item: Some(self)
+        })
+    }
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(MapDeserializer::new(iter::empty::<(&str, &str)>()))
+    }
+    fn deserialize_struct<V: Visitor<'de>>(self, _: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        for field in fields {
+            self.schema.keys.insert(join_path(&self.path, field));
+        }
+        visitor.visit_map(ProbeStruct {
+            path: self.path,
+            schema: self.schema,
+            fields: fields.iter(),
+            current: ""
+        })
+    }
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        if name == "CrabFetchColor" {
+            self.schema.colors.insert(self.path);
+            let color: StrDeserializer<Self::Error> = "clear".into_deserializer();
+            return visitor.visit_newtype_struct(color);
+        }
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_enum<V: Visitor<'de>>(self, _: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        self.schema.enums.insert(self.path, variants);
+        let variant: StrDeserializer<Self::Error> = variants.first().copied().unwrap_or_default().into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 bytes byte_buf unit unit_struct tuple tuple_struct identifier ignored_any
+    }
+}
+
+struct ProbeSeq<'a> {
+    item: Option<SchemaProbe<'a>>
+}
+impl<'de> SeqAccess<'de> for ProbeSeq<'_> {
+    type Error = de::value::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        match self.item.take() {
+            Some(item) => seed.deserialize(item).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct ProbeStruct<'a> {
+    path: String,
+    schema: &'a mut Schema,
+    fields: std::slice::Iter<'static, &'static str>,
+    current: &'static str
+}
+impl<'de> MapAccess<'de> for ProbeStruct<'_> {
+    type Error = de::value::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        let Some(field) = self.fields.next() else {
+            return Ok(None);
+        };
+        self.current = field;
+        let key: StrDeserializer<Self::Error> = field.into_deserializer();
+        seed.deserialize(key).map(Some)
+    }
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        seed.deserialize(SchemaProbe {
+            path: join_path(&self.path, self.current),
+            schema: self.schema
+        })
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_shipped_configs() {
+        use crate::config_check::check_contents;
+
+        // Everything that ships with CrabFetch should be free of problems
+        let configs: [(&str, &str); 5] = [
+            ("default-config.toml", include_str!("../default-config.toml")),
+            ("presets/default-config.toml", include_str!("../presets/default-config.toml")),
+            ("presets/basic.toml", include_str!("../presets/basic.toml")),
+            ("presets/full.toml", include_str!("../presets/full.toml")),
+            ("presets/neofetch.toml", include_str!("../presets/neofetch.toml")),
+        ];
+        for (name, contents) in configs {
+            let problems: Vec<String> = check_contents(contents).iter()
+                .map(ToString::to_string)
+                // The player module's in the shipped configs, whether or not it's built in
+                .filter(|x| cfg!(feature = "player") || !x.contains("without the player feature"))
+                .collect();
+            assert!(problems.is_empty(), "{name} has problems: {problems:#?}");
+        }
+    }
+
+    #[test]
+    fn test_problems() {
+        use crate::config_check::check_contents;
+
+        let contents: &str = r##"modules = ["cpu", "cpuu", "underline:abc", "segment", "command:uname"]
+seperator = " > "
+title_color = "bright_rd"
+percentage_color_thresholds = ["75:brightgreen", "85-brightyellow"]
+
+[cpu]
+titel = "CPU"
+format = "{name} @ {max_clock}"
+
+[memory]
+title = "{color-brightred}Memory {color-brigthblue}"
+
+[ascii]
+band_colors = ["#ff8800", "rgb(0, 128, 255)", "208", "#ff88"]
+mode = "bandhorizntal"
+size = "huge"
+
+[cache.ttl]
+cpus = 10
+"##;
+        let problems: Vec<String> = check_contents(contents).iter().map(ToString::to_string).collect();
+        assert_eq!(problems, vec![
+            "line 1: Unknown module `cpuu`, did you mean `cpu`?",
+            "line 1: `underline:abc` needs a length to underline with, e.g \"underline:16\"",
+            "line 1: `segment` needs a name for the segment, e.g \"segment:Hardware\"",
+            "line 1: `command:uname` won't be ran as allow_commands is off",
+            "line 2: Unknown key `seperator`, did you mean `separator`?",
+            "line 3: Invalid value \"bright_rd\" for `title_color`, did you mean \"bright_red\"?",
+            "line 4: Invalid threshold \"85-brightyellow\" in `percentage_color_thresholds`, these should be \"{percentage}:{color}\", e.g \"75:brightgreen\"",
+            "line 7: Unknown key `cpu.titel`, did you mean `cpu.title`?",
+            "line 8: Placeholder `{max_clock}` in `cpu.format` isn't supported by the cpu module, it supports; {name}, {core_count}, {thread_count}, {current_clock_mhz}, {current_clock_ghz}, {max_clock_mhz}, {max_clock_ghz}, {arch}",
+            "line 11: Unknown color `{color-brigthblue}` in `memory.title`, did you mean `{color-brightblue}`?",
+            "line 14: Invalid value \"#ff88\" for `ascii.band_colors`, colors can be one of; black, red, green, yellow, blue, magenta, cyan, white, bright_black, bright_red, bright_green, bright_yellow, bright_blue, bright_magenta, bright_cyan, bright_white, clear, or \"#rrggbb\", \"rgb(r, g, b)\" or a palette index from 0 to 255",
+            "line 15: Invalid value \"bandhorizntal\" for `ascii.mode`, did you mean \"bandhorizontal\"?",
+            "line 16: Invalid value \"huge\" for `ascii.size`, it can be one of; auto, small, large",
+            "line 19: Unknown module `cpus` in `cache.ttl`, did you mean `cpu`?",
+        ]);
+    }
+
+    #[test]
+    fn test_palette_problems() {
+        use crate::config_check::check_contents;
+
+        let contents: &str = r##"use_palette = "mocha"
+title_color = "accent"
+[palette.mocha]
+accent = "#cba6f7"
+muted = "mocha_accent"
+[hostname]
+format = "{color-accent}{username}{color-mutde}"
+"##;
+        let problems: Vec<String> = check_contents(contents).iter().map(ToString::to_string).collect();
+        assert_eq!(problems, vec![
+            "line 5: Invalid color \"mocha_accent\" for `muted` in the mocha palette",
+            "line 7: Unknown color `{color-mutde}` in `hostname.format`, did you mean `{color-muted}`?",
+        ]);
+
+        let problems: Vec<String> = check_contents(&contents.replace("\"mocha\"", "\"mocah\"")).iter().map(ToString::to_string).collect();
+        assert_eq!(problems[0], "line 1: Unknown palette \"mocah\", did you mean \"mocha\"?");
+        let problems: Vec<String> = check_contents(&contents.replace("\"mocha\"", "\"Mocha\"")).iter().map(ToString::to_string).collect();
+        assert_eq!(problems.len(), 2);
+    }
+}
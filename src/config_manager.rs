@@ -1,11 +1,9 @@
-use std::{env, fmt::{Debug, Display}, fs::{self, File}, io::Write, path::{Path, PathBuf}};
+use std::{any::TypeId, collections::HashMap, env, fmt::{Debug, Display}, fs::{self, File}, io::Write, iter, path::{Path, PathBuf}, str::FromStr};
 
-use config::{builder::DefaultState, Config, ConfigBuilder};
+use config::{builder::DefaultState, Config, ConfigBuilder, ConfigError};
 use serde::Deserialize;
 
-use crate::{ascii::AsciiConfiguration, battery::BatteryConfiguration, cpu::CPUConfiguration, datetime::DateTimeConfiguration, desktop::DesktopConfiguration, displays::DisplayConfiguration, editor::EditorConfiguration, formatter::CrabFetchColor, gpu::GPUConfiguration, host::HostConfiguration, hostname::HostnameConfiguration, initsys::InitSystemConfiguration, locale::LocaleConfiguration, memory::MemoryConfiguration, modules::{icon_theme::IconThemeConfiguration, localip::LocalIPConfiguration, theme::ThemeConfiguration}, mounts::MountConfiguration, os::OSConfiguration, packages::PackagesConfiguration, preset_configs, processes::ProcessesConfiguration, shell::ShellConfiguration, swap::SwapConfiguration, terminal::TerminalConfiguration, uptime::UptimeConfiguration, util};
-#[cfg(feature = "player")]
-use crate::player::PlayerConfiguration;
+use crate::{ascii::AsciiConfiguration, cache::CacheConfiguration, formatter::{self, CrabFetchColor}, layout::BoxConfiguration, module::Section, modules, preset_configs, scheduler::TimeoutConfiguration, util};
 
 
 #[allow(clippy::struct_excessive_bools)]
@@ -20,11 +18,19 @@ pub struct Configuration {
     pub title_italic: bool,
     pub decimal_places: u32,
     pub inline_values: bool,
+    pub fit_to_terminal: bool,
+    pub ellipsis: String,
+    pub columns: u8,
+    pub column_margin: u16,
     pub underline_character: char,
     pub color_character: String,
     pub color_margin: u8,
     pub color_use_background: bool,
     pub use_os_color: bool,
+    pub use_palette: Option<String>,
+    // Every palette in the config, by name. The one in use also has the colors from its theme file
+    #[serde(default)]
+    pub palette: HashMap<String, HashMap<String, CrabFetchColor>>,
     pub segment_top: String,
     pub segment_bottom: String,
     pub progress_left_border: String,
@@ -35,34 +41,38 @@ pub struct Configuration {
     pub percentage_color_thresholds: Vec<String>,
     pub use_ibis: bool,
     pub suppress_errors: bool,
+    pub root: String,
 
     pub ascii: AsciiConfiguration,
+    // box is a keyword
+    #[serde(rename = "box")]
+    pub boxes: BoxConfiguration,
+    pub cache: CacheConfiguration,
+    pub timeout: TimeoutConfiguration,
+
+    // Each module's own section, by its type. See module::ConfigSection
+    #[serde(skip)]
+    sections: HashMap<TypeId, Section>
+}
 
-    pub hostname: HostnameConfiguration,
-    pub cpu: CPUConfiguration,
-    pub gpu: GPUConfiguration,
-    pub memory: MemoryConfiguration,
-    pub swap: SwapConfiguration,
-    pub mounts: MountConfiguration,
-    pub host: HostConfiguration,
-    pub displays: DisplayConfiguration,
-    pub os: OSConfiguration,
-    pub packages: PackagesConfiguration,
-    pub desktop: DesktopConfiguration,
-    pub terminal: TerminalConfiguration,
-    pub shell: ShellConfiguration,
-    pub uptime: UptimeConfiguration,
-    pub battery: BatteryConfiguration,
-    pub locale: LocaleConfiguration,
-    #[cfg(feature = "player")]
-    pub player: PlayerConfiguration,
-    pub editor: EditorConfiguration,
-    pub initsys: InitSystemConfiguration,
-    pub processes: ProcessesConfiguration,
-    pub datetime: DateTimeConfiguration,
-    pub localip: LocalIPConfiguration,
-    pub theme: ThemeConfiguration,
-    pub icontheme: IconThemeConfiguration
+impl Configuration {
+    // A module's section of the config, e.g config.section::<CPUConfiguration>() for [cpu]
+    pub fn section<T: 'static>(&self) -> &T {
+        self.sections.get(&TypeId::of::<T>())
+            .and_then(|x| x.downcast_ref())
+            .expect("Config section isn't from a registered module")
+    }
+    pub fn section_mut<T: 'static>(&mut self) -> &mut T {
+        self.sections.get_mut(&TypeId::of::<T>())
+            .and_then(|x| x.downcast_mut())
+            .expect("Config section isn't from a registered module")
+    }
+
+    // A color from the palette in use, by its name
+    pub fn palette_color(&self, name: &str) -> Option<&CrabFetchColor> {
+        self.palette.get(self.use_palette.as_ref()?)?
+            .get(&name.trim().to_lowercase())
+    }
 }
 
 // Config Error 
@@ -89,42 +99,52 @@ impl Debug for ConfigurationError {
     }
 }
 
+// Where the config is read from, see find_config
+pub enum ConfigSource {
+    // No config file, so it's all left as the defaults
+    Defaults,
+    File(String),
+    // The contents of one of the presets built into CrabFetch
+    BuiltinPreset(&'static str)
+}
+
+pub fn find_config(location_override: &Option<String>) -> Result<ConfigSource, ConfigurationError> {
+    let Some(location_override) = location_override else {
+        // Find the config path
+        return Ok(find_file_in_config_dir("config.toml").map_or(ConfigSource::Defaults, |x| ConfigSource::File(x.display().to_string())));
+    };
+    if location_override == "none" {
+        return Ok(ConfigSource::Defaults);
+    }
+
+    // Presets
+    if let Some(stripped) = location_override.strip_prefix("preset:") {
+        if let Some(preset) = preset_configs::find_preset(stripped) {
+            return Ok(ConfigSource::BuiltinPreset(preset));
+        }
+        return match find_file_in_config_dir(&format!("presets/{stripped}.toml")) {
+            Some(r) => Ok(ConfigSource::File(r.display().to_string())),
+            None => Err(ConfigurationError::new(None, "Unable to find preset.".to_string())),
+        };
+    }
+
+    let config_path_str: String = shellexpand::tilde(&location_override).to_string();
+    // Verify it exists
+    if !Path::new(&config_path_str).exists() {
+        return Err(ConfigurationError::new(Some(config_path_str), "Unable to find config file.".to_string()));
+    }
+
+    Ok(ConfigSource::File(config_path_str))
+}
+
 #[allow(clippy::ref_option)]
 pub fn parse(location_override: &Option<String>, module_override: &Option<String>) -> Result<Configuration, ConfigurationError> {
     let mut builder: ConfigBuilder<DefaultState> = Config::builder();
-    let mut config_path_str: Option<String> = None;
-    if location_override.is_some() {
-        let location_override: String = location_override.clone().unwrap();
-
-        if location_override != "none" {
-            // Presets
-            if let Some(stripped) = location_override.strip_prefix("preset:") {
-                match stripped {
-                    "full" => return Ok(preset_configs::preset_full()),
-                    "neofetch" => return Ok(preset_configs::preset_neofetch()),
-                    "basic" => return Ok(preset_configs::preset_basic()),
-                    _ => {
-                        config_path_str = find_file_in_config_dir(&format!("presets/{stripped}.toml")).map(|x| x.display().to_string());
-                        if config_path_str.is_none() {
-                            return Err(ConfigurationError::new(None, "Unable to find preset.".to_string()));
-                        }
-                    }
-                }
-            } else {
-                config_path_str = Some(shellexpand::tilde(&location_override).to_string());
-                let config_path_str: String = config_path_str.as_ref().unwrap().to_string();
-
-                // Verify it exists
-                let path: &Path = Path::new(&config_path_str);
-                if !path.exists() {
-                    return Err(ConfigurationError::new(Some(config_path_str), "Unable to find config file.".to_string()));
-                }
-            }
-        }
-    } else {
-        // Find the config path
-        config_path_str = find_file_in_config_dir("config.toml").map(|x| x.display().to_string());
-    }
+    let config_path_str: Option<String> = match find_config(location_override)? {
+        ConfigSource::Defaults => None,
+        ConfigSource::File(r) => Some(r),
+        ConfigSource::BuiltinPreset(r) => return Ok(preset_configs::parse_preset(r)),
+    };
 
     if config_path_str.is_some() {
         builder = builder.add_source(config::File::with_name(config_path_str.as_ref().unwrap()).required(false));
@@ -144,14 +164,88 @@ pub fn parse(location_override: &Option<String>, module_override: &Option<String
         Err(e) => return Err(ConfigurationError::new(config_path_str, e.to_string())),
     };
 
-    let deserialized: Configuration = match config.try_deserialize::<Configuration>() {
+    match deserialize(config) {
+        Ok(r) => Ok(r),
+        Err(e) => Err(ConfigurationError::new(config_path_str, e)),
+    }
+}
+
+// Deserializes the built config, with the colors of the palette it uses able to be used by name
+pub fn deserialize(config: Config) -> Result<Configuration, String> {
+    let palette: Vec<(String, CrabFetchColor)> = load_palette(&config)?;
+    let mut deserialized: Configuration = match formatter::with_palette(&palette, || deserialize_sections(config)) {
         Ok(r) => r,
-        Err(e) => return Err(ConfigurationError::new(config_path_str, e.to_string())),
+        Err(e) => return Err(e.to_string()),
     };
 
+    // Lowercased like the palette's colors, so palette_color finds it whatever case it was given in
+    deserialized.use_palette = deserialized.use_palette.map(|x| x.to_lowercase());
+    if let Some(name) = &deserialized.use_palette {
+        deserialized.palette.insert(name.clone(), palette.into_iter().collect());
+    }
+    Ok(deserialized)
+}
+
+fn deserialize_sections(config: Config) -> Result<Configuration, ConfigError> {
+    let mut sections: HashMap<TypeId, Section> = HashMap::new();
+    let mut timeouts: HashMap<String, u64> = HashMap::new();
+    for module in modules::MODULES {
+        let section: Section = (module.config.deserialize)(&config, module.name)?;
+        sections.insert((*section).type_id(), section);
+    }
+    // Any module can have a timeout_ms in its section, along with "command" for the command: modules
+    for name in modules::MODULES.iter().map(|x| x.name).chain(iter::once("command")) {
+        match config.get::<u64>(&format!("{name}.timeout_ms")) {
+            Ok(r) => { timeouts.insert(name.to_string(), r); },
+            Err(ConfigError::NotFound(_)) => {},
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut deserialized: Configuration = config.try_deserialize()?;
+    deserialized.sections = sections;
+    deserialized.timeout.modules = timeouts;
     Ok(deserialized)
 }
 
+// The colors of the palette chosen with use_palette
+// These come from the config's [palette.name] section, and a themes/name.toml file in the config folder, with
+// the config's taking priority
+pub fn load_palette(config: &Config) -> Result<Vec<(String, CrabFetchColor)>, String> {
+    let Ok(name) = config.get_string("use_palette") else {
+        return Ok(Vec::new());
+    };
+
+    let mut colors: HashMap<String, String> = HashMap::new();
+    let theme_file: Option<PathBuf> = find_theme_file(&name);
+    if let Some(path) = &theme_file {
+        let theme: HashMap<String, String> = Config::builder()
+            .add_source(config::File::from(path.as_path()))
+            .build()
+            .and_then(Config::try_deserialize)
+            .map_err(|e| format!("Unable to read theme file {}: {e}", path.display()))?;
+        colors.extend(theme);
+    }
+    let section: Option<HashMap<String, String>> = config.get(&format!("palette.{name}")).ok();
+    if theme_file.is_none() && section.is_none() {
+        return Err(format!("Unable to find the palette \"{name}\", it needs a [palette.{name}] section or a themes/{name}.toml file in the config folder"));
+    }
+    colors.extend(section.unwrap_or_default());
+
+    colors.into_iter()
+        .map(|(key, value)| match CrabFetchColor::from_str(&value) {
+            Ok(r) => Ok((key.to_lowercase(), r)),
+            Err(()) => Err(format!("Invalid color \"{value}\" for `{key}` in the {name} palette")),
+        })
+        .collect()
+}
+pub fn find_theme_file(name: &str) -> Option<PathBuf> {
+    find_file_in_config_dir(&format!("themes/{name}.toml"))
+}
+pub fn find_logos_dir() -> Option<PathBuf> {
+    find_file_in_config_dir("logos")
+}
+
 fn find_file_in_config_dir(path: &str) -> Option<PathBuf> {
     // Tries $XDG_CONFIG_HOME/CrabFetch before backing up to $HOME/.config/CrabFetch
     let mut paths: Vec<PathBuf> = Vec::new();
@@ -245,6 +339,39 @@ mod tests {
         assert!(removed.is_ok()); // Asserting this cus if the file fails to remove it's likely cus it never existed
     }
     
+    // Test the palette's colors can be used by name, both in the config and afterwards
+    #[test]
+    fn palette_colors() {
+        use config::{Config, FileFormat};
+        use crate::{config_manager::{deserialize, fill_builder_defaults}, formatter::{self, CrabFetchColor}};
+
+        let contents: &str = r##"use_palette = "mocha"
+title_color = "accent"
+[palette.mocha]
+Accent = "#cba6f7"
+warn = "208"
+[palette.latte]
+accent = "#8839ef"
+"##;
+        let builder = fill_builder_defaults(Config::builder().add_source(config::File::from_str(contents, FileFormat::Toml)));
+        let config = deserialize(builder.build().unwrap()).unwrap();
+        assert_eq!(config.title_color, CrabFetchColor::Rgb(203, 166, 247));
+        assert_eq!(formatter::parse_color("warn", &config), Some(CrabFetchColor::Palette(208)));
+        assert_eq!(formatter::parse_color("bright_red", &config), Some(CrabFetchColor::BrightRed));
+        assert_eq!(formatter::parse_color("nope", &config), None);
+
+        // The palette's name is case insensitive, for colors used after the config's read too
+        let mixed_case: &str = &contents.replace("\"mocha\"", "\"Mocha\"");
+        let builder = fill_builder_defaults(Config::builder().add_source(config::File::from_str(mixed_case, FileFormat::Toml)));
+        let config = deserialize(builder.build().unwrap()).unwrap();
+        assert_eq!(config.title_color, CrabFetchColor::Rgb(203, 166, 247));
+        assert_eq!(formatter::parse_color("accent", &config), Some(CrabFetchColor::Rgb(203, 166, 247)));
+
+        let contents: &str = &contents.replace("\"mocha\"", "\"frappe\"");
+        let builder = fill_builder_defaults(Config::builder().add_source(config::File::from_str(contents, FileFormat::Toml)));
+        assert!(deserialize(builder.build().unwrap()).is_err_and(|e| e.starts_with("Unable to find the palette \"frappe\"")));
+    }
+
     // Tests that the default-config.toml file is the same as the DEFAULT_CONFIG_CONTENTS string in
     // here 
     // In case anyone's wondering why they're separated; it's so that package maintainers or people
@@ -344,6 +471,10 @@ pub fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> Config
 
     builder = builder.set_default("decimal_places", 2).unwrap();
     builder = builder.set_default("inline_values", false).unwrap();
+    builder = builder.set_default("fit_to_terminal", true).unwrap();
+    builder = builder.set_default("ellipsis", "…").unwrap();
+    builder = builder.set_default("columns", 1).unwrap();
+    builder = builder.set_default("column_margin", 4).unwrap();
     builder = builder.set_default("underline_character", "―").unwrap();
     builder = builder.set_default("color_character", "   ").unwrap();
     builder = builder.set_default("color_margin", 0).unwrap();
@@ -362,6 +493,7 @@ pub fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> Config
 
     builder = builder.set_default("use_ibis", false).unwrap();
     builder = builder.set_default("suppress_errors", true).unwrap();
+    builder = builder.set_default("root", "/").unwrap();
 
     builder = builder.set_default("percentage_color_thresholds", vec!["75:brightgreen", "85:brightyellow", "90:brightred"]).unwrap();
 
@@ -369,98 +501,38 @@ pub fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> Config
     builder = builder.set_default("ascii.display", true).unwrap();
     builder = builder.set_default("ascii.side", "left").unwrap();
     builder = builder.set_default("ascii.margin", 4).unwrap();
+    builder = builder.set_default("ascii.min_terminal_width", 60).unwrap();
+    builder = builder.set_default("ascii.narrow_side", "top").unwrap();
     builder = builder.set_default("ascii.bold", true).unwrap();
     builder = builder.set_default("ascii.mode", "os").unwrap();
     builder = builder.set_default("ascii.solid_color", "bright_magenta").unwrap();
     builder = builder.set_default("ascii.band_colors", vec!["bright_magenta", "bright_cyan", "bright_white", "bright_cyan", "bright_magenta"]).unwrap();
+    builder = builder.set_default("ascii.image_protocol", "auto").unwrap();
+    builder = builder.set_default("ascii.image_width", 32).unwrap();
+    builder = builder.set_default("ascii.size", "auto").unwrap();
+
+    // Boxes
+    builder = builder.set_default("box.mode", "none").unwrap();
+    builder = builder.set_default("box.style", "rounded").unwrap();
+    builder = builder.set_default("box.color", "white").unwrap();
+    builder = builder.set_default("box.title", "").unwrap();
+    builder = builder.set_default("box.titles_in_border", true).unwrap();
+
+    // Cache
+    builder = builder.set_default("cache.enabled", false).unwrap();
+    builder = builder.set_default("cache.ttl.cpu", 604_800).unwrap();
+    builder = builder.set_default("cache.ttl.gpu", 604_800).unwrap();
+    builder = builder.set_default("cache.ttl.host", 604_800).unwrap();
+    builder = builder.set_default("cache.ttl.packages", 86400).unwrap();
+    builder = builder.set_default("cache.ttl.versions", 86400).unwrap();
+
+    // Timeouts
+    builder = builder.set_default("timeout.deadline_ms", 5000).unwrap();
 
     // Modules
-    builder = builder.set_default("hostname.title", "").unwrap();
-    builder = builder.set_default("hostname.format", "{color-title}{username}{color-white}@{color-title}{hostname}").unwrap();
-
-    builder = builder.set_default("cpu.title", "CPU").unwrap();
-    builder = builder.set_default("cpu.format", "{name} ({core_count}c {thread_count}t) @ {max_clock_ghz} GHz").unwrap();
-    builder = builder.set_default("cpu.remove_trailing_processor", true).unwrap();
-
-    builder = builder.set_default("gpu.amd_accuracy", true).unwrap();
-    builder = builder.set_default("gpu.ignore_disabled_gpus", true).unwrap();
-    builder = builder.set_default("gpu.detect_through_driver", false).unwrap();
-    builder = builder.set_default("gpu.title", "GPU").unwrap();
-    builder = builder.set_default("gpu.format", "{vendor} {model} ({vram})").unwrap();
-
-    builder = builder.set_default("memory.title", "Memory").unwrap();
-    builder = builder.set_default("memory.format", "{used} / {max} ({percent})").unwrap();
-
-    builder = builder.set_default("swap.title", "Swap").unwrap();
-    builder = builder.set_default("swap.format", "{used} / {total} ({percent})").unwrap();
-
-    builder = builder.set_default("mounts.title", "Disk ({mount})").unwrap();
-    builder = builder.set_default("mounts.format", "{space_used} used of {space_total} ({percent}) [{filesystem}]").unwrap();
-    builder = builder.set_default("mounts.ignore", vec![""]).unwrap();
-
-    builder = builder.set_default("host.title", "Host").unwrap();
-    builder = builder.set_default("host.format", "{host} ({chassis})").unwrap();
-    builder = builder.set_default("host.newline_chassis", false).unwrap();
-    builder = builder.set_default("host.chassis_title", "Chassis").unwrap();
-    builder = builder.set_default("host.chassis_format", "{chassis}").unwrap();
-
-    builder = builder.set_default("displays.title", "Display ({make} {model})").unwrap();
-    builder = builder.set_default("displays.format", "{width}x{height} @ {refresh_rate}Hz ({name})").unwrap();
-    builder = builder.set_default("displays.scale_size", false).unwrap();
-
-    builder = builder.set_default("os.title", "Operating System").unwrap();
-    builder = builder.set_default("os.format", "{distro} ({kernel})").unwrap();
-    builder = builder.set_default("os.newline_kernel", false).unwrap();
-    builder = builder.set_default("os.kernel_title", "Kernel").unwrap();
-    builder = builder.set_default("os.kernel_format", "Linux {kernel}").unwrap();
-
-
-    builder = builder.set_default("packages.title", "Packages").unwrap();
-    builder = builder.set_default("packages.format", "{count} ({manager})").unwrap();
-    builder = builder.set_default("packages.ignore", Vec::<String>::new()).unwrap();
-
-    builder = builder.set_default("desktop.title", "Desktop").unwrap();
-    builder = builder.set_default("desktop.format", "{desktop} ({display_type})").unwrap();
-
-    builder = builder.set_default("terminal.title", "Terminal").unwrap();
-    builder = builder.set_default("terminal.format", "{name} {version}").unwrap();
-
-    builder = builder.set_default("shell.title", "Shell").unwrap();
-    builder = builder.set_default("shell.format", "{name} {version}").unwrap();
-    builder = builder.set_default("shell.show_default_shell", "false").unwrap();
-
-    builder = builder.set_default("uptime.title", "Uptime").unwrap();
-
-    builder = builder.set_default("battery.title", "Battery {index}").unwrap();
-    builder = builder.set_default("battery.format", "{percentage}%").unwrap();
-
-    builder = builder.set_default("editor.title", "Editor").unwrap();
-    builder = builder.set_default("editor.format", "{name} {version}").unwrap();
-    builder = builder.set_default("editor.fancy", true).unwrap();
-
-    builder = builder.set_default("locale.title", "Locale").unwrap();
-    builder = builder.set_default("locale.format", "{language} ({encoding})").unwrap();
-
-    builder = builder.set_default("player.title", "Player ({player})").unwrap();
-    builder = builder.set_default("player.format", "{track} by {track_artists} ({album}) [{status}]").unwrap();
-    builder = builder.set_default("player.ignore", Vec::<String>::new()).unwrap();
-
-    builder = builder.set_default("initsys.title", "Init System").unwrap();
-    builder = builder.set_default("initsys.format", "{name} {version}").unwrap();
-
-    builder = builder.set_default("processes.title", "Total Processes").unwrap();
-
-    builder = builder.set_default("datetime.title", "Date/Time").unwrap();
-    builder = builder.set_default("datetime.format", "%H:%M:%S on %e %B %G").unwrap();
-
-    builder = builder.set_default("localip.title", "Local IP ({interface})").unwrap();
-    builder = builder.set_default("localip.format", "{addr}").unwrap();
-
-    builder = builder.set_default("theme.title", "Theme").unwrap();
-    builder = builder.set_default("theme.format", "Gtk3: {gtk3}  Gtk4: {gtk4}").unwrap();
-
-    builder = builder.set_default("icontheme.title", "Icons").unwrap();
-    builder = builder.set_default("icontheme.format", "Gtk3: {gtk3}  Gtk4: {gtk4}").unwrap();
+    for module in modules::MODULES {
+        builder = (module.fill_builder_defaults)(builder);
+    }
 
     builder
 }
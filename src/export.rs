@@ -0,0 +1,285 @@
+// Renders the fetch to a standalone SVG or HTML file, for --export
+// This works off the final frame that'd be printed, parsing the escape codes in it back into styled spans
+use std::{fmt::Write, fs, str::FromStr};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::formatter::{self, CrabFetchColor};
+
+// Roughly a dark terminal's, as anything uncolored is left to the terminal
+const DEFAULT_FOREGROUND: (u8, u8, u8) = (229, 229, 229);
+const DEFAULT_BACKGROUND: (u8, u8, u8) = (24, 24, 24);
+// In pixels, for the SVG's monospace grid
+const FONT_SIZE: f32 = 14.0;
+const CELL_WIDTH: f32 = 8.4;
+const LINE_HEIGHT: f32 = 18.0;
+const PADDING: f32 = 16.0;
+
+#[derive(Debug, PartialEq)]
+pub enum ExportFormat {
+    Svg,
+    Html
+}
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "svg" => Ok(ExportFormat::Svg),
+            "html" => Ok(ExportFormat::Html),
+            _ => Err(format!("Unknown export format \"{s}\", it can be either svg or html.")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
+struct Style {
+    foreground: Option<(u8, u8, u8)>,
+    background: Option<(u8, u8, u8)>,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool
+}
+#[derive(Debug, PartialEq)]
+struct Span {
+    text: String,
+    style: Style
+}
+
+pub fn export(frame: &str, format: &ExportFormat, path: &str) -> Result<(), String> {
+    let lines: Vec<Vec<Span>> = parse_spans(frame);
+    let contents: String = match format {
+        ExportFormat::Svg => render_svg(&lines),
+        ExportFormat::Html => render_html(&lines),
+    };
+    fs::write(path, contents).map_err(|e| format!("Unable to write export to {path}: {e}"))
+}
+
+// Splits the frame into lines of spans, each with the style the escape codes before it left it in
+// Anything that isn't a color/style code, e.g cursor movement, is dropped
+fn parse_spans(frame: &str) -> Vec<Vec<Span>> {
+    let mut lines: Vec<Vec<Span>> = vec![Vec::new()];
+    let mut style: Style = Style::default();
+    let mut text: String = String::new();
+    let mut remaining: &str = frame;
+    while let Some(char) = remaining.chars().next() {
+        if char != '\x1b' && char != '\n' {
+            text.push(char);
+            remaining = &remaining[char.len_utf8()..];
+            continue;
+        }
+
+        // Whatever's come before is in the old style
+        if !text.is_empty() {
+            lines.last_mut().unwrap().push(Span {
+                text: std::mem::take(&mut text),
+                style: style.clone()
+            });
+        }
+        if char == '\n' {
+            lines.push(Vec::new());
+            remaining = &remaining[1..];
+            continue;
+        }
+
+        let length: usize = formatter::escape_length(remaining);
+        if let Some(parameters) = remaining[..length].strip_prefix("\x1b[").and_then(|x| x.strip_suffix('m')) {
+            apply_sgr(&mut style, parameters);
+        }
+        remaining = &remaining[length..];
+    }
+    if !text.is_empty() {
+        lines.last_mut().unwrap().push(Span {text, style});
+    }
+    // The frame ends on a newline, which doesn't need a blank line after it
+    if lines.last().is_some_and(Vec::is_empty) {
+        lines.pop();
+    }
+
+    lines
+}
+
+// Applies a "Select Graphic Rendition" code, e.g the 1;31 of \x1b[1;31m
+fn apply_sgr(style: &mut Style, parameters: &str) {
+    let codes: Vec<u8> = parameters.split(';')
+        .map(|x| x.parse().unwrap_or(0))
+        .collect();
+    let mut codes = codes.into_iter();
+    while let Some(code) = codes.next() {
+        match code {
+            0 => *style = Style::default(),
+            1 => style.bold = true,
+            2 => style.dim = true,
+            3 => style.italic = true,
+            4 => style.underline = true,
+            22 => {
+                style.bold = false;
+                style.dim = false;
+            },
+            23 => style.italic = false,
+            24 => style.underline = false,
+            30..=37 => style.foreground = CrabFetchColor::Palette(code - 30).rgb(),
+            90..=97 => style.foreground = CrabFetchColor::Palette(code - 90 + 8).rgb(),
+            39 => style.foreground = None,
+            40..=47 => style.background = CrabFetchColor::Palette(code - 40).rgb(),
+            100..=107 => style.background = CrabFetchColor::Palette(code - 100 + 8).rgb(),
+            49 => style.background = None,
+            // 256 colors are 38;5;n, 24-bit colors are 38;2;r;g;b
+            38 | 48 => {
+                let color: Option<(u8, u8, u8)> = match codes.next() {
+                    Some(5) => codes.next().and_then(|x| CrabFetchColor::Palette(x).rgb()),
+                    Some(2) => match (codes.next(), codes.next(), codes.next()) {
+                        (Some(r), Some(g), Some(b)) => Some((r, g, b)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if code == 38 {
+                    style.foreground = color;
+                } else {
+                    style.background = color;
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+fn hex(rgb: (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb.0, rgb.1, rgb.2)
+}
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Each span's placed at its column, so wide characters or a font with odd widths can't push the rest out of line
+#[allow(clippy::cast_precision_loss)]
+fn render_svg(lines: &[Vec<Span>]) -> String {
+    let columns: usize = lines.iter()
+        .map(|x| x.iter().map(|span| span.text.width()).sum())
+        .max()
+        .unwrap_or(0);
+    let width: f32 = columns as f32 * CELL_WIDTH + PADDING * 2.0;
+    let height: f32 = lines.len() as f32 * LINE_HEIGHT + PADDING * 2.0;
+
+    let mut svg: String = String::new();
+    let _ = writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">");
+    let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", hex(DEFAULT_BACKGROUND));
+    let _ = writeln!(svg, "<g font-family=\"monospace\" font-size=\"{FONT_SIZE}\" fill=\"{}\" xml:space=\"preserve\">", hex(DEFAULT_FOREGROUND));
+    for (i, line) in lines.iter().enumerate() {
+        let top: f32 = PADDING + i as f32 * LINE_HEIGHT;
+        let mut column: usize = 0;
+        for span in line {
+            let x: f32 = PADDING + column as f32 * CELL_WIDTH;
+            let span_width: usize = span.text.width();
+            column += span_width;
+            if let Some(background) = span.style.background {
+                let _ = writeln!(svg, "<rect x=\"{x}\" y=\"{top}\" width=\"{}\" height=\"{LINE_HEIGHT}\" fill=\"{}\"/>", span_width as f32 * CELL_WIDTH, hex(background));
+            }
+            if span.text.trim().is_empty() {
+                continue;
+            }
+
+            let mut attributes: String = String::new();
+            if let Some(foreground) = span.style.foreground {
+                let _ = write!(attributes, " fill=\"{}\"", hex(foreground));
+            }
+            if span.style.bold {
+                attributes.push_str(" font-weight=\"bold\"");
+            }
+            if span.style.dim {
+                attributes.push_str(" opacity=\"0.6\"");
+            }
+            if span.style.italic {
+                attributes.push_str(" font-style=\"italic\"");
+            }
+            if span.style.underline {
+                attributes.push_str(" text-decoration=\"underline\"");
+            }
+            // Text sits on its baseline, so it's brought down to around 3/4 of the line
+            let _ = writeln!(svg, "<text x=\"{x}\" y=\"{}\"{attributes}>{}</text>", top + LINE_HEIGHT * 0.75, escape_xml(&span.text));
+        }
+    }
+    svg.push_str("</g>\n</svg>\n");
+
+    svg
+}
+
+fn render_html(lines: &[Vec<Span>]) -> String {
+    let mut html: String = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>CrabFetch</title>\n</head>\n");
+    let _ = write!(html, "<body style=\"margin: 0; background-color: {};\">\n<pre style=\"margin: 0; padding: {PADDING}px; font-family: monospace; font-size: {FONT_SIZE}px; line-height: {LINE_HEIGHT}px; color: {};\">", hex(DEFAULT_BACKGROUND), hex(DEFAULT_FOREGROUND));
+    for (i, line) in lines.iter().enumerate() {
+        if i != 0 {
+            html.push('\n');
+        }
+        for span in line {
+            let mut css: Vec<String> = Vec::new();
+            if let Some(foreground) = span.style.foreground {
+                css.push(format!("color: {}", hex(foreground)));
+            }
+            if let Some(background) = span.style.background {
+                css.push(format!("background-color: {}", hex(background)));
+            }
+            if span.style.bold {
+                css.push("font-weight: bold".to_string());
+            }
+            if span.style.dim {
+                css.push("opacity: 0.6".to_string());
+            }
+            if span.style.italic {
+                css.push("font-style: italic".to_string());
+            }
+            if span.style.underline {
+                css.push("text-decoration: underline".to_string());
+            }
+
+            if css.is_empty() {
+                html.push_str(&escape_xml(&span.text));
+            } else {
+                let _ = write!(html, "<span style=\"{}\">{}</span>", css.join("; "), escape_xml(&span.text));
+            }
+        }
+    }
+    html.push_str("</pre>\n</body>\n</html>\n");
+
+    html
+}
+
+mod tests {
+    #[test]
+    fn test_parse_spans() {
+        use crate::export::{parse_spans, Span, Style};
+
+        let span = |text: &str, style: Style| Span {text: text.to_string(), style};
+        let lines = parse_spans("\x1b[1;91mCPU\x1b[0m > Ryzen\n\x1b[38;2;233;84;32mUbuntu\x1b[39m \x1b[3;48;5;196mx\x1b[0m\x1b7\x1b[2A\n");
+        assert_eq!(lines, vec![
+            vec![
+                span("CPU", Style {foreground: Some((255, 0, 0)), bold: true, ..Default::default()}),
+                span(" > Ryzen", Style::default()),
+            ],
+            vec![
+                span("Ubuntu", Style {foreground: Some((233, 84, 32)), ..Default::default()}),
+                span(" ", Style::default()),
+                span("x", Style {background: Some((255, 0, 0)), italic: true, ..Default::default()}),
+            ],
+        ]);
+    }
+    #[test]
+    fn test_render() {
+        use crate::export::{parse_spans, render_html, render_svg};
+
+        let lines = parse_spans("\x1b[32m<ok>\x1b[0m & メモリ\n");
+        assert!(render_html(&lines).contains("<span style=\"color: #00cd00\">&lt;ok&gt;</span> &amp; メモリ</pre>"));
+
+        let svg: String = render_svg(&lines);
+        assert!(svg.contains("width=\"141.2\" height=\"50\""));
+        assert!(svg.contains("<text x=\"16\" y=\"29.5\" fill=\"#00cd00\">&lt;ok&gt;</text>"));
+        assert!(svg.contains("<text x=\"49.6\" y=\"29.5\"> &amp; メモリ</text>"));
+    }
+}
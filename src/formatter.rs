@@ -1,15 +1,16 @@
 // Works in conjunction with ColoredString crate, for now...
-use std::str::FromStr;
+use std::{cell::RefCell, env, fmt, str::FromStr, sync::OnceLock};
 
 use colored::{ColoredString, Colorize};
-use serde::Deserialize;
+use serde::{de::{self, Unexpected, Visitor}, Deserialize, Deserializer};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-use crate::config_manager::Configuration;
+use crate::{config_manager::Configuration, util};
 
 // This is a hack to get the color deserializaton working
 // Essentially it uses my own enum, and to print it you need to call color_string
-#[derive(Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "snake_case")]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CrabFetchColor {
     Black,
     Red,
@@ -27,37 +28,129 @@ pub enum CrabFetchColor {
     BrightMagenta,
     BrightCyan,
     BrightWhite,
-    Clear
+    Clear,
+    // 24-bit color, from "#rrggbb" or "rgb(r, g, b)"
+    Rgb(u8, u8, u8),
+    // An index into the 256 color palette
+    Palette(u8)
 }
+// The names of the 16 colors, in palette order
+// Underscores are optional, which is how they're usually written in color placeholders
+pub const COLOR_NAMES: &[(&str, CrabFetchColor)] = &[
+    ("black", CrabFetchColor::Black),
+    ("red", CrabFetchColor::Red),
+    ("green", CrabFetchColor::Green),
+    ("yellow", CrabFetchColor::Yellow),
+    ("blue", CrabFetchColor::Blue),
+    ("magenta", CrabFetchColor::Magenta),
+    ("cyan", CrabFetchColor::Cyan),
+    ("white", CrabFetchColor::White),
+    ("bright_black", CrabFetchColor::BrightBlack),
+    ("bright_red", CrabFetchColor::BrightRed),
+    ("bright_green", CrabFetchColor::BrightGreen),
+    ("bright_yellow", CrabFetchColor::BrightYellow),
+    ("bright_blue", CrabFetchColor::BrightBlue),
+    ("bright_magenta", CrabFetchColor::BrightMagenta),
+    ("bright_cyan", CrabFetchColor::BrightCyan),
+    ("bright_white", CrabFetchColor::BrightWhite),
+    ("clear", CrabFetchColor::Clear),
+];
+// What the 16 colors look like in xterm, used to find the closest one to a 24-bit color
+const COLOR_VALUES: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
 impl FromStr for CrabFetchColor {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "black" => Ok(CrabFetchColor::Black),
-            "red" => Ok(CrabFetchColor::Red),
-            "green" => Ok(CrabFetchColor::Green),
-            "yellow" => Ok(CrabFetchColor::Yellow),
-            "blue" => Ok(CrabFetchColor::Blue),
-            "magenta" => Ok(CrabFetchColor::Magenta),
-            "cyan" => Ok(CrabFetchColor::Cyan),
-            "white" => Ok(CrabFetchColor::White),
-            "brightblack" => Ok(CrabFetchColor::BrightBlack),
-            "brightred" => Ok(CrabFetchColor::BrightRed),
-            "brightgreen" => Ok(CrabFetchColor::BrightGreen),
-            "brightyellow" => Ok(CrabFetchColor::BrightYellow),
-            "brightblue" => Ok(CrabFetchColor::BrightBlue),
-            "brightmagenta" => Ok(CrabFetchColor::BrightMagenta),
-            "brightcyan" => Ok(CrabFetchColor::BrightCyan),
-            "brightwhite" => Ok(CrabFetchColor::BrightWhite),
-            "clear" => Ok(CrabFetchColor::Clear),
-            _ => Err(())
+        let s: String = s.trim().to_lowercase();
+        if let Some(color) = PALETTE.with(|x| x.borrow().iter().find(|x| x.0 == s).map(|x| x.1.clone())) {
+            return Ok(color);
+        }
+        if let Some(color) = COLOR_NAMES.iter().find(|x| x.0.replace('_', "") == s.replace('_', "")) {
+            return Ok(color.1.clone());
+        }
+
+        if let Some(hex) = s.strip_prefix('#') {
+            if hex.len() != 6 || !hex.chars().all(|x| x.is_ascii_hexdigit()) {
+                return Err(());
+            }
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| ());
+            return Ok(CrabFetchColor::Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+        if let Some(rgb) = s.strip_prefix("rgb(").and_then(|x| x.strip_suffix(')')) {
+            let channels: Vec<u8> = rgb.split(',')
+                .map(|x| x.trim().parse::<u8>())
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|_| ())?;
+            let [r, g, b] = channels[..] else {
+                return Err(());
+            };
+            return Ok(CrabFetchColor::Rgb(r, g, b));
         }
+
+        s.parse::<u8>()
+            .map(CrabFetchColor::Palette)
+            .map_err(|_| ())
+    }
+}
+
+thread_local! {
+    // The colors of the palette in use, so that colors in the config can be given by their name in it
+    // This is only set while the config is being deserialized, see with_palette
+    static PALETTE: RefCell<Vec<(String, CrabFetchColor)>> = const { RefCell::new(Vec::new()) };
+}
+// Runs the function with the palette's colors able to be used by name, see config_manager::deserialize
+pub fn with_palette<T>(palette: &[(String, CrabFetchColor)], f: impl FnOnce() -> T) -> T {
+    PALETTE.with(|x| *x.borrow_mut() = palette.to_vec());
+    let result: T = f();
+    PALETTE.with(|x| x.borrow_mut().clear());
+    result
+}
+// Parses a color used after the config's been read, e.g in a placeholder, which can also be one of the palette's
+pub fn parse_color(color: &str, config: &Configuration) -> Option<CrabFetchColor> {
+    match config.palette_color(color) {
+        Some(r) => Some(r.clone()),
+        None => CrabFetchColor::from_str(color).ok(),
+    }
+}
+
+impl<'de> Deserialize<'de> for CrabFetchColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Asked for as a newtype so that --check-config can tell which values are colors
+        deserializer.deserialize_newtype_struct("CrabFetchColor", ColorVisitor)
+    }
+}
+struct ColorVisitor;
+impl<'de> Visitor<'de> for ColorVisitor {
+    type Value = CrabFetchColor;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a color name, \"#rrggbb\", \"rgb(r, g, b)\" or a palette index from 0 to 255")
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let color: String = String::deserialize(deserializer)?;
+        self.visit_str(&color)
+    }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        CrabFetchColor::from_str(v).map_err(|()| E::invalid_value(Unexpected::Str(v), &self))
+    }
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        u8::try_from(v)
+            .map(CrabFetchColor::Palette)
+            .map_err(|_| E::invalid_value(Unexpected::Unsigned(v), &self))
+    }
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        u8::try_from(v)
+            .map(CrabFetchColor::Palette)
+            .map_err(|_| E::invalid_value(Unexpected::Signed(v), &self))
     }
 }
 impl CrabFetchColor {
     pub fn color_string(&self, string: &str) -> ColoredString {
-        match self {
+        match self.resolve() {
             CrabFetchColor::Black => string.black(),
             CrabFetchColor::Red => string.red(),
             CrabFetchColor::Green => string.green(),
@@ -74,9 +167,83 @@ impl CrabFetchColor {
             CrabFetchColor::BrightMagenta => string.bright_magenta(),
             CrabFetchColor::BrightCyan => string.bright_cyan(),
             CrabFetchColor::BrightWhite => string.bright_white(),
-            CrabFetchColor::Clear => string.clear(),
+            // Written out by hand, as colored does it's own COLORTERM check in truecolor() and would
+            // bring it down to the 16 colors anyway, even after force_truecolor
+            CrabFetchColor::Rgb(r, g, b) if colored::control::SHOULD_COLORIZE.should_colorize() => format!("\x1b[38;2;{r};{g};{b}m{string}\x1b[0m").normal(),
+            CrabFetchColor::Rgb(..) => string.normal(),
+            // resolve() never gives back palette colors
+            CrabFetchColor::Clear | CrabFetchColor::Palette(_) => string.clear(),
+        }
+    }
+
+    // The 24-bit value of the color, going off xterm's values for the 16 colors
+    // Clear has none, as it's whatever the terminal's text is
+    pub fn rgb(&self) -> Option<(u8, u8, u8)> {
+        match self {
+            CrabFetchColor::Clear => None,
+            CrabFetchColor::Rgb(r, g, b) => Some((*r, *g, *b)),
+            CrabFetchColor::Palette(index) => palette_rgb(*index).or_else(|| COLOR_VALUES.get(*index as usize).copied()),
+            _ => COLOR_NAMES.iter().position(|x| &x.1 == self).map(|x| COLOR_VALUES[x]),
         }
     }
+
+    // Turns palette colors into 24-bit ones, and 24-bit ones into the closest of the 16 colors if
+    // the terminal doesn't say it supports them
+    fn resolve(&self) -> CrabFetchColor {
+        let rgb: (u8, u8, u8) = match self {
+            CrabFetchColor::Rgb(r, g, b) => (*r, *g, *b),
+            CrabFetchColor::Palette(index) => match palette_rgb(*index) {
+                Some(r) => r,
+                None => return COLOR_NAMES[*index as usize].1.clone(),
+            },
+            _ => return self.clone(),
+        };
+
+        if truecolor_supported() {
+            CrabFetchColor::Rgb(rgb.0, rgb.1, rgb.2)
+        } else {
+            nearest_color(rgb)
+        }
+    }
+}
+
+static TRUECOLOR_SUPPORTED: OnceLock<bool> = OnceLock::new();
+fn truecolor_supported() -> bool {
+    *TRUECOLOR_SUPPORTED.get_or_init(|| env::var("COLORTERM").is_ok_and(|x| x == "truecolor" || x == "24bit"))
+}
+// For when the output isn't going to a terminal that could be limited, e.g exporting it
+// Has to be done before any colors are used
+pub fn force_truecolor() {
+    let _ = TRUECOLOR_SUPPORTED.set(true);
+}
+// The 24-bit value of a 256 color palette index, or None for the first 16 as those are up to the terminal
+fn palette_rgb(index: u8) -> Option<(u8, u8, u8)> {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    match index {
+        0..=15 => None,
+        16..=231 => {
+            let cube: usize = (index - 16) as usize;
+            Some((LEVELS[cube / 36], LEVELS[(cube / 6) % 6], LEVELS[cube % 6]))
+        },
+        232..=255 => {
+            let gray: u8 = 8 + (index - 232) * 10;
+            Some((gray, gray, gray))
+        },
+    }
+}
+fn nearest_color(rgb: (u8, u8, u8)) -> CrabFetchColor {
+    let distance = |x: &(u8, u8, u8)| -> u32 {
+        let r: u32 = u32::from(x.0.abs_diff(rgb.0));
+        let g: u32 = u32::from(x.1.abs_diff(rgb.1));
+        let b: u32 = u32::from(x.2.abs_diff(rgb.2));
+        r * r + g * g + b * b
+    };
+    let index: usize = COLOR_VALUES.iter()
+        .enumerate()
+        .min_by_key(|(_, x)| distance(x))
+        .map_or(0, |(i, _)| i);
+
+    COLOR_NAMES[index].1.clone()
 }
 
 pub fn replace_color_placeholders(str: &str, config: &Configuration) -> String { 
@@ -92,9 +259,9 @@ pub fn replace_color_placeholders(str: &str, config: &Configuration) -> String {
         };
 
         let color_str: String = s[..len].to_string();
-        let color: CrabFetchColor = match CrabFetchColor::from_str(&color_str) {
-            Ok(r) => r,
-            Err(_) => if color_str == "title" {
+        let color: CrabFetchColor = match parse_color(&color_str, config) {
+            Some(r) => r,
+            None => if color_str == "title" {
                 config.title_color.clone()
             } else {continue},
         };
@@ -104,11 +271,12 @@ pub fn replace_color_placeholders(str: &str, config: &Configuration) -> String {
     new_string
 }
 
-pub fn process_percentage_placeholder(text: &str, percentage: f32, config: &Configuration) -> String {
+// The {percent} placeholder, colored by the percentage_color_thresholds
+pub fn percentage_string(percentage: f32, config: &Configuration) -> String {
     let mut percent_str: String = percentage.to_string();
     percent_str.push('%');
     if config.percentage_color_thresholds.is_empty() {
-        return text.replace("{percent}", &percent_str).to_string();
+        return percent_str;
     }
 
 
@@ -124,12 +292,12 @@ pub fn process_percentage_placeholder(text: &str, percentage: f32, config: &Conf
         if let Ok(threshold) = split[0].parse::<u8>() {
             #[allow(clippy::cast_possible_truncation)]
             if (i16::from(threshold) - percentage as i16) < 0 {
-                cur_color = CrabFetchColor::from_str(split[1]).unwrap_or(CrabFetchColor::Clear);
+                cur_color = parse_color(split[1], config).unwrap_or(CrabFetchColor::Clear);
                 color_assigned = true;
             }
 
             if min_thres > threshold && !color_assigned {
-                min_color = CrabFetchColor::from_str(split[1]).unwrap_or(CrabFetchColor::Clear);
+                min_color = parse_color(split[1], config).unwrap_or(CrabFetchColor::Clear);
                 min_thres = threshold;
             }
         }
@@ -138,8 +306,7 @@ pub fn process_percentage_placeholder(text: &str, percentage: f32, config: &Conf
         cur_color = min_color;
     }
 
-    percent_str = cur_color.color_string(&percent_str.to_string()).to_string();
-    text.replace("{percent}", &percent_str).to_string()
+    cur_color.color_string(&percent_str).to_string()
 }
 
 #[allow(clippy::cast_precision_loss)]
@@ -200,6 +367,96 @@ pub fn make_bar(bar: &mut String, left_border: &str, right_border: &str, progres
     bar.push_str(right_border);
 }
 
+// The terminal's width in columns, or $COLUMNS if we're not outputting to one
+pub fn terminal_columns() -> Option<usize> {
+    if let Some(size) = util::terminal_size() {
+        return Some(size.ws_col as usize);
+    }
+    env::var("COLUMNS").ok().and_then(|x| x.parse().ok()).filter(|x| *x > 0)
+}
+
+// How many columns a string takes up once it's printed, ignoring any escape codes in it
+// Wide characters like CJK take up two, and combining characters or zero width joiners take none
+pub fn display_width(str: &str) -> usize {
+    strip_ansi_escapes::strip_str(str).width()
+}
+
+// Cuts a string down to fit within a width, ending it with the ellipsis if anything had to go
+// Escape codes are kept, including any after the cut so that e.g a hyperlink's still closed, with a
+// reset on the end so the color doesn't carry on past it
+pub fn truncate(str: &str, width: usize, ellipsis: &str) -> String {
+    if display_width(str) <= width {
+        return str.to_string();
+    }
+    // Not even room for the ellipsis, so it's just cut off
+    let ellipsis: &str = if display_width(ellipsis) <= width {ellipsis} else {""};
+    let target_width: usize = width - display_width(ellipsis);
+
+    let mut truncated: String = String::new();
+    let mut current_width: usize = 0;
+    let mut has_escapes: bool = false;
+    let mut cut: bool = false;
+    let mut remaining: &str = str;
+    while !remaining.is_empty() {
+        if remaining.starts_with('\x1b') {
+            // Anything that's already full is cut before the escape, so the ellipsis keeps the style before it
+            if !cut && current_width == target_width {
+                truncated.push_str(ellipsis);
+                cut = true;
+            }
+            let length: usize = escape_length(remaining);
+            truncated.push_str(&remaining[..length]);
+            has_escapes = true;
+            remaining = &remaining[length..];
+            continue;
+        }
+
+        // Whole graphemes only, so a character's never split from what's combined with it
+        let text_length: usize = remaining.find('\x1b').unwrap_or(remaining.len());
+        for grapheme in remaining[..text_length].graphemes(true) {
+            if cut {
+                break;
+            }
+            let grapheme_width: usize = grapheme.width();
+            if current_width + grapheme_width > target_width {
+                truncated.push_str(ellipsis);
+                cut = true;
+                break;
+            }
+            truncated.push_str(grapheme);
+            current_width += grapheme_width;
+        }
+        remaining = &remaining[text_length..];
+    }
+    if has_escapes && !truncated.ends_with("\x1b[0m") {
+        truncated.push_str("\x1b[0m");
+    }
+
+    truncated
+}
+
+// The length in bytes of the escape code at the start of a string
+pub fn escape_length(str: &str) -> usize {
+    let mut chars = str.char_indices().skip(1);
+    match chars.next() {
+        // CSI, e.g colors, ends with anything from @ to ~
+        Some((_, '[')) => chars.find(|x| ('@'..='~').contains(&x.1)).map_or(str.len(), |x| x.0 + 1),
+        // OSC, e.g hyperlinks, ends with a bell or ESC \
+        Some((_, ']')) => {
+            let mut last: char = ']';
+            for (i, x) in chars {
+                if x == '\x07' || (last == '\x1b' && x == '\\') {
+                    return i + x.len_utf8();
+                }
+                last = x;
+            }
+            str.len()
+        },
+        Some((i, x)) => i + x.len_utf8(),
+        None => str.len(),
+    }
+}
+
 
 mod tests {
     // Test bytes format correctly
@@ -248,6 +505,37 @@ mod tests {
         assert_eq!(super::auto_format_bytes(15626234632, true, 2), "14.21 TiB");
         assert_eq!(super::auto_format_bytes(15626234632, true, 3), "14.212 TiB");
     }
+    // Test the 24-bit and palette colors parse, and can be brought down to the 16 colors
+    #[test]
+    fn parse_colors() {
+        use std::str::FromStr;
+        use super::CrabFetchColor;
+
+        assert_eq!(CrabFetchColor::from_str("bright_red"), Ok(CrabFetchColor::BrightRed));
+        assert_eq!(CrabFetchColor::from_str("BrightRed"), Ok(CrabFetchColor::BrightRed));
+        assert_eq!(CrabFetchColor::from_str("#FF8800"), Ok(CrabFetchColor::Rgb(255, 136, 0)));
+        assert_eq!(CrabFetchColor::from_str("rgb(0, 128,255)"), Ok(CrabFetchColor::Rgb(0, 128, 255)));
+        assert_eq!(CrabFetchColor::from_str("208"), Ok(CrabFetchColor::Palette(208)));
+        assert!(CrabFetchColor::from_str("#ff88").is_err());
+        assert!(CrabFetchColor::from_str("rgb(1, 2)").is_err());
+        assert!(CrabFetchColor::from_str("rgb(300, 0, 0)").is_err());
+        assert!(CrabFetchColor::from_str("256").is_err());
+
+        assert_eq!(super::parse_ansi_color("1;34"), Some(CrabFetchColor::Blue));
+        assert_eq!(super::parse_ansi_color("0;38;2;71;128;97"), Some(CrabFetchColor::Rgb(71, 128, 97)));
+        assert_eq!(super::parse_ansi_color("38;5;208"), Some(CrabFetchColor::Palette(208)));
+        assert_eq!(super::parse_ansi_color("1;92"), Some(CrabFetchColor::BrightGreen));
+        assert_eq!(super::parse_ansi_color("1"), None);
+        assert_eq!(super::parse_ansi_color("blue"), None);
+        assert_eq!(super::find_os_color(&["cachyos", "arch"], Some("38;2;8;161;170")), CrabFetchColor::BrightCyan);
+        assert_eq!(super::find_os_color(&["cachyos"], Some("38;2;8;161;170")), CrabFetchColor::Rgb(8, 161, 170));
+        assert_eq!(super::palette_rgb(9), None);
+        assert_eq!(super::palette_rgb(208), Some((255, 135, 0)));
+        assert_eq!(super::palette_rgb(244), Some((128, 128, 128)));
+        assert_eq!(super::nearest_color((250, 10, 10)), CrabFetchColor::BrightRed);
+        assert_eq!(super::nearest_color((190, 20, 10)), CrabFetchColor::Red);
+        assert_eq!(super::nearest_color((10, 10, 10)), CrabFetchColor::Black);
+    }
     // Test progress bars get created correctly
     #[test]
     fn create_progress_bar() {
@@ -298,20 +586,70 @@ mod tests {
         super::make_bar(&mut bar_str, "[", "]", "#", " ", 39.0, 1);
         assert_eq!(bar_str, "");
     }
+    // Test long values are cut down without breaking their escape codes
+    #[test]
+    fn truncate() {
+        assert_eq!(super::truncate("Intel(R) Core(TM) i7", 30, "…"), "Intel(R) Core(TM) i7");
+        assert_eq!(super::truncate("Intel(R) Core(TM) i7", 10, "…"), "Intel(R) …");
+        assert_eq!(super::truncate("Intel(R) Core(TM) i7", 10, "..."), "Intel(R...");
+        assert_eq!(super::truncate("Intel(R) Core(TM) i7", 2, "..."), "In");
+        assert_eq!(super::truncate("\x1b[91mIntel\x1b[0m(R) Core(TM) i7", 6, "…"), "\x1b[91mIntel…\x1b[0m");
+        // The hyperlink's still closed, even though it's after the cut
+        assert_eq!(super::truncate("\x1b]8;;file:///\x1b\\Intel\x1b]8;;\x1b\\ Core", 4, ""), "\x1b]8;;file:///\x1b\\Inte\x1b]8;;\x1b\\\x1b[0m");
+        assert_eq!(super::truncate("\x1b]8;;file:///\x1b\\Intel\x1b]8;;\x1b\\ Core", 5, "…"), "\x1b]8;;file:///\x1b\\Inte…\x1b]8;;\x1b\\\x1b[0m");
+        assert_eq!(super::display_width("\x1b[91mIntel\x1b[0m"), 5);
+
+        // Wide and combining characters
+        assert_eq!(super::display_width("メモリ"), 6);
+        assert_eq!(super::display_width("e\u{301}"), 1);
+        assert_eq!(super::display_width("👩\u{200d}💻"), 2);
+        assert_eq!(super::truncate("メモリ > 8 GB", 6, "…"), "メモ…");
+        assert_eq!(super::truncate("メモリ > 8 GB", 5, "…"), "メモ…");
+        assert_eq!(super::truncate("cafe\u{301} > open", 5, "…"), "cafe\u{301}…");
+        assert_eq!(super::truncate("👩\u{200d}💻 > code", 3, "…"), "👩\u{200d}💻…");
+    }
 }
 
 // A list of OS ID's + Their recommended colors for use_os_color 
-// TODO: Backup to ANSI_COLOR in os-release?
-pub fn find_os_color(os_id: &str) -> CrabFetchColor {
-    for x in OS_COLOR {
-        if x.0 != os_id {
-            continue;
+// Goes through the IDs in order, e.g the distro's own ID then the ones it's based on, falling back to
+// the os-release's ANSI_COLOR if none of them are in the list
+pub fn find_os_color(os_ids: &[&str], ansi_color: Option<&str>) -> CrabFetchColor {
+    for os_id in os_ids {
+        for x in OS_COLOR {
+            if x.0 != *os_id {
+                continue;
+            }
+
+            return x.1.clone();
         }
-        
-        return x.1.clone();
     }
 
-    CrabFetchColor::Clear
+    ansi_color.and_then(parse_ansi_color).unwrap_or(CrabFetchColor::Clear)
+}
+// Turns SGR parameters such as "1;34" or "38;2;23;147;209" into the color they set
+// Anything other than the color, like bold, is ignored
+pub fn parse_ansi_color(sgr: &str) -> Option<CrabFetchColor> {
+    let params: Vec<u8> = sgr.split(';').map(|x| x.trim().parse::<u8>()).collect::<Result<Vec<u8>, _>>().ok()?;
+    let mut color: Option<CrabFetchColor> = None;
+    let mut i: usize = 0;
+    while i < params.len() {
+        match params[i..] {
+            [38, 2, r, g, b, ..] => {
+                color = Some(CrabFetchColor::Rgb(r, g, b));
+                i += 4;
+            },
+            [38, 5, index, ..] => {
+                color = Some(CrabFetchColor::Palette(index));
+                i += 2;
+            },
+            [x @ 30..=37, ..] => color = Some(COLOR_NAMES[(x - 30) as usize].1.clone()),
+            [x @ 90..=97, ..] => color = Some(COLOR_NAMES[(x - 90 + 8) as usize].1.clone()),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    color
 }
 pub const OS_COLOR: &[(&str, CrabFetchColor)] = &[
     ("arch", CrabFetchColor::BrightCyan),
@@ -0,0 +1,489 @@
+// Image logos, drawn with the kitty graphics protocol or sixels in place of the ASCII, or turned into
+// text made of half blocks or braille that goes through the same path as the ASCII
+use std::{env, fmt::Write, fs};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use colored::Colorize;
+use png::{ColorType, Decoder, OutputInfo, Reader, Transformations};
+use serde::Deserialize;
+use zune_jpeg::{zune_core::{colorspace::ColorSpace, options::DecoderOptions}, JpegDecoder};
+
+use crate::{config_manager::Configuration, util};
+
+// Kitty replaces an image when it's sent again with the same ID, so watch mode doesn't pile them up
+const KITTY_IMAGE_ID: u32 = 0x00C7AB;
+// Kitty wants the data in chunks of at most 4096 bytes
+const KITTY_CHUNK_SIZE: usize = 4096;
+// Used when the terminal doesn't tell us how big it's cells are
+const DEFAULT_CELL_SIZE: (u16, u16) = (10, 20);
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const JPEG_SIGNATURE: &[u8] = &[0xFF, 0xD8, 0xFF];
+// Which dot each pixel of a 2x4 braille cell is, by row then column
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy)]
+pub enum ImageProtocol {
+    Auto,
+    Kitty,
+    Sixel,
+    HalfBlock,
+    Braille
+}
+
+pub struct LogoImage {
+    // The size of the image in terminal cells
+    pub columns: u16,
+    pub rows: u16,
+    // The escape codes that actually draw it, at the cursor
+    escape: String
+}
+impl LogoImage {
+    // Draws the image a certain amount of lines above the cursor and columns to the right of the
+    // start of that line, then puts the cursor back
+    pub fn place(&self, lines_up: usize, column: usize) -> String {
+        let mut placement: String = String::from("\x1b7\r");
+        if lines_up > 0 {
+            let _ = write!(placement, "\x1b[{lines_up}A");
+        }
+        if column > 0 {
+            let _ = write!(placement, "\x1b[{column}C");
+        }
+        placement.push_str(&self.escape);
+        placement.push_str("\x1b8");
+        placement
+    }
+}
+
+struct Pixels {
+    width: usize,
+    height: usize,
+    // RGBA, 4 bytes per pixel
+    data: Vec<u8>
+}
+impl Pixels {
+    // The color of a pixel, or None if it's mostly transparent
+    fn rgb(&self, x: usize, y: usize) -> Option<(u8, u8, u8)> {
+        let index: usize = (y * self.width + x) * 4;
+        let pixel: &[u8] = self.data.get(index..index + 4)?;
+        if pixel[3] < 128 {
+            return None;
+        }
+        Some((pixel[0], pixel[1], pixel[2]))
+    }
+}
+
+// Loads the image from the config ready to display, or None if there's none set or the terminal
+// can't show it, in which case it should fall back to the ASCII
+pub fn find_image(config: &Configuration) -> Result<Option<LogoImage>, String> {
+    let Some(ref path) = config.ascii.image else {
+        return Ok(None)
+    };
+    // Piping the output somewhere would just fill it with escape codes
+    if unsafe { libc::isatty(libc::STDOUT_FILENO) } != 1 {
+        return Ok(None);
+    }
+    let protocol: ImageProtocol = match config.ascii.image_protocol {
+        ImageProtocol::Auto => match detect_protocol() {
+            Some(r) => r,
+            None => return Ok(None),
+        },
+        // These are handled by find_image_ascii
+        ImageProtocol::HalfBlock | ImageProtocol::Braille => return Ok(None),
+        x => x,
+    };
+
+    let (file, pixels) = read_image(path)?;
+
+    let columns: u16 = config.ascii.image_width;
+    let (cell_width, cell_height) = cell_size();
+    let rows: u16 = image_rows(columns, (pixels.width, pixels.height), (cell_width, cell_height));
+
+    let escape: String = match protocol {
+        ImageProtocol::Sixel => {
+            let width: usize = usize::from(columns) * usize::from(cell_width);
+            sixel_escape(&scale(&pixels, (width, width * pixels.height / pixels.width.max(1))))
+        },
+        // Everything else has already been narrowed down to kitty by now
+        _ => kitty_escape(&file, &pixels, columns, rows),
+    };
+
+    Ok(Some(LogoImage {
+        columns,
+        rows,
+        escape
+    }))
+}
+
+// The image from the config turned into text, if it's set to use half blocks or braille
+// Returns the text & it's width, same as ascii::find_ascii
+pub fn find_image_ascii(config: &Configuration) -> Result<Option<(String, u16)>, String> {
+    let Some(ref path) = config.ascii.image else {
+        return Ok(None)
+    };
+    // The other protocols are drawn as images, so there's no need to decode it here
+    let to_ascii: fn(&Pixels, u16) -> String = match config.ascii.image_protocol {
+        ImageProtocol::HalfBlock => half_block_ascii,
+        ImageProtocol::Braille => braille_ascii,
+        _ => return Ok(None),
+    };
+    let (_, pixels) = read_image(path)?;
+    let ascii: String = to_ascii(&pixels, config.ascii.image_width);
+    // Fully transparent rows at the top and bottom would just push everything else away
+    let lines: Vec<&str> = ascii.split('\n').collect();
+    let start: usize = lines.iter().position(|x| !x.trim().is_empty()).unwrap_or(lines.len());
+    let end: usize = lines.iter().rposition(|x| !x.trim().is_empty()).map_or(start, |x| x + 1);
+
+    Ok(Some((lines[start..end].join("\n"), config.ascii.image_width)))
+}
+
+// The file's raw contents, alongside it decoded
+fn read_image(path: &str) -> Result<(Vec<u8>, Pixels), String> {
+    let path: String = shellexpand::tilde(path).to_string();
+    let file: Vec<u8> = fs::read(&path).map_err(|e| format!("Unable to read logo image {path}: {e}"))?;
+    let pixels: Pixels = if file.starts_with(PNG_SIGNATURE) {
+        decode_png(&file)
+    } else if file.starts_with(JPEG_SIGNATURE) {
+        decode_jpeg(&file)
+    } else {
+        Err("Only PNG and JPEG images are supported".to_string())
+    }.map_err(|e| format!("Unable to decode logo image {path}: {e}"))?;
+
+    Ok((file, pixels))
+}
+
+// Going off what the terminals tell us about themselves, as actually asking the terminal means
+// waiting on a reply that might never come
+fn detect_protocol() -> Option<ImageProtocol> {
+    let term: String = env::var("TERM").unwrap_or_default();
+    let term_program: String = env::var("TERM_PROGRAM").unwrap_or_default();
+
+    if term == "xterm-kitty" || term == "xterm-ghostty" || env::var("KITTY_WINDOW_ID").is_ok() || env::var("GHOSTTY_RESOURCES_DIR").is_ok()
+        || term_program == "WezTerm" || env::var("KONSOLE_VERSION").is_ok() {
+        return Some(ImageProtocol::Kitty);
+    }
+    if term.starts_with("foot") || term.starts_with("mlterm") || term.starts_with("contour") || term.contains("sixel")
+        || term_program == "iTerm.app" {
+        return Some(ImageProtocol::Sixel);
+    }
+
+    None
+}
+
+// The pixel size of a single terminal cell
+fn cell_size() -> (u16, u16) {
+    match util::terminal_size() {
+        Some(size) if size.ws_xpixel != 0 && size.ws_ypixel != 0 => (size.ws_xpixel / size.ws_col, size.ws_ypixel / size.ws_row),
+        _ => DEFAULT_CELL_SIZE,
+    }
+}
+
+// How many rows of cells the image takes up at a certain width, keeping it's aspect ratio
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn image_rows(columns: u16, image_size: (usize, usize), cell_size: (u16, u16)) -> u16 {
+    if image_size.0 == 0 {
+        return 0;
+    }
+    let height: f64 = f64::from(columns) * f64::from(cell_size.0) * (image_size.1 as f64 / image_size.0 as f64);
+    (height / f64::from(cell_size.1)).ceil().max(1.0) as u16
+}
+
+fn decode_png(png: &[u8]) -> Result<Pixels, String> {
+    let mut decoder: Decoder<&[u8]> = Decoder::new(png);
+    // Gets everything down to 8-bit RGB(A) or grayscale
+    decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+    let mut reader: Reader<&[u8]> = decoder.read_info().map_err(|e| e.to_string())?;
+
+    let mut buffer: Vec<u8> = vec![0; reader.output_buffer_size()];
+    let info: OutputInfo = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+    buffer.truncate(info.buffer_size());
+
+    let data: Vec<u8> = match info.color_type {
+        ColorType::Rgba => buffer,
+        ColorType::Rgb => buffer.chunks_exact(3).flat_map(|x| [x[0], x[1], x[2], 255]).collect(),
+        ColorType::GrayscaleAlpha => buffer.chunks_exact(2).flat_map(|x| [x[0], x[0], x[0], x[1]]).collect(),
+        ColorType::Grayscale => buffer.iter().flat_map(|x| [*x, *x, *x, 255]).collect(),
+        // EXPAND turns these into RGB
+        ColorType::Indexed => return Err("Unexpected indexed colors".to_string()),
+    };
+
+    Ok(Pixels {
+        width: info.width as usize,
+        height: info.height as usize,
+        data
+    })
+}
+
+fn decode_jpeg(jpeg: &[u8]) -> Result<Pixels, String> {
+    let options: DecoderOptions = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::RGBA);
+    let mut decoder: JpegDecoder<&[u8]> = JpegDecoder::new_with_options(jpeg, options);
+    let data: Vec<u8> = decoder.decode().map_err(|e| e.to_string())?;
+    let Some(info) = decoder.info() else {
+        return Err("Missing image info".to_string());
+    };
+
+    Ok(Pixels {
+        width: usize::from(info.width),
+        height: usize::from(info.height),
+        data
+    })
+}
+
+// Resizes the image by averaging the pixels that land in each new one
+fn scale(pixels: &Pixels, size: (usize, usize)) -> Pixels {
+    let (width, height) = size;
+    let mut data: Vec<u8> = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        let y_start: usize = y * pixels.height / height;
+        let y_end: usize = ((y + 1) * pixels.height / height).max(y_start + 1);
+        for x in 0..width {
+            let x_start: usize = x * pixels.width / width;
+            let x_end: usize = ((x + 1) * pixels.width / width).max(x_start + 1);
+
+            let mut total: [usize; 4] = [0; 4];
+            for source_y in y_start..y_end {
+                for source_x in x_start..x_end {
+                    let index: usize = (source_y * pixels.width + source_x) * 4;
+                    for (channel, value) in total.iter_mut().enumerate() {
+                        *value += usize::from(pixels.data[index + channel]);
+                    }
+                }
+            }
+            let count: usize = (y_end - y_start) * (x_end - x_start);
+            data.extend(total.iter().map(|x| u8::try_from(x / count).unwrap_or(u8::MAX)));
+        }
+    }
+
+    Pixels {
+        width,
+        height,
+        data
+    }
+}
+
+// Kitty can take PNGs as-is and scale them to the cells itself, anything else is sent as raw RGBA
+fn kitty_escape(file: &[u8], pixels: &Pixels, columns: u16, rows: u16) -> String {
+    let (format, encoded): (String, String) = if file.starts_with(PNG_SIGNATURE) {
+        ("f=100".to_string(), STANDARD.encode(file))
+    } else {
+        (format!("f=32,s={},v={}", pixels.width, pixels.height), STANDARD.encode(&pixels.data))
+    };
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+
+    let mut escape: String = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more: u8 = u8::from(i + 1 < chunks.len());
+        // Only the first chunk has the actual details of the image
+        if i == 0 {
+            let _ = write!(escape, "\x1b_Ga=T,{format},q=2,C=1,i={KITTY_IMAGE_ID},c={columns},r={rows},m={more};");
+        } else {
+            let _ = write!(escape, "\x1b_Gm={more};");
+        }
+        escape.push_str(&String::from_utf8_lossy(chunk));
+        escape.push_str("\x1b\\");
+    }
+
+    escape
+}
+
+// Sixels are limited to a palette, so this squashes the image into 6 levels of red and blue and 7 of
+// green, which is close enough for a logo
+// Anything mostly transparent is left out, letting the terminal's background through
+fn sixel_escape(pixels: &Pixels) -> String {
+    const RED_LEVELS: usize = 6;
+    const GREEN_LEVELS: usize = 7;
+    const BLUE_LEVELS: usize = 6;
+    let level = |value: u8, levels: usize| -> usize { (usize::from(value) * (levels - 1) + 127) / 255 };
+
+    let colors: Vec<Option<usize>> = pixels.data.chunks_exact(4)
+        .map(|x| {
+            if x[3] < 128 {
+                return None;
+            }
+            Some((level(x[0], RED_LEVELS) * GREEN_LEVELS + level(x[1], GREEN_LEVELS)) * BLUE_LEVELS + level(x[2], BLUE_LEVELS))
+        })
+        .collect();
+
+    // P2 of 1 keeps any pixels we don't draw transparent
+    let mut escape: String = format!("\x1bP0;1;0q\"1;1;{};{}", pixels.width, pixels.height);
+    let mut used: Vec<bool> = vec![false; RED_LEVELS * GREEN_LEVELS * BLUE_LEVELS];
+    colors.iter().flatten().for_each(|x| used[*x] = true);
+    for (color, _) in used.iter().enumerate().filter(|(_, x)| **x) {
+        // Sixel colors are in percentages
+        let percent = |value: usize, levels: usize| -> usize { value * 100 / (levels - 1) };
+        let (red, green, blue) = (color / (GREEN_LEVELS * BLUE_LEVELS), (color / BLUE_LEVELS) % GREEN_LEVELS, color % BLUE_LEVELS);
+        let _ = write!(escape, "#{color};2;{};{};{}", percent(red, RED_LEVELS), percent(green, GREEN_LEVELS), percent(blue, BLUE_LEVELS));
+    }
+
+    // Each sixel is a column of 6 pixels, drawn one color at a time across the band
+    for band_start in (0..pixels.height).step_by(6) {
+        let band_end: usize = (band_start + 6).min(pixels.height);
+        let mut band_colors: Vec<usize> = colors[band_start * pixels.width..band_end * pixels.width].iter().flatten().copied().collect();
+        band_colors.sort_unstable();
+        band_colors.dedup();
+
+        for (i, color) in band_colors.iter().enumerate() {
+            if i != 0 {
+                escape.push('$');
+            }
+            let _ = write!(escape, "#{color}");
+
+            let mut run: (u8, usize) = (0, 0);
+            for x in 0..pixels.width {
+                let mut bits: u8 = 0;
+                for y in band_start..band_end {
+                    if colors[y * pixels.width + x] == Some(*color) {
+                        bits |= 1 << (y - band_start);
+                    }
+                }
+                if bits != run.0 && run.1 > 0 {
+                    push_sixel_run(&mut escape, run);
+                    run.1 = 0;
+                }
+                run = (bits, run.1 + 1);
+            }
+            push_sixel_run(&mut escape, run);
+        }
+        escape.push('-');
+    }
+    escape.push_str("\x1b\\");
+
+    escape
+}
+fn push_sixel_run(escape: &mut String, run: (u8, usize)) {
+    let character: char = char::from(63 + run.0);
+    if run.1 > 3 {
+        let _ = write!(escape, "!{}{character}", run.1);
+    } else {
+        (0..run.1).for_each(|_| escape.push(character));
+    }
+}
+
+// Each cell is two pixels, the top one as the text color and the bottom as the background
+// Cells are about twice as tall as they are wide, so this keeps the pixels square
+fn half_block_ascii(pixels: &Pixels, columns: u16) -> String {
+    let width: usize = usize::from(columns);
+    let height: usize = (width * pixels.height / pixels.width.max(1)).max(1);
+    let scaled: Pixels = scale(pixels, (width, height));
+
+    let mut lines: Vec<String> = Vec::new();
+    for y in (0..height).step_by(2) {
+        let mut line: String = String::new();
+        for x in 0..width {
+            let bottom: Option<(u8, u8, u8)> = if y + 1 < height { scaled.rgb(x, y + 1) } else { None };
+            match (scaled.rgb(x, y), bottom) {
+                (Some(top), Some(bottom)) => line.push_str(&"\u{2580}".truecolor(top.0, top.1, top.2).on_truecolor(bottom.0, bottom.1, bottom.2).to_string()),
+                (Some(top), None) => line.push_str(&"\u{2580}".truecolor(top.0, top.1, top.2).to_string()),
+                (None, Some(bottom)) => line.push_str(&"\u{2584}".truecolor(bottom.0, bottom.1, bottom.2).to_string()),
+                (None, None) => line.push(' '),
+            }
+        }
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+// Each cell is 2x4 dots, colored by the average of the dots that are drawn
+// Images with transparency use that for which dots to draw, otherwise it's anything brighter than
+// the image's average
+fn braille_ascii(pixels: &Pixels, columns: u16) -> String {
+    let width: usize = usize::from(columns) * 2;
+    let height: usize = (width * pixels.height / pixels.width.max(1)).max(1);
+    let scaled: Pixels = scale(pixels, (width, height));
+
+    let luminance = |x: (u8, u8, u8)| -> u32 { (299 * u32::from(x.0) + 587 * u32::from(x.1) + 114 * u32::from(x.2)) / 1000 };
+    let colors: Vec<Option<(u8, u8, u8)>> = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| scaled.rgb(x, y))
+        .collect();
+    let threshold: u32 = if colors.iter().any(Option::is_none) {
+        0
+    } else {
+        let total: usize = colors.iter().flatten().map(|x| luminance(*x) as usize).sum();
+        u32::try_from(total / colors.len().max(1)).unwrap_or(0)
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+    for y in (0..height).step_by(4) {
+        let mut line: String = String::new();
+        for x in (0..width).step_by(2) {
+            let mut dots: u32 = 0;
+            let mut total: (u32, u32, u32, u32) = (0, 0, 0, 0);
+            for (dot_y, row) in BRAILLE_DOTS.iter().enumerate() {
+                for (dot_x, dot) in row.iter().enumerate() {
+                    let Some(Some(color)) = colors.get((y + dot_y) * width + x + dot_x).filter(|_| y + dot_y < height) else {
+                        continue
+                    };
+                    if luminance(*color) < threshold {
+                        continue;
+                    }
+                    dots |= dot;
+                    total = (total.0 + u32::from(color.0), total.1 + u32::from(color.1), total.2 + u32::from(color.2), total.3 + 1);
+                }
+            }
+
+            let Some(character) = char::from_u32(0x2800 + dots).filter(|_| dots != 0) else {
+                line.push(' ');
+                continue
+            };
+            let average = |x: u32| -> u8 { u8::try_from(x / total.3).unwrap_or(u8::MAX) };
+            line.push_str(&character.to_string().truecolor(average(total.0), average(total.1), average(total.2)).to_string());
+        }
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+mod tests {
+    #[test]
+    fn test_image_rows() {
+        use crate::image::image_rows;
+
+        // Square image in cells twice as tall as they are wide
+        assert_eq!(image_rows(32, (100, 100), (10, 20)), 16);
+        assert_eq!(image_rows(32, (200, 100), (10, 20)), 8);
+        assert_eq!(image_rows(10, (3, 1000), (8, 16)), 1667);
+        assert_eq!(image_rows(10, (0, 0), (8, 16)), 0);
+    }
+    #[test]
+    fn test_sixel_escape() {
+        use crate::image::{sixel_escape, Pixels};
+
+        // 2x1, a red pixel and a transparent one
+        let pixels: Pixels = Pixels {
+            width: 2,
+            height: 1,
+            data: vec![255, 0, 0, 255, 0, 0, 0, 0]
+        };
+        assert_eq!(sixel_escape(&pixels), "\x1bP0;1;0q\"1;1;2;1#210;2;100;0;0#210@?-\x1b\\");
+    }
+    #[test]
+    fn test_text_ascii() {
+        use crate::image::{braille_ascii, half_block_ascii, Pixels};
+
+        colored::control::set_override(false);
+        // 2x4, the top left and bottom right pixels transparent
+        let mut data: Vec<u8> = [255, 255, 255, 255].repeat(8);
+        data[3] = 0;
+        data[31] = 0;
+        let pixels: Pixels = Pixels {
+            width: 2,
+            height: 4,
+            data
+        };
+        assert_eq!(half_block_ascii(&pixels, 2), "\u{2584}\u{2580}\n\u{2580}\u{2580}");
+        assert_eq!(braille_ascii(&pixels, 1), "\u{287E}");
+    }
+    // Images drawn through a protocol shouldn't be read just to be turned into text
+    #[test]
+    fn test_image_ascii_protocol() {
+        use crate::{config_manager, image::{find_image_ascii, ImageProtocol}};
+
+        let mut config = config_manager::parse(&Some("none".to_string()), &None).unwrap();
+        config.ascii.image = Some("/nonexistent/logo.png".to_string());
+        config.ascii.image_protocol = ImageProtocol::Kitty;
+        assert_eq!(find_image_ascii(&config), Ok(None));
+        config.ascii.image_protocol = ImageProtocol::HalfBlock;
+        assert!(find_image_ascii(&config).is_err());
+    }
+}
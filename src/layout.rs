@@ -0,0 +1,387 @@
+// Laying the info block out into columns, and drawing boxes around it or each segment in it
+// These are done once the lines are styled and fit to the terminal, as that's the only point the
+// widths of the lines are known
+use std::ops::Range;
+
+use serde::Deserialize;
+
+use crate::{config_manager::Configuration, formatter::{self, CrabFetchColor}};
+
+#[derive(Deserialize)]
+pub struct BoxConfiguration {
+    pub mode: BoxMode,
+    pub style: BoxStyle,
+    pub color: CrabFetchColor,
+    // Shown in the info box's top border, can have color placeholders
+    pub title: String,
+    // Puts segment names in their box's top border, rather than on a line inside it
+    pub titles_in_border: bool
+}
+#[derive(Debug, Deserialize, PartialEq)]
+pub enum BoxMode {
+    None,
+    Info,
+    Segments
+}
+#[derive(Debug, Deserialize, PartialEq)]
+pub enum BoxStyle {
+    Single,
+    Double,
+    Rounded,
+    Heavy
+}
+impl BoxStyle {
+    // Top left, top right, bottom left, bottom right, horizontal and vertical
+    fn characters(&self) -> [char; 6] {
+        match self {
+            BoxStyle::Single => ['┌', '┐', '└', '┘', '─', '│'],
+            BoxStyle::Double => ['╔', '╗', '╚', '╝', '═', '║'],
+            BoxStyle::Rounded => ['╭', '╮', '╰', '╯', '─', '│'],
+            BoxStyle::Heavy => ['┏', '┓', '┗', '┛', '━', '┃'],
+        }
+    }
+}
+
+// Where a segment is in the styled lines
+pub struct Segment {
+    pub name: String,
+    // The lines of the modules in it
+    pub lines: Range<usize>,
+    // Whether its segment line is just before them, which is only missing when it's carried on from another column
+    pub has_start_line: bool,
+    // Whether there's an end_segment line just after them, rather than it running into the next segment or the end
+    pub has_end_line: bool
+}
+
+// Where the pseudo-modules that change the layout are in the styled lines
+#[derive(Default)]
+pub struct LineMarkers {
+    pub segments: Vec<Segment>,
+    // The first line of each column after the first
+    pub column_breaks: Vec<usize>,
+    segment_open: bool
+}
+impl LineMarkers {
+    // Starts a segment with its segment line at the line, ending whichever one's still open there
+    pub fn start_segment(&mut self, name: &str, line: usize) {
+        self.end_segment(line, false);
+        self.segments.push(Segment {
+            name: name.to_string(),
+            lines: line + 1..line + 1,
+            has_start_line: true,
+            has_end_line: false
+        });
+        self.segment_open = true;
+    }
+    // Ends the open segment, if there is one, just before the line
+    pub fn end_segment(&mut self, line: usize, has_end_line: bool) {
+        if !self.segment_open {
+            return;
+        }
+        if let Some(segment) = self.segments.last_mut() {
+            segment.lines.end = line;
+            segment.has_end_line = has_end_line;
+        }
+        self.segment_open = false;
+    }
+
+    // The segments within some of the lines, relative to the start of them
+    // One that crosses into or out of them is cut off there, so it gets a box in each column it's in
+    pub fn segments_in(&self, lines: &Range<usize>) -> Vec<Segment> {
+        self.segments.iter()
+            .filter(|x| {
+                let first_line: usize = x.lines.start - usize::from(x.has_start_line);
+                let last_line: usize = x.lines.end + usize::from(x.has_end_line);
+                first_line < lines.end && last_line > lines.start
+            })
+            .map(|x| {
+                let start: usize = x.lines.start.max(lines.start);
+                let end: usize = x.lines.end.min(lines.end).max(start);
+                Segment {
+                    name: x.name.clone(),
+                    lines: start - lines.start..end - lines.start,
+                    has_start_line: x.has_start_line && lines.contains(&(x.lines.start - 1)),
+                    has_end_line: x.has_end_line && lines.contains(&x.lines.end)
+                }
+            })
+            .collect()
+    }
+}
+
+// Splits the lines into columns, either at each column_break or evenly into the amount of columns
+pub fn split_columns(line_count: usize, column_breaks: &[usize], columns: u8) -> Vec<Range<usize>> {
+    let starts: Vec<usize> = if column_breaks.is_empty() {
+        let column_length: usize = line_count.div_ceil(columns.max(1) as usize).max(1);
+        (0..line_count).step_by(column_length).collect()
+    } else {
+        let mut starts: Vec<usize> = vec![0];
+        starts.extend(column_breaks);
+        starts
+    };
+
+    starts.iter()
+        .enumerate()
+        .map(|(i, start)| *start..starts.get(i + 1).copied().unwrap_or(line_count))
+        // Breaks right next to each other or at the very start/end would leave empty columns
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+// Puts the columns side by side, each padded out to its widest line
+pub fn join_columns(columns: &[Vec<String>], margin: u16) -> Vec<String> {
+    let widths: Vec<usize> = columns.iter()
+        .map(|x| x.iter().map(|line| formatter::display_width(line)).max().unwrap_or(0))
+        .collect();
+    let rows: usize = columns.iter().map(Vec::len).max().unwrap_or(0);
+
+    (0..rows)
+        .map(|row| {
+            let mut line: String = String::new();
+            for (i, column) in columns.iter().enumerate() {
+                let column_line: &str = column.get(row).map_or("", String::as_str);
+                line.push_str(column_line);
+                // Nothing to line up after the last one
+                if i + 1 < columns.len() {
+                    line.push_str(&" ".repeat(widths[i] - formatter::display_width(column_line) + margin as usize));
+                }
+            }
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+// How much wider a line gets from being boxed, for the border and a space either side
+pub const BOX_PADDING: usize = 4;
+
+// Draws the boxes around the lines, which have had their titles joined to their values by join_lines
+// A max width stops the titles in the borders from going past it, as the lines are already fit to it
+pub fn draw_boxes(lines: &[String], segments: &[Segment], config: &Configuration, max_width: Option<usize>) -> Vec<String> {
+    let mut boxed: Vec<String> = Vec::new();
+    match config.boxes.mode {
+        BoxMode::None => boxed.extend_from_slice(lines),
+        BoxMode::Info => {
+            let title: String = formatter::replace_color_placeholders(&config.boxes.title, config);
+            let inner_width: usize = box_width(lines, &[&title], max_width);
+            draw_box(&mut boxed, lines, &title, inner_width, config);
+        },
+        BoxMode::Segments => {
+            let names: Vec<String> = segments.iter()
+                .map(|x| config.title_color.color_string(&x.name).to_string())
+                .collect();
+            // Every segment's the same width, so they line up with each other
+            let segment_lines: Vec<String> = segments.iter()
+                .flat_map(|x| lines[x.lines.clone()].iter().cloned())
+                .collect();
+            let titles: Vec<&str> = names.iter().map(String::as_str).collect();
+            let inner_width: usize = box_width(&segment_lines, &titles, max_width);
+
+            let mut current_line: usize = 0;
+            for (segment, name) in segments.iter().zip(&names) {
+                // The box goes over the segment line
+                let start: usize = segment.lines.start - usize::from(segment.has_start_line);
+                if current_line > start {
+                    continue;
+                }
+                boxed.extend_from_slice(&lines[current_line..start]);
+                current_line = segment.lines.end + usize::from(segment.has_end_line);
+                // Only the end_segment line of one carried on from the last column, which doesn't need a box
+                let mut inside: Vec<String> = lines[segment.lines.clone()].to_vec();
+                if inside.is_empty() && !segment.has_start_line {
+                    continue;
+                }
+                if config.boxes.titles_in_border {
+                    draw_box(&mut boxed, &inside, name, inner_width, config);
+                } else {
+                    inside.insert(0, name.clone());
+                    draw_box(&mut boxed, &inside, "", inner_width, config);
+                }
+            }
+            boxed.extend_from_slice(&lines[current_line..]);
+        },
+    }
+
+    boxed
+}
+
+// Joins each title to its value, lining the values up if they're inline
+pub fn join_lines(output: &[(String, String)], inline_values: bool) -> Vec<String> {
+    let max_title_len: usize = output.iter()
+        .map(|x| formatter::display_width(&x.0))
+        .max()
+        .unwrap_or(0);
+    output.iter()
+        .map(|(title, value)| {
+            let gap: usize = if inline_values && !title.is_empty() {max_title_len - formatter::display_width(title)} else {0};
+            format!("{title}{}{value}", " ".repeat(gap))
+        })
+        .collect()
+}
+
+// The width inside the boxes, enough for the widest line and the titles in the top border
+fn box_width(lines: &[String], titles: &[&str], max_width: Option<usize>) -> usize {
+    let line_width: usize = lines.iter()
+        .map(|x| formatter::display_width(x))
+        .max()
+        .unwrap_or(0);
+    let title_width: usize = titles.iter()
+        .filter(|x| !x.is_empty())
+        .map(|x| formatter::display_width(x) + 2)
+        .max()
+        .unwrap_or(0);
+    let width: usize = line_width.max(title_width);
+    match max_width {
+        Some(max_width) => width.min(max_width.saturating_sub(BOX_PADDING)),
+        None => width,
+    }
+}
+
+fn draw_box(boxed: &mut Vec<String>, lines: &[String], title: &str, inner_width: usize, config: &Configuration) {
+    let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical]: [char; 6] = config.boxes.style.characters();
+    let border = |x: &str| config.boxes.color.color_string(x).to_string();
+
+    // ╭─ Title ──────╮
+    let mut top: String = border(&format!("{top_left}{horizontal}"));
+    let title: String = formatter::truncate(title, inner_width.saturating_sub(2), &config.ellipsis);
+    let title_width: usize = if title.is_empty() {
+        0
+    } else {
+        top.push(' ');
+        top.push_str(&title);
+        top.push(' ');
+        formatter::display_width(&title) + 2
+    };
+    top.push_str(&border(&format!("{}{top_right}", horizontal.to_string().repeat((inner_width + 1).saturating_sub(title_width)))));
+    boxed.push(top);
+
+    // │ Line   │
+    for line in lines {
+        let gap: usize = inner_width.saturating_sub(formatter::display_width(line));
+        boxed.push(format!("{} {line}{} {}", border(&vertical.to_string()), " ".repeat(gap), border(&vertical.to_string())));
+    }
+
+    // ╰──────────────╯
+    boxed.push(border(&format!("{bottom_left}{}{bottom_right}", horizontal.to_string().repeat(inner_width + 2))));
+}
+
+mod tests {
+    #[test]
+    fn test_boxes() {
+        use crate::{config_manager, layout::{draw_boxes, join_lines, BoxMode, BoxStyle, LineMarkers, Segment}};
+
+        colored::control::set_override(false);
+        let mut config = config_manager::parse(&Some("none".to_string()), &None).unwrap();
+        let output: Vec<(String, String)> = vec![
+            (String::new(), "user@host".to_string()),
+            (String::new(), "[== Hardware ==]".to_string()),
+            ("CPU".to_string(), " > Ryzen".to_string()),
+            ("Memory".to_string(), " > 4 GB / 16 GB".to_string()),
+            (String::new(), "[==          ==]".to_string()),
+        ];
+        let segments: Vec<Segment> = vec![Segment {name: "Hardware".to_string(), lines: 2..4, has_start_line: true, has_end_line: true}];
+        let lines: Vec<String> = join_lines(&output, false);
+        let inline_lines: Vec<String> = join_lines(&output, true);
+
+        config.boxes.mode = BoxMode::Info;
+        config.boxes.style = BoxStyle::Single;
+        config.boxes.title = "Info".to_string();
+        assert_eq!(draw_boxes(&lines[..1], &[], &config, None), vec![
+            "┌─ Info ────┐",
+            "│ user@host │",
+            "└───────────┘",
+        ]);
+
+        config.boxes.mode = BoxMode::Segments;
+        config.boxes.style = BoxStyle::Rounded;
+        config.boxes.titles_in_border = true;
+        assert_eq!(draw_boxes(&inline_lines, &segments, &config, None), vec![
+            "user@host",
+            "╭─ Hardware ────────────╮",
+            "│ CPU    > Ryzen        │",
+            "│ Memory > 4 GB / 16 GB │",
+            "╰───────────────────────╯",
+        ]);
+
+        // Not ended, and without the name in the border
+        config.boxes.titles_in_border = false;
+        assert_eq!(draw_boxes(&lines[..4], &[Segment {name: "Hardware".to_string(), lines: 2..4, has_start_line: true, has_end_line: false}], &config, None), vec![
+            "user@host",
+            "╭───────────────────────╮",
+            "│ Hardware              │",
+            "│ CPU > Ryzen           │",
+            "│ Memory > 4 GB / 16 GB │",
+            "╰───────────────────────╯",
+        ]);
+
+        // A segment that runs into the next one, with both of them being boxed
+        let mut markers: LineMarkers = LineMarkers::default();
+        markers.start_segment("Hardware", 0);
+        markers.start_segment("Software", 2);
+        markers.end_segment(4, true);
+        let unended_lines: Vec<String> = ["[== Hardware ==]", "CPU > Ryzen", "[== Software ==]", "OS > Arch", "[==          ==]", "user@host"].iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(draw_boxes(&unended_lines, &markers.segments, &config, None), vec![
+            "╭─────────────╮",
+            "│ Hardware    │",
+            "│ CPU > Ryzen │",
+            "╰─────────────╯",
+            "╭─────────────╮",
+            "│ Software    │",
+            "│ OS > Arch   │",
+            "╰─────────────╯",
+            "user@host",
+        ]);
+
+        // Titles are cut down to fit if they'd go past the max width
+        config.boxes.mode = BoxMode::Info;
+        config.boxes.title = "A much longer title".to_string();
+        assert_eq!(draw_boxes(&lines[..1], &[], &config, Some(17)), vec![
+            "╭─ A much lon… ─╮",
+            "│ user@host     │",
+            "╰───────────────╯",
+        ]);
+    }
+    #[test]
+    fn test_columns() {
+        use crate::layout::{join_columns, split_columns, LineMarkers, Segment};
+
+        assert_eq!(split_columns(5, &[], 1), vec![0..5]);
+        assert_eq!(split_columns(5, &[], 2), vec![0..3, 3..5]);
+        assert_eq!(split_columns(2, &[], 3), vec![0..1, 1..2]);
+        assert_eq!(split_columns(0, &[], 2), Vec::new());
+        // Breaks win over the column count, and empty columns are dropped
+        assert_eq!(split_columns(5, &[2], 3), vec![0..2, 2..5]);
+        assert_eq!(split_columns(5, &[0, 2, 2, 5], 1), vec![0..2, 2..5]);
+
+        let columns: Vec<Vec<String>> = vec![
+            vec!["CPU > Ryzen".to_string(), "メモリ > 16 GB".to_string()],
+            vec!["OS > Arch".to_string()],
+        ];
+        assert_eq!(join_columns(&columns, 2), vec![
+            "CPU > Ryzen     OS > Arch",
+            "メモリ > 16 GB",
+        ]);
+
+        let mut markers: LineMarkers = LineMarkers::default();
+        markers.start_segment("Hardware", 0);
+        markers.end_segment(3, true);
+        markers.start_segment("Software", 4);
+        markers.end_segment(8, true);
+        let segments: Vec<Segment> = markers.segments_in(&(4..6));
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].name.as_str(), segments[0].lines.clone(), segments[0].has_start_line, segments[0].has_end_line), ("Software", 1..2, true, false));
+
+        // Crossing a column break, it carries on into the next column without its segment line
+        let mut markers: LineMarkers = LineMarkers::default();
+        markers.start_segment("Hardware", 0);
+        markers.end_segment(3, true);
+        let segments: Vec<Segment> = markers.segments_in(&(2..4));
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].lines.clone(), segments[0].has_start_line, segments[0].has_end_line), (0..1, false, true));
+        // With nothing but its end_segment line there, the line's dropped
+        let mut config = crate::config_manager::parse(&Some("none".to_string()), &None).unwrap();
+        config.boxes.mode = crate::layout::BoxMode::Segments;
+        let lines: Vec<String> = vec!["[==          ==]".to_string(), "OS > Arch".to_string()];
+        assert_eq!(crate::layout::draw_boxes(&lines, &markers.segments_in(&(3..5)), &config, None), vec!["OS > Arch"]);
+    }
+}
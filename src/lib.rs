@@ -0,0 +1,37 @@
+//! The detection and formatting behind `CrabFetch`, as a library.
+//!
+//! Each module lives under [`modules`] with its own detection function, e.g [`modules::cpu::get_cpu`]
+//! or [`modules::gpu::get_gpus`], which returns that module's info struct. The info structs have
+//! getters for the raw values, or can be styled against a [`Configuration`] the same way the
+//! binary does.
+//!
+//! ```no_run
+//! let config: crab_fetch::Configuration = crab_fetch::config_manager::parse(&None, &None).unwrap();
+//! let cpu = crab_fetch::modules::cpu::get_cpu(&config).unwrap();
+//! println!("{} ({} cores)", cpu.name(), cpu.cores());
+//! ```
+#![warn(clippy::pedantic)]
+#![allow(clippy::too_many_lines, clippy::unreadable_literal, clippy::similar_names, clippy::ignored_unit_patterns)]
+#![allow(clippy::must_use_candidate, clippy::missing_errors_doc, clippy::missing_panics_doc, clippy::module_name_repetitions)]
+
+pub mod modules;
+pub mod config_manager;
+pub mod config_check;
+pub mod ascii;
+pub mod image;
+pub mod export;
+pub mod layout;
+pub mod formatter;
+pub mod template;
+pub mod module;
+pub mod common_sources;
+pub mod scheduler;
+pub mod cache;
+mod proccess_info;
+mod versions;
+mod util;
+mod ascii_art;
+mod preset_configs;
+
+pub use config_manager::Configuration;
+pub use module::{Module, ModuleError, ModuleOutputs};
@@ -1,58 +1,15 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::too_many_lines, clippy::unreadable_literal, clippy::similar_names, clippy::ignored_unit_patterns)]
 
-use std::process::{Command, Output};
+use std::io::{self, Write};
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+use std::thread;
 use std::time::Duration;
-use std::{cmp::max, env, process::exit, time::Instant};
-
-use ascii::AsciiMode;
-use common_sources::gtk::GTKSettingsCache;
-use formatter::CrabFetchColor;
-use module::{Module, ModuleError};
-use modules::battery::{self, BatteryInfo};
-use modules::cpu::{self, CPUInfo};
+use std::{cmp::max, env, ops::Range, process::exit, time::Instant};
+
 use clap::{ArgAction, Parser};
 use colored::Colorize;
-use modules::datetime::{self, DateTimeInfo};
-use modules::desktop::{self, DesktopInfo};
-use modules::displays::{self, DisplayInfo};
-use modules::editor::{self, EditorInfo};
-use modules::host::{self, HostInfo};
-use modules::icon_theme::{self, IconThemeInfo};
-use modules::initsys::{self, InitSystemInfo};
-use modules::locale::{self, LocaleInfo};
-use modules::memory::{self, MemoryInfo};
-use modules::gpu::{self, GPUInfo};
-use modules::mounts::{self, MountInfo};
-#[cfg(feature = "player")]
-use modules::player::{self, PlayerInfo};
-use modules::os::{self, OSInfo};
-use modules::packages::{self, PackagesInfo};
-use modules::processes::{self, ProcessesInfo};
-use modules::shell::{self, ShellInfo};
-use modules::swap::{self, SwapInfo};
-use modules::terminal::{self, TerminalInfo};
-use modules::theme::{self, ThemeInfo};
-use modules::uptime::{self, UptimeInfo};
-use modules::hostname::{self, HostnameInfo};
-use config_manager::Configuration;
-use common_sources::package_managers::ManagerInfo;
-use common_sources::syscalls::SyscallCache;
-
-use crate::ascii::get_ascii_line;
-use crate::modules::localip::{self, LocalIPInfo};
-
-mod modules;
-mod config_manager;
-mod ascii;
-mod formatter;
-mod proccess_info;
-mod versions;
-mod module;
-mod util;
-mod ascii_art;
-mod common_sources;
-mod preset_configs;
+use crab_fetch::{ascii::{self, get_ascii_line, AsciiMode}, common_sources::syscalls::SyscallCache, config_check, config_manager, export::{self, ExportFormat}, formatter::{self, CrabFetchColor}, image::{self, ImageProtocol, LogoImage}, layout::{self, BoxMode, LineMarkers}, module, modules::{self, os::{self, OSInfo}}, scheduler::{self, DetectionTimes}, Configuration, ModuleOutputs};
 
 #[derive(Parser)]
 #[command(about, long_about = None)]
@@ -79,6 +36,11 @@ pub struct Args {
     /// Disable ASCII rendering entirely
     disable_ascii: bool,
 
+    #[arg(long, value_name = "PATH")]
+    /// Uses a PNG or JPEG image as the ASCII, turned into half blocks at the config's image_width.
+    /// If the config's image_protocol is "braille", it's turned into braille instead.
+    logo_from_image: Option<String>,
+
     #[arg(short, long, require_equals(true), default_missing_value("false"), default_value("false"), action=ArgAction::Set)]
     /// Whether to suppress any errors or not.
     suppress_errors: bool,
@@ -94,7 +56,7 @@ pub struct Args {
 
     #[arg(long)]
     /// Runs CrabFetch in a "benchmark" mode, showing the total times it takes between each stage
-    /// and module detection times.
+    /// and module detection times. These are printed to stderr.
     benchmark: bool,
 
     #[arg(long)]
@@ -105,6 +67,44 @@ pub struct Args {
     #[arg(long, short)]
     /// Displays the version of CrabFetch, as well as the current features enabled in this build.
     version: bool,
+
+    #[arg(long)]
+    /// Reads system files such as /proc, /sys and /etc from a different root directory.
+    /// Useful for fetching the info of a mounted chroot or container image.
+    root: Option<String>,
+
+    #[arg(long)]
+    /// Outputs the raw detected data of each module as JSON instead of the usual fetch.
+    /// Module errors are included as objects in place of the module's data.
+    json: bool,
+
+    #[arg(long)]
+    /// Checks your config file for any problems, such as unknown keys or modules and invalid colors,
+    /// and reports them all. Exits with a non-zero code if any were found.
+    check_config: bool,
+
+    #[arg(long, value_name = "INTERVAL", value_parser = parse_interval, conflicts_with_all = ["json", "benchmark"])]
+    /// Keeps CrabFetch running and redraws the fetch in place every interval, e.g "2" or "500ms".
+    /// Volatile modules such as memory and uptime are re-detected each time, everything else is kept.
+    watch: Option<Duration>,
+
+    #[arg(long, num_args = 2, value_names = ["FORMAT", "PATH"], conflicts_with_all = ["json", "watch"])]
+    /// Renders the fetch to a standalone SVG or HTML file instead of printing it, e.g "--export svg fetch.svg".
+    /// Image logos shown through the terminal are swapped for the distro's ASCII.
+    export: Option<Vec<String>>,
+}
+
+// Plain numbers are taken as seconds, anything else as a duration such as "500ms" or "1m"
+fn parse_interval(interval: &str) -> Result<Duration, String> {
+    let duration: Duration = match interval.parse::<f64>() {
+        Ok(r) => Duration::try_from_secs_f64(r).map_err(|e| e.to_string())?,
+        Err(_) => humantime::parse_duration(interval).map_err(|e| e.to_string())?,
+    };
+    if duration.is_zero() {
+        return Err("The interval can't be zero.".to_string());
+    }
+
+    Ok(duration)
 }
 
 // This is done here simply to make the main function not as indented of a mess, it's abstracted into here
@@ -120,8 +120,9 @@ fn print_bench_time(benchmarking: bool, benchmark_warn: Option<u128>, name: &str
         return;
     }
 
-    // This is different to module bench times fyi
-    let t: Duration = time.unwrap().elapsed();
+    print_bench_duration(benchmark_warn, name, time.unwrap().elapsed());
+}
+fn print_bench_duration(benchmark_warn: Option<u128>, name: &str, t: Duration) {
     let mut t_output: String = format!("{t:2?}");
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
     if let Some(threshold) = benchmark_warn {
@@ -131,111 +132,8 @@ fn print_bench_time(benchmarking: bool, benchmark_warn: Option<u128>, name: &str
             t_output = t_output.bright_yellow().to_string();
         }
     }
-    println!("[Benchmark] {name}: {t_output}");
-}
-
-// Macro for calling most module types
-#[macro_export]
-macro_rules! run_generic_module {
-    ($mod: ident, $type: ident, $run: ident, $known: expr, $config: expr, $err: expr, $out: expr, $($rargs:tt)*) => {
-        if $known.is_none() {
-            $known = Some($mod::$run($($rargs)*));
-        }
-        match $known.as_ref().unwrap() {
-            Ok(x) => $out.push(x.style(&$config)),
-            Err(e) => {
-                if $err {
-                    $out.push((String::new(), e.to_string()));
-                } else {
-                    $out.push($type::unknown_output(&$config));
-                }
-            },
-        }; 
-    };
-}
-#[macro_export]
-macro_rules! run_multiline_module {
-    ($mod: ident, $type: ident, $run: ident, $known: expr, $config: expr, $err: expr, $out: expr, $($rargs:tt)*) => {
-        if $known.is_none() {
-            $known = Some($mod::$run($($rargs)*));
-        }
-        match $known.as_ref().unwrap() {
-            Ok(x) => {
-                for y in x {
-                    $out.push(y.style(&$config));
-                }
-            },
-            Err(e) => {
-                if $err {
-                    $out.push((String::new(), e.to_string()));
-                } else {
-                    $out.push($type::unknown_output(&$config));
-                }
-            },
-        }; 
-    };
-}
-
-
-// Stores all the module's outputs as we know them
-// This is to prevent us doing additional work when we don't need to, when modules need shared data
-struct ModuleOutputs {
-    hostname: Option<Result<HostnameInfo, ModuleError>>,
-    cpu: Option<Result<CPUInfo, ModuleError>>,
-    gpu: Option<Result<Vec<GPUInfo>, ModuleError>>,
-    memory: Option<Result<MemoryInfo, ModuleError>>,
-    swap: Option<Result<SwapInfo, ModuleError>>,
-    mounts: Option<Result<Vec<MountInfo>, ModuleError>>,
-    host: Option<Result<HostInfo, ModuleError>>,
-    displays: Option<Result<Vec<DisplayInfo>, ModuleError>>,
-    packages: Option<PackagesInfo>,
-    desktop: Option<Result<DesktopInfo, ModuleError>>,
-    terminal: Option<Result<TerminalInfo, ModuleError>>,
-    shell: Option<Result<ShellInfo, ModuleError>>,
-    battery: Option<Result<Vec<BatteryInfo>, ModuleError>>,
-    uptime: Option<Result<UptimeInfo, ModuleError>>,
-    locale: Option<Result<LocaleInfo, ModuleError>>,
-    #[cfg(feature = "player")]
-    player: Option<Result<Vec<PlayerInfo>, ModuleError>>,
-    editor: Option<Result<EditorInfo, ModuleError>>,
-    os: Option<Result<OSInfo, ModuleError>>,
-    initsys: Option<Result<InitSystemInfo, ModuleError>>,
-    processes: Option<Result<ProcessesInfo, ModuleError>>,
-    datetime: Option<DateTimeInfo>,
-    localip: Option<Result<Vec<LocalIPInfo>, ModuleError>>,
-    theme: Option<Result<ThemeInfo, ModuleError>>,
-    icon_theme: Option<Result<IconThemeInfo, ModuleError>>,
-}
-impl ModuleOutputs {
-    fn new() -> Self {
-        Self {
-            hostname: None,
-            cpu: None,
-            gpu: None,
-            memory: None,
-            swap: None,
-            mounts: None,
-            host: None,
-            displays: None,
-            packages: None,
-            desktop: None,
-            terminal: None,
-            shell: None,
-            battery: None,
-            uptime: None,
-            locale: None,
-            #[cfg(feature = "player")]
-            player: None,
-            editor: None,
-            os: None,
-            initsys: None,
-            theme: None,
-            icon_theme: None,
-            processes: None,
-            datetime: None,
-            localip: None,
-        }
-    }
+    // Kept out of stdout, so that it doesn't end up mixed into e.g the JSON output
+    eprintln!("[Benchmark] {name}: {t_output}");
 }
 
 fn main() {
@@ -296,6 +194,41 @@ fn main() {
         print_bench_time(args.benchmark, args.benchmark_warn, "Generating Config File", bench);
         exit(0);
     }
+    if args.check_config {
+        let (source, problems) = match config_check::check_config(&args.config) {
+            Ok(r) => r,
+            Err(e) => {
+                println!("{e}");
+                exit(1);
+            },
+        };
+        for problem in &problems {
+            match problem.line {
+                Some(line) => println!("{source}:{line}: {}", problem.message),
+                None => println!("{source}: {}", problem.message),
+            }
+        }
+        if problems.is_empty() {
+            println!("No problems found in {source}");
+            exit(0);
+        }
+        println!("Found {} problem(s) in {source}", problems.len());
+        exit(1);
+    }
+    // Checked before anything's detected, so a wrong format doesn't have to wait for the whole fetch first
+    let export: Option<(ExportFormat, String)> = args.export.as_ref().map(|x| match x[0].parse() {
+        Ok(format) => (format, x[1].clone()),
+        Err(e) => {
+            println!("{e}");
+            exit(-1);
+        },
+    });
+    if export.is_some() {
+        // There's no terminal to limit the colors of the file
+        colored::control::set_override(true);
+        formatter::force_truecolor();
+    }
+
     let bench: Option<Instant> = benchmark_point(args.benchmark); 
     let mut config: Configuration = match config_manager::parse(&args.config, &args.module_override) {
         Ok(r) => r,
@@ -306,6 +239,15 @@ fn main() {
     };
 
     if args.disable_ascii { config.ascii.display = false }
+    if let Some(ref path) = args.logo_from_image {
+        config.ascii.image = Some(path.clone());
+        if config.ascii.image_protocol != ImageProtocol::Braille {
+            config.ascii.image_protocol = ImageProtocol::HalfBlock;
+        }
+    }
+    if let Some(ref root) = args.root { config.root.clone_from(root) }
+    // Nor is it bound to the terminal's width
+    if export.is_some() { config.fit_to_terminal = false }
     print_bench_time(args.benchmark, args.benchmark_warn, "Parsing Config", bench);
 
     // if config isn't supprsesing errors, make it go down to args
@@ -313,36 +255,26 @@ fn main() {
     // Inline values
     let inline_values: bool = config.inline_values || args.inline_values;
 
-    // Define our module outputs, and figure out the max title length
+    // Define our module outputs
     let mut known_outputs: ModuleOutputs = ModuleOutputs::new();
     print_bench_time(args.benchmark, args.benchmark_warn, "Pre-Process", bench);
 
-    // Pre-Process any package manager info we may need
-    let bench: Option<Instant> = benchmark_point(args.benchmark);
-    let mut package_managers: ManagerInfo = ManagerInfo::new();
-    package_managers.probe_and_cache(&config);
-    print_bench_time(args.benchmark, args.benchmark_warn, "Cache Package Managers", bench);
-
     // Setup our syscall cache
     let mut syscall_cache: SyscallCache = SyscallCache::new();
 
-    // GTK Settings
-    let mut gtk_settings_cache: GTKSettingsCache = GTKSettingsCache::default();
-
     // Set the title color if we're usign os colors
-    if config.use_os_color || (config.ascii.display && config.ascii.mode == AsciiMode::OS) {
-        let id: &str = if let Some(ref x) = args.distro_override {
-            x
+    if !args.json && (config.use_os_color || (config.ascii.display && config.ascii.mode == AsciiMode::OS)) {
+        let c: CrabFetchColor = if let Some(ref x) = args.distro_override {
+            formatter::find_os_color(&[x], None)
         } else {
-            if known_outputs.os.is_none() {
+            if !known_outputs.contains("os") {
                 let os_bench: Option<Instant> = benchmark_point(args.benchmark); 
-                known_outputs.os = Some(os::get_os(&config, &mut syscall_cache));
+                known_outputs.insert("os", module::boxed(os::get_os(&config, &mut syscall_cache)));
                 print_bench_time(args.benchmark, args.benchmark_warn, "OS (for OS Color)", os_bench);
             }
-            &known_outputs.os.as_ref().unwrap().as_ref().unwrap().distro_id
+            known_outputs.get_info::<OSInfo>("os").map_or(CrabFetchColor::Clear, |x| formatter::find_os_color(&x.distro_ids(), x.ansi_color()))
         };
 
-        let c: CrabFetchColor = formatter::find_os_color(id);
         if config.use_os_color {
             config.title_color = c.clone();
         }
@@ -351,17 +283,162 @@ fn main() {
         }
     }
 
+    // Nothing past here changes the config, so it can be shared with the detection threads
+    let config: Arc<Configuration> = Arc::new(config);
+
     // 
     //  Detect
     //
     let detect_bench: Option<Instant> = benchmark_point(args.benchmark); 
+    let detection_times: DetectionTimes = scheduler::detect_modules(&config, &mut known_outputs, &mut syscall_cache);
+    if args.benchmark {
+        for (name, time) in &detection_times.modules {
+            print_bench_duration(args.benchmark_warn, &format!("Detection ({name})"), *time);
+        }
+        print_bench_duration(args.benchmark_warn, "Parallel Detection Saving", detection_times.saving());
+    }
+    print_bench_time(args.benchmark, args.benchmark_warn, "Entire detection step", detect_bench);
+
+    // JSON skips styling and the display entirely, so command: modules aren't ran just to be thrown away
+    if args.json {
+        let bench: Option<Instant> = benchmark_point(args.benchmark); 
+        match serde_json::to_string_pretty(&known_outputs.to_json(&config.modules)) {
+            Ok(r) => println!("{r}"),
+            Err(e) => {
+                println!("Unable to serialize module data to JSON: {e}");
+                exit(-1);
+            },
+        }
+        print_bench_time(args.benchmark, args.benchmark_warn, "JSON Output", bench);
+        print_bench_time(args.benchmark, args.benchmark_warn, "Full Runtime of CrabFetch", full_runtime_bench);
+        return;
+    }
+
+    // 
+    //  Style
+    //
+    let style_bench: Option<Instant> = benchmark_point(args.benchmark); 
+    let (output, markers): (Vec<(String, String)>, LineMarkers) = style_modules(&args, &config, &known_outputs, log_errors);
+    print_bench_time(args.benchmark, args.benchmark_warn, "Entire style step", style_bench);
+
+
+    // 
+    //  Display
+    //
+    let ascii_bench: Option<Instant> = benchmark_point(args.benchmark); 
+    let mut ascii_split: Vec<&str> = Vec::new();
+    let mut ascii_target_length: u16 = 0;
+    // :(
+    let fuck_off_borrow_checker: String;
+    // Exports can only have text in them, so the ASCII's used instead
+    let logo_image: Option<LogoImage> = if config.ascii.display && export.is_none() {
+        image::find_image(&config).unwrap_or_else(|e| {
+            if log_errors {
+                println!("{e}");
+            }
+            None
+        })
+    } else {
+        None
+    };
+    if let Some(ref logo_image) = logo_image {
+        // Blank lines to leave room for the image, which is drawn over them afterwards
+        ascii_split = vec![""; logo_image.rows as usize];
+        ascii_target_length = logo_image.columns + config.ascii.margin;
+    } else if config.ascii.display {
+        if !known_outputs.contains("os") {
+            let os_bench: Option<Instant> = benchmark_point(args.benchmark); 
+            known_outputs.insert("os", module::boxed(os::get_os(&config, &mut syscall_cache)));
+            print_bench_time(args.benchmark, args.benchmark_warn, "OS (for ASCII)", os_bench);
+        }
+        if let Some(os) = known_outputs.get_info::<OSInfo>("os") {
+            // Calculate the ASCII stuff while we're here
+            // The override only gives us the one ID to go off
+            let (os_ids, ansi_color): (Vec<&str>, Option<&str>) = match args.distro_override {
+                Some(ref x) => (vec![x], None),
+                None => (os.distro_ids(), os.ansi_color()),
+            };
+            // With columns, the modules only take up as many lines as the longest column
+            let module_lines: usize = layout::split_columns(output.len(), &markers.column_breaks, config.columns).iter()
+                .map(ExactSizeIterator::len)
+                .max()
+                .unwrap_or(0);
+            // An image turned into text is used like any other ASCII
+            let ascii: (String, u16, Option<Vec<CrabFetchColor>>) = image::find_image_ascii(&config)
+                .unwrap_or_else(|e| {
+                    if log_errors {
+                        println!("{e}");
+                    }
+                    None
+                })
+                .map_or_else(|| ascii::find_ascii(&os_ids, args.ignore_custom_ascii, &config, module_lines), |x| (x.0, x.1, None));
+            // Logo files can bring their own colors, but the config's take priority
+            let colors: Vec<CrabFetchColor> = match ascii.2 {
+                Some(colors) if config.ascii.colors.is_empty() => colors,
+                _ => ascii::logo_colors(&os_ids, ansi_color, &config),
+            };
+            // Solid, band and gradient colors take over the whole logo, so the markers are just dropped there
+            fuck_off_borrow_checker = match config.ascii.mode {
+                AsciiMode::Raw | AsciiMode::OS => ascii::color_markers(&ascii.0, Some(&colors)),
+                AsciiMode::Solid | AsciiMode::Band | AsciiMode::BandHorizontal | AsciiMode::BandDiagonal | AsciiMode::Gradient => ascii::color_markers(&ascii.0, None),
+            };
+            // Only blank lines around the ASCII are dropped, as images can have some in the middle
+            ascii_split = fuck_off_borrow_checker.split('\n').collect();
+            while ascii_split.first().is_some_and(|x| x.trim().is_empty()) {
+                ascii_split.remove(0);
+            }
+            while ascii_split.last().is_some_and(|x| x.trim().is_empty()) {
+                ascii_split.pop();
+            }
+            ascii_target_length = ascii.1 + config.ascii.margin;
+        }
+    }
+
+    print_bench_time(args.benchmark, args.benchmark_warn, "Display ASCII Pre-Calc", ascii_bench);
+
+    // Watch mode takes over from here, redrawing until it's stopped
+    if let Some(interval) = args.watch {
+        let first_frame: String = draw(&config, &output, &markers, &ascii_split, ascii_target_length, logo_image.as_ref(), inline_values);
+        watch(interval, first_frame, || {
+            scheduler::refresh_modules(&config, &mut known_outputs, &mut syscall_cache);
+            let (output, markers): (Vec<(String, String)>, LineMarkers) = style_modules(&args, &config, &known_outputs, log_errors);
+            draw(&config, &output, &markers, &ascii_split, ascii_target_length, logo_image.as_ref(), inline_values)
+        });
+        return;
+    }
+
+    // the actual outputs
+    let bench: Option<Instant> = benchmark_point(args.benchmark); 
+    let frame: String = draw(&config, &output, &markers, &ascii_split, ascii_target_length, logo_image.as_ref(), inline_values);
+    match export {
+        Some((format, path)) => {
+            if let Err(e) = export::export(&frame, &format, &path) {
+                println!("{e}");
+                exit(-1);
+            }
+        },
+        None => print!("{frame}"),
+    }
+    print_bench_time(args.benchmark, args.benchmark_warn, "Module + ASCII Output", bench);
+
+    print_bench_time(args.benchmark, args.benchmark_warn, "Full Runtime of CrabFetch", full_runtime_bench);
+}
+
+
+// Styles each module in the config's module list into the lines to display
+// Along with where each segment and column break is, for laying them out
+fn style_modules(args: &Args, config: &Configuration, known_outputs: &ModuleOutputs, log_errors: bool) -> (Vec<(String, String)>, LineMarkers) {
     let mut output: Vec<(String, String)> = Vec::new();
+    let mut markers: LineMarkers = LineMarkers::default();
     let mut cur_segment_length: usize = 0;
     for module in &config.modules {
         let module_parse_bench: Option<Instant> = benchmark_point(args.benchmark); 
         let module_split: Vec<&str> = module.split(':').collect();
         let module_name: &str = module_split[0];
         match module_name {
+            "column_break" => {
+                markers.column_breaks.push(output.len());
+            },
             "space" => {
                 let bench: Option<Instant> = benchmark_point(args.benchmark); 
                 output.push((String::new(), String::new()));
@@ -369,231 +446,37 @@ fn main() {
             },
             "underline" => {
                 let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                let underline_length: usize = module_split[1].parse().unwrap();
-                output.push((String::new(), config.underline_character.to_string().repeat(underline_length)));
+                match module_split.get(1).map(|x| x.parse::<usize>()) {
+                    Some(Ok(underline_length)) => output.push((String::new(), config.underline_character.to_string().repeat(underline_length))),
+                    _ => output.push((String::new(), "Underline module invoked, but without a valid length.".to_string())),
+                }
                 print_bench_time(args.benchmark, args.benchmark_warn, "Underline Module", bench);
             },
             "segment" => {
                 let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                let segment_name: &str = module_split[1];  
-                let segment_string: String = config.segment_top.replace("{name}", segment_name);
-                output.push((String::new(), formatter::replace_color_placeholders(&segment_string, &config)));
-                cur_segment_length = segment_name.len();
+                if let Some(segment_name) = module_split.get(1) {
+                    // A segment that's still open is ended here, rather than having this one inside it
+                    markers.start_segment(segment_name, output.len());
+                    let segment_string: String = config.segment_top.replace("{name}", segment_name);
+                    output.push((String::new(), formatter::replace_color_placeholders(&segment_string, config)));
+                    cur_segment_length = formatter::display_width(segment_name);
+                } else {
+                    output.push((String::new(), "Segment module invoked, but no name given.".to_string()));
+                }
                 print_bench_time(args.benchmark, args.benchmark_warn, "Segment Start", bench);
             },
             "end_segment" => {
                 let bench: Option<Instant> = benchmark_point(args.benchmark); 
 
-                let index: usize = config.segment_bottom.find("{name_sized_gap:").unwrap();
-                let split: &Vec<char> = &config.segment_bottom[index+16..].chars().collect::<Vec<char>>();
-                let char: &char = split.first().unwrap();
-
-                let target = format!("{{name_sized_gap:{char}}}");
-                let segment_string: String = config.segment_bottom.replace(&target, &char.to_string().repeat(cur_segment_length + 2));
-                output.push((String::new(), formatter::replace_color_placeholders(&segment_string, &config)));
+                // Either {name_sized_gap:c} to fill the gap with c, or just {name_sized_gap} to leave it empty
+                let segment_string: String = match config.segment_bottom.split_once("{name_sized_gap:").and_then(|x| x.1.chars().next()) {
+                    Some(char) => config.segment_bottom.replace(&format!("{{name_sized_gap:{char}}}"), &char.to_string().repeat(cur_segment_length + 2)),
+                    None => config.segment_bottom.replace("{name_sized_gap}", &" ".repeat(cur_segment_length)),
+                };
+                markers.end_segment(output.len(), true);
+                output.push((String::new(), formatter::replace_color_placeholders(&segment_string, config)));
                 print_bench_time(args.benchmark, args.benchmark_warn, "Segment End", bench);
             },
-            "hostname" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(hostname, HostnameInfo, get_hostname, known_outputs.hostname, config, log_errors, output, &config, &mut syscall_cache);
-                print_bench_time(args.benchmark, args.benchmark_warn, "Hostname Module", bench);
-            },
-            "cpu" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(cpu, CPUInfo, get_cpu, known_outputs.cpu, config, log_errors, output, &config);
-                print_bench_time(args.benchmark, args.benchmark_warn, "CPU Module", bench);
-            },
-            "gpu" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                if known_outputs.gpu.is_none() {
-                    known_outputs.gpu = Some(gpu::get_gpus(&config));
-                }
-                match known_outputs.gpu.as_ref().unwrap() {
-                    Ok(gpus) => {
-                        let mut index: u8 = 1;
-                        for gpu in gpus {
-                            let mut gpu = gpu.clone();
-                            gpu.set_index(index);
-                            output.push(gpu.style(&config));
-                            index += 1;
-                        }
-                    },
-                    Err(e) => {
-                        if log_errors {
-                            output.push((String::new(), e.to_string()));
-                        } else {
-                            output.push(GPUInfo::unknown_output(&config));
-                        }
-                    },
-                }; 
-                print_bench_time(args.benchmark, args.benchmark_warn, "GPU Module", bench);
-            },
-            "memory" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(memory, MemoryInfo, get_memory, known_outputs.memory, config, log_errors, output, );
-                print_bench_time(args.benchmark, args.benchmark_warn, "Memory Module", bench);
-            },
-            "swap" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(swap, SwapInfo, get_swap, known_outputs.swap, config, log_errors, output, &mut syscall_cache);
-                print_bench_time(args.benchmark, args.benchmark_warn, "Swap Module", bench);
-            },
-            "mounts" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                if known_outputs.mounts.is_none() {
-                    known_outputs.mounts = Some(mounts::get_mounted_drives(&config));
-                }
-                match known_outputs.mounts.as_ref().unwrap() {
-                    Ok(mounts) => {
-                        for mount in mounts {
-                            if mount.is_ignored(&config) {
-                                continue;
-                            }
-                            output.push(mount.style(&config));
-                        }
-                    },
-                    Err(e) => {
-                        if log_errors {
-                            output.push((String::new(), e.to_string()));
-                        } else {
-                            output.push(MountInfo::unknown_output(&config));
-                        }
-                    },
-                }; 
-                print_bench_time(args.benchmark, args.benchmark_warn, "Mounts Module", bench);
-            },
-            "host" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                if known_outputs.host.is_none() {
-                    known_outputs.host = Some(host::get_host(&config));
-                }
-                match known_outputs.host.as_ref().unwrap() {
-                    Ok(host) => {
-                        output.push(host.style(&config));
-                        if config.host.newline_chassis {
-                            output.push(host.style_chassis(&config));
-                        }
-                    },
-                    Err(e) => {
-                        if log_errors {
-                            output.push((String::new(), e.to_string()));
-                        } else {
-                            output.push(HostInfo::unknown_output(&config));
-                        }
-                    },
-                }; 
-                print_bench_time(args.benchmark, args.benchmark_warn, "Host Module", bench);
-            },
-            "displays" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_multiline_module!(displays, DisplayInfo, get_displays, known_outputs.displays, config, log_errors, output, &config);
-                print_bench_time(args.benchmark, args.benchmark_warn, "Displays Module", bench);
-            },
-            "os" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                if known_outputs.os.is_none() {
-                    known_outputs.os = Some(os::get_os(&config, &mut syscall_cache));
-                }
-                match known_outputs.os.as_ref().unwrap() {
-                    Ok(os) => {
-                        output.push(os.style(&config));
-                        if config.os.newline_kernel {
-                            output.push(os.style_kernel(&config));
-                        }
-                    },
-                    Err(e) => {
-                        if log_errors {
-                            output.push((String::new(), e.to_string()));
-                        } else {
-                            output.push(OSInfo::unknown_output(&config));
-                        }
-                    },
-                }; 
-                print_bench_time(args.benchmark, args.benchmark_warn, "OS Module", bench);
-            },
-            "packages" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                if known_outputs.packages.is_none() {
-                    known_outputs.packages = Some(packages::get_packages(&package_managers));
-                }
-                output.push(known_outputs.packages.as_ref().unwrap().style(&config));
-                print_bench_time(args.benchmark, args.benchmark_warn, "Packages Module", bench);
-            },
-            "desktop" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(desktop, DesktopInfo, get_desktop, known_outputs.desktop, config, log_errors, output, &config);
-                print_bench_time(args.benchmark, args.benchmark_warn, "Desktop Module", bench);
-            },
-            "terminal" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(terminal, TerminalInfo, get_terminal, known_outputs.terminal, config, log_errors, output, &config, &package_managers);
-                print_bench_time(args.benchmark, args.benchmark_warn, "Terminal Module", bench);
-            },
-            "shell" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(shell, ShellInfo, get_shell, known_outputs.shell, config, log_errors, output, &config, &package_managers);
-                print_bench_time(args.benchmark, args.benchmark_warn, "Shell Module", bench);
-            },
-            "battery" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_multiline_module!(battery, BatteryInfo, get_batteries, known_outputs.battery, config, log_errors, output, );
-                print_bench_time(args.benchmark, args.benchmark_warn, "Battery Module", bench);
-            },
-            "uptime" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(uptime, UptimeInfo, get_uptime, known_outputs.uptime, config, log_errors, output, &mut syscall_cache);
-                print_bench_time(args.benchmark, args.benchmark_warn, "Uptime Module", bench);
-            },
-            "locale" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(locale, LocaleInfo, get_locale, known_outputs.locale, config, log_errors, output, );
-                print_bench_time(args.benchmark, args.benchmark_warn, "Locale Module", bench);
-            },
-            #[cfg(feature = "player")]
-            "player" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_multiline_module!(player, PlayerInfo, get_players, known_outputs.player, config, log_errors, output, &config);
-                print_bench_time(args.benchmark, args.benchmark_warn, "Player Module", bench);
-            },
-            "editor" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(editor, EditorInfo, get_editor, known_outputs.editor, config, log_errors, output, &config, &package_managers);
-                print_bench_time(args.benchmark, args.benchmark_warn, "Editor Module", bench);
-            },
-            "initsys" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(initsys, InitSystemInfo, get_init_system, known_outputs.initsys, config, log_errors, output, &config, &package_managers);
-                print_bench_time(args.benchmark, args.benchmark_warn, "InitSys Module", bench);
-            },
-            "processes" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(processes, ProcessesInfo, get_process_count, known_outputs.processes, config, log_errors, output, );
-                print_bench_time(args.benchmark, args.benchmark_warn, "Processes Module", bench);
-            },
-            "datetime" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                if known_outputs.datetime.is_none() {
-                    known_outputs.datetime = Some(datetime::get_date_time());
-                }
-                output.push(known_outputs.datetime.as_ref().unwrap().style(&config));
-                print_bench_time(args.benchmark, args.benchmark_warn, "Datetime Module", bench);
-            },
-            "localip" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_multiline_module!(localip, LocalIPInfo, get_local_ips, known_outputs.localip, config, log_errors, output, );
-                print_bench_time(args.benchmark, args.benchmark_warn, "Local IP Module", bench);
-            }
-            "theme" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(theme, ThemeInfo, get_theme, known_outputs.theme, config, log_errors, output, &mut gtk_settings_cache);
-                print_bench_time(args.benchmark, args.benchmark_warn, "Theme Module", bench);
-            },
-            "icontheme" => {
-                let bench: Option<Instant> = benchmark_point(args.benchmark); 
-                run_generic_module!(icon_theme, IconThemeInfo, get_icon_theme, known_outputs.icon_theme, config, log_errors, output, &mut gtk_settings_cache);
-                print_bench_time(args.benchmark, args.benchmark_warn, "Icon Theme Module", bench);
-            },
-
             // i hate what's below as well, don't worry
             "colors" => {
                 let bench: Option<Instant> = benchmark_point(args.benchmark); 
@@ -686,27 +569,27 @@ fn main() {
                 } else if module_split.len() <= 1 {
                     output.push((String::new(), "Command module invoked, but no command to run.".to_string()));
                 } else {
-                    let command: Output = Command::new("sh")
-                        .arg("-c")
-                        .arg(module_split[1])
-                        .output()
-                        .expect("Failed to execute command.");
-
-                    if command.status.success() {
-                        let result = String::from_utf8(command.stdout).unwrap_or("Command failed: Unable to parse output.".to_string());
-                        output.push((String::new(), result));
-                    } else {
-                        output.push((String::new(), format!("Command failed: {}", String::from_utf8(command.stderr).unwrap_or("Unknown command.".to_string()))));
-                    }
+                    output.push(scheduler::run_command_module(config, module_split[1]));
                 }
 
-                print_bench_time(args.benchmark, args.benchmark_warn, &format!("Command: {}", module_split[1]), bench);
+                print_bench_time(args.benchmark, args.benchmark_warn, &format!("Command: {}", module_split.get(1).unwrap_or(&"")), bench);
             }
             _ => {
                 let bench: Option<Instant> = benchmark_point(args.benchmark); 
 
+                if let Some(registered) = modules::find_module(module_name) {
+                    match known_outputs.get(registered.name) {
+                        Some(Ok(x)) => output.extend(x.style_output(config)),
+                        Some(Err(e)) if e.is_timeout() => output.push(scheduler::timed_out_output(config, registered.name, registered.unknown_output)),
+                        Some(Err(e)) if log_errors => output.push((String::new(), e.to_string())),
+                        _ => output.push((registered.unknown_output)(config)),
+                    }
+                    print_bench_time(args.benchmark, args.benchmark_warn, &format!("{module_name} Module"), bench);
+                    continue;
+                }
+
                 if config.unknown_as_text {
-                    output.push((String::new(), formatter::replace_color_placeholders(module_name, &config)));
+                    output.push((String::new(), formatter::replace_color_placeholders(module_name, config)));
                 } else {
                     output.push((String::new(), format!("Unknown module: {module_name}")));
                 }
@@ -714,120 +597,222 @@ fn main() {
                 print_bench_time(args.benchmark, args.benchmark_warn, "Unknown Module / Custom Text", bench);
             }
         }
-        print_bench_time(args.benchmark, args.benchmark_warn, "  Entire Module Parse/Detection", module_parse_bench);
+        print_bench_time(args.benchmark, args.benchmark_warn, "  Entire Module Parse/Style", module_parse_bench);
     }
-    print_bench_time(args.benchmark, args.benchmark_warn, "Entire detection step", detect_bench);
+    markers.end_segment(output.len(), false);
 
+    (output, markers)
+}
 
-    // 
-    //  Display
-    //
-    let ascii_bench: Option<Instant> = benchmark_point(args.benchmark); 
-    let mut ascii_split: Vec<&str> = Vec::new();
-    let mut ascii_length: usize = 0;
-    let mut ascii_target_length: u16 = 0;
-    // :(
-    let fuck_off_borrow_checker: String;
-    if config.ascii.display {
-        if known_outputs.os.is_none() {
-            let os_bench: Option<Instant> = benchmark_point(args.benchmark); 
-            known_outputs.os = Some(os::get_os(&config, &mut syscall_cache));
-            print_bench_time(args.benchmark, args.benchmark_warn, "OS (for ASCII)", os_bench);
+// Draws the styled lines alongside the ASCII, returning it all as one string ready to be printed
+fn draw(config: &Configuration, output: &[(String, String)], markers: &LineMarkers, ascii_split: &[&str], ascii_target_length: u16, logo_image: Option<&LogoImage>, inline_values: bool) -> String {
+    let mut frame: String = String::new();
+    let mut ascii_length: usize = ascii_split.len();
+
+    // Checked each draw, so watch mode keeps up with the terminal being resized
+    let columns: Option<usize> = if config.fit_to_terminal {formatter::terminal_columns()} else {None};
+    let mut side: &str = &config.ascii.side;
+    let mut display_ascii: bool = config.ascii.display;
+    if display_ascii && (side == "left" || side == "right") && columns.is_some_and(|x| x < config.ascii.min_terminal_width as usize) {
+        side = &config.ascii.narrow_side;
+        // Moving it won't help if it's wider than the terminal by itself
+        if !(side == "top" || side == "bottom") || columns.is_some_and(|x| ascii_target_length as usize > x) {
+            display_ascii = false;
+            ascii_length = 0;
         }
-        if known_outputs.os.as_ref().unwrap().is_ok() {
-            // Calculate the ASCII stuff while we're here
-            let ascii: (String, u16) = if args.distro_override.is_some() {
-                ascii::find_ascii(&args.distro_override.clone().unwrap(), args.ignore_custom_ascii)
-            } else {
-                ascii::find_ascii(&known_outputs.os.as_ref().unwrap().as_ref().unwrap().distro_id, args.ignore_custom_ascii)
-            };
-            fuck_off_borrow_checker = ascii.0;
-            ascii_split = fuck_off_borrow_checker.split('\n').filter(|x| x.trim() != "").collect();
-            ascii_length = ascii_split.len();
-            ascii_target_length = ascii.1 + config.ascii.margin;
+    }
+
+    let ascii_width: usize = if display_ascii && (side == "left" || side == "right") {ascii_target_length as usize} else {0};
+    let max_width: Option<usize> = columns.map(|x| x.saturating_sub(ascii_width));
+    let column_ranges: Vec<Range<usize>> = layout::split_columns(output.len(), &markers.column_breaks, config.columns);
+    let laid_out_output: Vec<(String, String)>;
+    let mut output: &[(String, String)] = output;
+    let mut inline_values: bool = inline_values;
+    if column_ranges.len() > 1 || config.boxes.mode != BoxMode::None {
+        // The info box goes around all the columns, while segment boxes are drawn within each one
+        let info_padding: usize = if config.boxes.mode == BoxMode::Info {layout::BOX_PADDING} else {0};
+        let segment_padding: usize = if config.boxes.mode == BoxMode::Segments {layout::BOX_PADDING} else {0};
+        let margins: usize = config.column_margin as usize * (column_ranges.len() - 1);
+        let column_width: Option<usize> = max_width.map(|x| x.saturating_sub(info_padding + margins) / column_ranges.len());
+
+        let columns: Vec<Vec<String>> = column_ranges.into_iter()
+            .map(|range| {
+                let lines: Vec<(String, String)> = match column_width {
+                    Some(column_width) => fit_to_width(&output[range.clone()], column_width.saturating_sub(segment_padding), inline_values, &config.ellipsis),
+                    None => output[range.clone()].to_vec(),
+                };
+                let lines: Vec<String> = layout::join_lines(&lines, inline_values);
+                if config.boxes.mode == BoxMode::Segments {
+                    layout::draw_boxes(&lines, &markers.segments_in(&range), config, column_width)
+                } else {
+                    lines
+                }
+            })
+            .collect();
+        let mut lines: Vec<String> = layout::join_columns(&columns, config.column_margin);
+        if config.boxes.mode == BoxMode::Info {
+            lines = layout::draw_boxes(&lines, &[], config, max_width);
         }
+
+        // The titles are joined to the values now, so they're no longer inline
+        laid_out_output = lines.into_iter().map(|x| (String::new(), x)).collect();
+        output = &laid_out_output;
+        inline_values = false;
+    } else if let Some(max_width) = max_width {
+        laid_out_output = fit_to_width(output, max_width, inline_values, &config.ellipsis);
+        output = &laid_out_output;
     }
 
     // get the maximum module line length for both right side ascii + inline values
     let mut max_title_len: usize = 0;
     let mut max_total_len: usize = 0;
     // no need to even calculate it if not
-    if config.ascii.side == "right" || inline_values {
-        for out in &output {
-            max_title_len = max(max_title_len, strip_ansi_escapes::strip_str(&out.0).chars().count());
-            max_total_len = max(max_total_len, strip_ansi_escapes::strip_str(&out.0).chars().count() + strip_ansi_escapes::strip_str(&out.1).chars().count());
+    if side == "right" || inline_values {
+        for out in output {
+            max_title_len = max(max_title_len, formatter::display_width(&out.0));
+            max_total_len = max(max_total_len, formatter::display_width(&out.0) + formatter::display_width(&out.1));
         }
         if inline_values {
             max_total_len += max_title_len;
         }
     }
 
-    print_bench_time(args.benchmark, args.benchmark_warn, "Display ASCII Pre-Calc", ascii_bench);
-
-    // the actual outputs
-    let bench: Option<Instant> = benchmark_point(args.benchmark); 
     let mut current_line: usize = 0;
     // top ascii
-    if config.ascii.display && config.ascii.side == "top" {
+    if display_ascii && side == "top" {
         #[allow(clippy::mut_range_bound)]
         for _ in current_line..ascii_length {
-            println!("{}", get_ascii_line(current_line, &ascii_split, ascii_target_length, &config));
+            frame.push_str(&get_ascii_line(current_line, ascii_split, ascii_target_length, config));
+            frame.push('\n');
             current_line += 1;
         }
         // Margin
-        print!("{}", "\n".repeat(config.ascii.margin as usize));
+        frame.push_str(&"\n".repeat(config.ascii.margin as usize));
     }
 
     // the modules + left/right ascii alongside them
     for out in output {
         // left ascii
-        if config.ascii.display && config.ascii.side == "left" {
-            print!("{}", get_ascii_line(current_line, &ascii_split, ascii_target_length, &config));
+        if display_ascii && side == "left" {
+            frame.push_str(&get_ascii_line(current_line, ascii_split, ascii_target_length, config));
         }
 
-        let title_len: usize = strip_ansi_escapes::strip_str(&out.0).chars().count();
+        let title_len: usize = formatter::display_width(&out.0);
         let title_len_inline: usize = if inline_values && !out.0.is_empty() {max_title_len - title_len} else {0};
-        print!("{}", out.0); // title
+        frame.push_str(&out.0); // title
         if inline_values && !out.0.is_empty() {
-            print!("{}", " ".repeat(title_len_inline));
+            frame.push_str(&" ".repeat(title_len_inline));
         }
-        print!("{}", out.1); // value
+        frame.push_str(&out.1); // value
 
         // right ascii
-        if config.ascii.display && config.ascii.side == "right" {
+        if display_ascii && side == "right" {
             // This manually adds the margin to the right, as get_ascii_line only does the left
-            let line_length_remainder: usize = max_total_len - (title_len + title_len_inline + strip_ansi_escapes::strip_str(&out.1).chars().count());
-            print!("{}", " ".repeat(line_length_remainder + config.ascii.margin as usize));
-            print!("{}", get_ascii_line(current_line, &ascii_split, ascii_target_length - config.ascii.margin, &config));
+            let line_length_remainder: usize = max_total_len - (title_len + title_len_inline + formatter::display_width(&out.1));
+            frame.push_str(&" ".repeat(line_length_remainder + config.ascii.margin as usize));
+            frame.push_str(&get_ascii_line(current_line, ascii_split, ascii_target_length - config.ascii.margin, config));
         }
 
         current_line += 1;
-        println!();
+        frame.push('\n');
     }
     // bottom ascii
-    if config.ascii.display && config.ascii.side == "bottom" {
+    if display_ascii && side == "bottom" {
         // Margin
-        print!("{}", "\n".repeat(config.ascii.margin as usize));
+        frame.push_str(&"\n".repeat(config.ascii.margin as usize));
 
         for x in 0..ascii_length {
-            println!("{}", get_ascii_line(x, &ascii_split, ascii_target_length, &config));
+            frame.push_str(&get_ascii_line(x, ascii_split, ascii_target_length, config));
+            frame.push('\n');
         }
     }
 
     // remaning ascii from left/right in case we're using less modules than required lines
-    if current_line < ascii_length && config.ascii.display && (config.ascii.side == "left" || config.ascii.side == "right") {
+    if current_line < ascii_length && display_ascii && (side == "left" || side == "right") {
         let mut ascii_line: usize = current_line;
         for _ in current_line..ascii_length {
-            if config.ascii.side == "right" {
-                print!("{}", " ".repeat(max_total_len + config.ascii.margin as usize));
+            if side == "right" {
+                frame.push_str(&" ".repeat(max_total_len + config.ascii.margin as usize));
             }
-            print!("{}", get_ascii_line(ascii_line, &ascii_split, ascii_target_length, &config));
+            frame.push_str(&get_ascii_line(ascii_line, ascii_split, ascii_target_length, config));
             ascii_line += 1;
-            println!();
+            frame.push('\n');
         }
     }
-    print_bench_time(args.benchmark, args.benchmark_warn, "Module + ASCII Output", bench);
 
-    print_bench_time(args.benchmark, args.benchmark_warn, "Full Runtime of CrabFetch", full_runtime_bench);
+    // The image goes over the space left for it, now the cursor's not going to move anymore
+    if let Some(logo_image) = logo_image.filter(|_| display_ascii) {
+        let (top_line, column): (usize, usize) = match side {
+            "right" => (0, max_total_len + config.ascii.margin as usize),
+            "bottom" => (output.len() + config.ascii.margin as usize, 0),
+            _ => (0, 0),
+        };
+        frame.push_str(&logo_image.place(frame.matches('\n').count() - top_line, column));
+    }
+
+    frame
 }
 
+// Cuts the lines down to fit in the width left for them, the values first then the titles if even they don't fit
+fn fit_to_width(output: &[(String, String)], width: usize, inline_values: bool, ellipsis: &str) -> Vec<(String, String)> {
+    let titles: Vec<String> = output.iter()
+        .map(|x| formatter::truncate(&x.0, width, ellipsis))
+        .collect();
+    let max_title_len: usize = titles.iter()
+        .map(|x| formatter::display_width(x))
+        .max()
+        .unwrap_or(0);
+
+    titles.into_iter()
+        .zip(output)
+        .map(|(title, out)| {
+            let title_len: usize = if inline_values && !title.is_empty() {max_title_len} else {formatter::display_width(&title)};
+            let value: String = formatter::truncate(&out.1, width.saturating_sub(title_len), ellipsis);
+            (title, value)
+        })
+        .collect()
+}
+
+// Set once watch mode's been asked to stop, e.g by Ctrl+C
+static WATCH_STOPPED: AtomicBool = AtomicBool::new(false);
+extern "C" fn stop_watching(_: libc::c_int) {
+    WATCH_STOPPED.store(true, Ordering::SeqCst);
+}
+
+// Redraws the fetch in place on the terminal's alternate screen every interval, until it's stopped
+// The alternate screen leaves whatever was in the terminal beforehand untouched once it's done
+fn watch<F>(interval: Duration, first_frame: String, mut next_frame: F) where F: FnMut() -> String {
+    let handler: extern "C" fn(libc::c_int) = stop_watching;
+    unsafe {
+        libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+        libc::signal(libc::SIGTERM, handler as libc::sighandler_t);
+    }
+
+    // Alternate screen + hide the cursor
+    print!("\x1b[?1049h\x1b[?25l");
+    let mut frame: String = first_frame;
+    loop {
+        draw_in_place(&frame);
+
+        // Slept in small steps, so stopping doesn't wait on the rest of the interval
+        let tick_start: Instant = Instant::now();
+        while !WATCH_STOPPED.load(Ordering::SeqCst) && tick_start.elapsed() < interval {
+            thread::sleep(interval.saturating_sub(tick_start.elapsed()).min(Duration::from_millis(50)));
+        }
+        if WATCH_STOPPED.load(Ordering::SeqCst) {
+            break;
+        }
+
+        frame = next_frame();
+    }
+    // Show the cursor + back to the normal screen
+    print!("\x1b[?25h\x1b[?1049l");
+    let _ = io::stdout().flush();
+}
+fn draw_in_place(frame: &str) {
+    // Drawn over the top of the last frame, clearing the rest of each line as it goes and anything
+    // below once it's done, so nothing's left over if this frame is any shorter
+    let lines: Vec<&str> = frame.lines().collect();
+    print!("\x1b[H{}\x1b[K\x1b[J", lines.join("\x1b[K\r\n"));
+    let _ = io::stdout().flush();
+}
@@ -1,8 +1,11 @@
-use std::fmt::{Debug, Display};
+use std::{any::Any, collections::HashMap, fmt::{Debug, Display}, time::Duration};
 
 use colored::{ColoredString, Colorize};
+use config::{builder::DefaultState, Config, ConfigBuilder, ConfigError};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Map, Value};
 
-use crate::{config_manager::Configuration, formatter::{self, CrabFetchColor}};
+use crate::{cache::CachePolicy, common_sources::{gtk::GTKSettingsCache, package_managers::ManagerInfo, syscalls::SyscallCache}, config_check::{self, SchemaProbe}, config_manager::Configuration, formatter::{self, CrabFetchColor}};
 
 pub trait Module {
     fn new() -> Self;
@@ -37,20 +40,189 @@ pub trait Module {
     fn replace_color_placeholders(&self, str: &str, config: &Configuration) -> String {
         formatter::replace_color_placeholders(str, config)
     }
+
+    // The lines this module outputs, which is just the styled line for most modules
+    fn style_lines(&self, config: &Configuration) -> Vec<(String, String)> {
+        vec![self.style(config)]
+    }
+    // Same as above but for modules that detect multiple entries, e.g GPUs or mounts
+    fn style_list(list: &[Self], config: &Configuration) -> Vec<(String, String)> where Self: Sized {
+        list.iter()
+            .flat_map(|x| x.style_lines(config))
+            .collect()
+    }
+}
+
+// The object-safe side of a module
+// This lets a module's detected info be stored, rendered and serialized without knowing its type
+pub trait ModuleOutput: Send {
+    fn style_output(&self, config: &Configuration) -> Vec<(String, String)>;
+    fn to_json(&self) -> Value;
+    fn as_any(&self) -> &dyn Any;
+}
+impl<T: Module + Serialize + Send + 'static> ModuleOutput for T {
+    fn style_output(&self, config: &Configuration) -> Vec<(String, String)> {
+        self.style_lines(config)
+    }
+    fn to_json(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+impl<T: Module + Serialize + Send + 'static> ModuleOutput for Vec<T> {
+    fn style_output(&self, config: &Configuration) -> Vec<(String, String)> {
+        T::style_list(self, config)
+    }
+    fn to_json(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub type Detected = Result<Box<dyn ModuleOutput>, ModuleError>;
+pub fn boxed<T: ModuleOutput + 'static>(result: Result<T, ModuleError>) -> Detected {
+    result.map(|x| Box::new(x) as Box<dyn ModuleOutput>)
+}
+// The reverse of ModuleOutput::to_json, used to bring a module's info back out of the cache
+pub fn restore<T: ModuleOutput + DeserializeOwned + 'static>(info: Value) -> Option<Box<dyn ModuleOutput>> {
+    serde_json::from_value::<T>(info).ok()
+        .map(|x| Box::new(x) as Box<dyn ModuleOutput>)
+}
+
+// How a module is detected, and the shared inputs it needs for it
+// The scheduler uses this to figure out which thread it can be ran on
+pub enum Detector {
+    Independent(fn(&Configuration) -> Detected),
+    Syscalls(fn(&Configuration, &mut SyscallCache) -> Detected),
+    GTKSettings(fn(&mut GTKSettingsCache) -> Detected),
+    PackageManagers(fn(&Configuration, &ManagerInfo) -> Detected),
+}
+
+// A module's own section of the config, e.g [cpu]
+// It's deserialized into the module's own type, which the module gets back out with Configuration::section
+pub type Section = Box<dyn Any + Send + Sync>;
+pub struct ConfigSection {
+    pub deserialize: fn(&Config, &str) -> Result<Section, ConfigError>,
+    // Notes down the keys the section can have, for --check-config
+    pub probe: fn(SchemaProbe),
+}
+impl ConfigSection {
+    pub const fn of<T: DeserializeOwned + Send + Sync + 'static>() -> ConfigSection {
+        ConfigSection {
+            deserialize: deserialize_section::<T>,
+            probe: config_check::probe::<T>,
+        }
+    }
+}
+fn deserialize_section<T: DeserializeOwned + Send + Sync + 'static>(config: &Config, name: &str) -> Result<Section, ConfigError> {
+    config.get::<T>(name).map(|x| Box::new(x) as Section)
+}
+
+// A module's entry in the registry, see modules::MODULES
+pub struct RegisteredModule {
+    // The name used in the config's module list, e.g "cpu"
+    // This is also the name of its config section
+    pub name: &'static str,
+    pub config: ConfigSection,
+    // Fills in the defaults of the module's config section
+    pub fill_builder_defaults: fn(ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState>,
+    pub detect: Detector,
+    pub unknown_output: fn(&Configuration) -> (String, String),
+    // The placeholders the module's title and formats can use, without the braces
+    // Only used to warn about any others in --check-config
+    pub placeholders: &'static [&'static str],
+    // Whether the module's info changes from moment to moment, e.g memory or uptime
+    // These are re-detected on every redraw of --watch, while everything else is kept
+    pub volatile: bool,
+    // Whether the module's info can be kept between runs, see cache.rs
+    pub cache: Option<CachePolicy>,
+}
+
+// Stores all the module's outputs as we know them, keyed by the module's name
+// This is to prevent us doing additional work when we don't need to, when modules need shared data
+#[derive(Default)]
+pub struct ModuleOutputs {
+    outputs: HashMap<&'static str, Detected>
+}
+impl ModuleOutputs {
+    pub fn new() -> Self {
+        Self {
+            outputs: HashMap::new()
+        }
+    }
+
+    pub fn get(&self, module: &str) -> Option<&Detected> {
+        self.outputs.get(module)
+    }
+    pub fn contains(&self, module: &str) -> bool {
+        self.outputs.contains_key(module)
+    }
+    pub fn insert(&mut self, module: &'static str, detected: Detected) {
+        self.outputs.insert(module, detected);
+    }
+    // Forgets any module's output that doesn't match, so that it's detected again next time
+    pub fn retain<F>(&mut self, keep: F) where F: Fn(&str, &Detected) -> bool {
+        self.outputs.retain(|module, detected| keep(module, detected));
+    }
+    // Gets a module's info as its concrete type, if it was detected successfully
+    pub fn get_info<T: 'static>(&self, module: &str) -> Option<&T> {
+        match self.outputs.get(module)? {
+            Ok(x) => x.as_any().downcast_ref::<T>(),
+            Err(_) => None,
+        }
+    }
+
+    // Gathers the raw data of every module in the list into a single JSON object, keyed by the
+    // module's name
+    // Errors are kept as an object under "error" so they can be told apart from the module's data
+    pub fn to_json(&self, modules: &[String]) -> Map<String, Value> {
+        let mut map: Map<String, Value> = Map::new();
+        for module in modules {
+            let module_name: &str = module.split(':').next().unwrap_or(module);
+            let value: Value = match self.outputs.get(module_name) {
+                Some(Ok(x)) => x.to_json(),
+                Some(Err(e)) => json!({ "error": e }),
+                None => continue,
+            };
+            map.insert(module_name.to_string(), value);
+        }
+
+        map
+    }
 }
 
 // A generic module error
+#[derive(Serialize)]
 pub struct ModuleError {
     module_name: String,
-    message: String
+    message: String,
+    // Set when the module was given up on by the scheduler, rather than failing by itself
+    // Kept in the JSON output too, so scripts can tell a timeout apart from the module not being detected
+    timed_out: bool
 }
 impl ModuleError {
     pub fn new(module: &str, message: String) -> ModuleError {
         ModuleError {
             module_name: module.to_string(),
-            message
+            message,
+            timed_out: false
         }
     }
+    pub fn timed_out(module: &str, timeout: Duration) -> ModuleError {
+        ModuleError {
+            module_name: module.to_string(),
+            message: format!("Timed out after {}ms", timeout.as_millis()),
+            timed_out: true
+        }
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        self.timed_out
+    }
 }
 impl Display for ModuleError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -62,3 +234,20 @@ impl Debug for ModuleError {
         write!(f, "Module {} failed: {}", self.module_name, self.message)
     }
 }
+
+mod tests {
+    #[test]
+    fn test_json_order() {
+        use crate::module::{ModuleError, ModuleOutputs};
+
+        let mut outputs: ModuleOutputs = ModuleOutputs::new();
+        outputs.insert("cpu", Err(ModuleError::new("CPU", "Nope".to_string())));
+        outputs.insert("uptime", Err(ModuleError::new("Uptime", "Nope".to_string())));
+        outputs.insert("memory", Err(ModuleError::new("Memory", "Nope".to_string())));
+
+        // In the config's order rather than alphabetical, with anything that isn't a module left out
+        let modules: Vec<String> = ["uptime", "space", "memory", "cpu"].map(String::from).to_vec();
+        let json = outputs.to_json(&modules);
+        assert_eq!(json.keys().collect::<Vec<&String>>(), vec!["uptime", "memory", "cpu"]);
+    }
+}
@@ -1,13 +1,23 @@
 use std::{fs::{self, DirEntry, ReadDir}, path::PathBuf};
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::{self, CrabFetchColor}, module::Module, util, ModuleError};
+use crate::{config_manager::Configuration, formatter::{self, CrabFetchColor}, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template, util, ModuleError};
 
+#[derive(Serialize)]
 pub struct BatteryInfo {
     index: String,
     percentage: f32,
 }
+impl BatteryInfo {
+    pub fn index(&self) -> &str {
+        &self.index
+    }
+    pub fn percentage(&self) -> f32 {
+        self.percentage
+    }
+}
 #[derive(Deserialize)]
 pub struct BatteryConfiguration {
     pub title: String,
@@ -32,48 +42,53 @@ impl Module for BatteryInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.battery.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.battery.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.battery.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.battery.separator.as_ref().unwrap_or(&config.separator);
+        let section: &BatteryConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.battery.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.battery.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.battery.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.battery.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.battery.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.battery.separator.as_ref().unwrap_or(&config.separator);
-
-        let title: String = config.battery.title
-            .replace("{index}", "0").to_string()
-            .replace("{percentage}", "Unknown").to_string()
-            .replace("{bar}", "").to_string();
+        let section: &BatteryConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
+
+        let title: String = template::render(&section.title, &|name| match name {
+            "index" => Some("0".to_string()),
+            _ => MODULE.placeholders.contains(&name).then(|| template::UNKNOWN.to_string())
+        });
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, config: &Configuration) -> String {
-        let dec_places: u32 = config.battery.decimal_places.unwrap_or(config.decimal_places);
-
-        let mut bar: String = String::new();
-        if text.contains("{bar}") {
-            let left_border: &str = config.battery.progress_left_border.as_ref().unwrap_or(&config.progress_left_border);
-            let right_border: &str = config.battery.progress_right_border.as_ref().unwrap_or(&config.progress_right_border);
-            let progress: &str = config.battery.progress_progress.as_ref().unwrap_or(&config.progress_progress);
-            let empty: &str = config.battery.progress_empty.as_ref().unwrap_or(&config.progress_empty);
-            let length: u8 = config.battery.progress_target_length.unwrap_or(config.progress_target_length);
-            formatter::make_bar(&mut bar, left_border, right_border, progress, empty, self.percentage, length);
-        }
-
-        #[allow(clippy::cast_possible_truncation)]
-        formatter::process_percentage_placeholder(text, formatter::round(f64::from(self.percentage), dec_places) as f32, config)
-            .replace("{index}", &self.index)
-            .replace("{percentage}", &self.percentage.to_string())
-            .replace("{bar}", &bar)
+        let section: &BatteryConfiguration = config.section();
+        let dec_places: u32 = section.decimal_places.unwrap_or(config.decimal_places);
+
+        template::render(text, &|name| match name {
+            #[allow(clippy::cast_possible_truncation)]
+            "percent" => Some(formatter::percentage_string(formatter::round(f64::from(self.percentage), dec_places) as f32, config)),
+            "index" => Some(self.index.clone()),
+            "percentage" => Some(self.percentage.to_string()),
+            "bar" => {
+                let left_border: &str = section.progress_left_border.as_ref().unwrap_or(&config.progress_left_border);
+                let right_border: &str = section.progress_right_border.as_ref().unwrap_or(&config.progress_right_border);
+                let progress: &str = section.progress_progress.as_ref().unwrap_or(&config.progress_progress);
+                let empty: &str = section.progress_empty.as_ref().unwrap_or(&config.progress_empty);
+                let length: u8 = section.progress_target_length.unwrap_or(config.progress_target_length);
+                let mut bar: String = String::new();
+                formatter::make_bar(&mut bar, left_border, right_border, progress, empty, self.percentage, length);
+                Some(bar)
+            },
+            _ => None
+        })
     }
 
     fn gen_info_flags(_: &str) -> u32 {
@@ -81,10 +96,27 @@ impl Module for BatteryInfo {
     }
 }
 
-pub fn get_batteries() -> Result<Vec<BatteryInfo>, ModuleError> {
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "battery",
+    config: ConfigSection::of::<BatteryConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|config| module::boxed(get_batteries(config))),
+    unknown_output: BatteryInfo::unknown_output,
+    placeholders: &["index", "percentage", "percent", "bar"],
+    volatile: true,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("battery.title", "Battery {index}").unwrap();
+    builder = builder.set_default("battery.format", "{percentage}%").unwrap();
+
+    builder
+}
+
+pub fn get_batteries(config: &Configuration) -> Result<Vec<BatteryInfo>, ModuleError> {
     let mut batteries: Vec<BatteryInfo> = Vec::new();
 
-    let dir: ReadDir = match fs::read_dir("/sys/class/power_supply/") {
+    let dir: ReadDir = match fs::read_dir(util::root_path(&config.root, "/sys/class/power_supply/")) {
         Ok(r) => r,
         Err(e) => return Err(ModuleError::new("Battery", format!("Can't read from /sys/class/power_supply: {e}"))),
     };
@@ -1,14 +1,16 @@
 use core::str;
-use std::{fs::{read_dir, File, ReadDir}, io::{BufRead, BufReader, Read}, path::{Component, Path}};
+use std::{fs::{read_dir, File, ReadDir}, io::{BufRead, BufReader, Read}, path::{Component, Path, PathBuf}};
 
 #[cfg(feature = "android")]
 use {android_system_properties::AndroidSystemProperties, std::env};
 #[cfg(target_arch = "x86_64")]
 use raw_cpuid::CpuId;
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::{self, CrabFetchColor}, module::Module, util::{self, is_flag_set_u32}, ModuleError};
+use crate::{cache::{CachePolicy, CacheSignal}, config_manager::Configuration, formatter::{self, CrabFetchColor}, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, util::{self, is_flag_set_u32}, ModuleError};
 
+#[derive(Serialize, Deserialize)]
 pub struct CPUInfo {
     name: String,
     cores: u16,
@@ -17,6 +19,26 @@ pub struct CPUInfo {
     max_clock_mhz: f32,
     arch: String
 }
+impl CPUInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn cores(&self) -> u16 {
+        self.cores
+    }
+    pub fn threads(&self) -> u16 {
+        self.threads
+    }
+    pub fn current_clock_mhz(&self) -> f32 {
+        self.current_clock_mhz
+    }
+    pub fn max_clock_mhz(&self) -> f32 {
+        self.max_clock_mhz
+    }
+    pub fn arch(&self) -> &str {
+        &self.arch
+    }
+}
 #[derive(Deserialize)]
 pub struct CPUConfiguration {
     pub title: String,
@@ -42,68 +64,66 @@ impl Module for CPUInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.cpu.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.cpu.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.cpu.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.cpu.separator.as_ref().unwrap_or(&config.separator);
+        let section: &CPUConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.cpu.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.cpu.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.cpu.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.cpu.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.cpu.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.cpu.separator.as_ref().unwrap_or(&config.separator);
-
-        let title: String = config.cpu.title
-            .replace("{name}", "Unknown")
-            .replace("{core_count}", "Unknown")
-            .replace("{thread_count}", "Unknown")
-            .replace("{current_clock_mhz}", "Unknown")
-            .replace("{current_clock_ghz}", "Unknown")
-            .replace("{max_clock_mhz}", "Unknown")
-            .replace("{max_clock_ghz}", "Unknown")
-            .replace("{arch}", "Unknown");
+        let section: &CPUConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
+
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
         
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, config: &Configuration) -> String {
-        let dec_places: u32 = config.cpu.decimal_places.unwrap_or(config.decimal_places);
-
-        text.replace("{name}", &self.name)
-            .replace("{core_count}", &self.cores.to_string())
-            .replace("{thread_count}", &self.threads.to_string())
-            .replace("{current_clock_mhz}", &formatter::round(f64::from(self.current_clock_mhz), dec_places).to_string())
-            .replace("{current_clock_ghz}", &formatter::round(f64::from(self.current_clock_mhz / 1000.0), dec_places).to_string())
-            .replace("{max_clock_mhz}", &formatter::round(f64::from(self.max_clock_mhz), dec_places).to_string())
-            .replace("{max_clock_ghz}", &formatter::round(f64::from(self.max_clock_mhz / 1000.0), dec_places).to_string())
-            .replace("{arch}", &self.arch.to_string())
+        let dec_places: u32 = config.section::<CPUConfiguration>().decimal_places.unwrap_or(config.decimal_places);
+
+        template::render(text, &|name| match name {
+            "name" => Some(self.name.clone()),
+            "core_count" => Some(self.cores.to_string()),
+            "thread_count" => Some(self.threads.to_string()),
+            "current_clock_mhz" => Some(formatter::round(f64::from(self.current_clock_mhz), dec_places).to_string()),
+            "current_clock_ghz" => Some(formatter::round(f64::from(self.current_clock_mhz / 1000.0), dec_places).to_string()),
+            "max_clock_mhz" => Some(formatter::round(f64::from(self.max_clock_mhz), dec_places).to_string()),
+            "max_clock_ghz" => Some(formatter::round(f64::from(self.max_clock_mhz / 1000.0), dec_places).to_string()),
+            "arch" => Some(self.arch.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         // Figure out the info we need to fetch
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
-        if format.contains("{name}") {
+        if template.uses("name") {
             info_flags |= CPU_INFOFLAG_MODEL_NAME;
         }
-        if format.contains("{core_count}") {
+        if template.uses("core_count") {
             info_flags |= CPU_INFOFLAG_CORES;
         }
-        if format.contains("{thread_count}") {
+        if template.uses("thread_count") {
             info_flags |= CPU_INFOFLAG_THREADS;
         }
-        if format.contains("{current_clock_mhz}") || format.contains("{current_clock_ghz}") {
+        if template.uses("current_clock_mhz") || template.uses("current_clock_ghz") {
             info_flags |= CPU_INFOFLAG_CURRENT_CLOCK;
         }
-        if format.contains("{max_clock_mhz}") || format.contains("{max_clock_ghz}") {
+        if template.uses("max_clock_mhz") || template.uses("max_clock_ghz") {
             info_flags |= CPU_INFOFLAG_MAX_CLOCK;
         }
-        if format.contains("{arch}") || format.contains("{arch}") {
+        if template.uses("arch") {
             info_flags |= CPU_INFOFLAG_ARCH;
         }
 
@@ -118,21 +138,50 @@ const CPU_INFOFLAG_CURRENT_CLOCK: u32 = 8;
 const CPU_INFOFLAG_MAX_CLOCK: u32 = 16;
 const CPU_INFOFLAG_ARCH: u32 = 32;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "cpu",
+    config: ConfigSection::of::<CPUConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|config| module::boxed(get_cpu(config))),
+    unknown_output: CPUInfo::unknown_output,
+    placeholders: &["name", "core_count", "thread_count", "current_clock_mhz", "current_clock_ghz", "max_clock_mhz", "max_clock_ghz", "arch"],
+    // Only for the current clock, the rest of it won't change
+    volatile: true,
+    cache: Some(CachePolicy {
+        invalidated_by: CacheSignal::KernelRelease,
+        // The current clock's never the same twice, so it'd be stuck at whatever it was when cached
+        config_key: |config| {
+            let section: &CPUConfiguration = config.section();
+            (CPUInfo::gen_info_flags(&section.format) & CPU_INFOFLAG_CURRENT_CLOCK == 0)
+                .then(|| format!("{}|{}", section.format, section.remove_trailing_processor))
+        },
+        restore: module::restore::<CPUInfo>,
+    }),
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("cpu.title", "CPU").unwrap();
+    builder = builder.set_default("cpu.format", "{name} ({core_count}c {thread_count}t) @ {max_clock_ghz} GHz").unwrap();
+    builder = builder.set_default("cpu.remove_trailing_processor", true).unwrap();
+
+    builder
+}
+
 pub fn get_cpu(config: &Configuration) -> Result<CPUInfo, ModuleError> {
+    let section: &CPUConfiguration = config.section();
     let mut cpu: CPUInfo = CPUInfo::new();
-    let info_flags: u32 = CPUInfo::gen_info_flags(&config.cpu.format);
+    let info_flags: u32 = CPUInfo::gen_info_flags(&section.format);
 
     // This ones split into 2 as theres a lot to parse
-    match get_basic_info(&mut cpu, info_flags) {
+    match get_basic_info(&mut cpu, info_flags, &config.root) {
         Ok(_) => {},
         Err(e) => return Err(e)
     };
-    match get_max_clock(&mut cpu, info_flags) {
+    match get_max_clock(&mut cpu, info_flags, &config.root) {
         Ok(_) => {},
         Err(e) => return Err(e)
     };
 
-    if config.cpu.remove_trailing_processor {
+    if section.remove_trailing_processor {
         // Tried doing this with Regex but it added 400 micro secs so fuck that shit
         let loc: usize = match cpu.name.find("-Core Processor") {
             Some(r) => r,
@@ -155,10 +204,10 @@ pub fn get_cpu(config: &Configuration) -> Result<CPUInfo, ModuleError> {
     Ok(cpu)
 }
 
-fn get_basic_info(cpu: &mut CPUInfo, info_flags: u32) -> Result<(), ModuleError> {
+fn get_basic_info(cpu: &mut CPUInfo, info_flags: u32, root: &str) -> Result<(), ModuleError> {
     // Starts by reading and parsing /proc/cpuinfo
     // This gives us the cpu name, cores, threads and current clock
-    let file: File = match File::open("/proc/cpuinfo") {
+    let file: File = match File::open(util::root_path(root, "/proc/cpuinfo")) {
         Ok(r) => r,
         Err(e) => return Err(ModuleError::new("CPU", format!("Can't read from /proc/cpuinfo - {e}"))),
     };
@@ -236,7 +285,7 @@ fn get_basic_info(cpu: &mut CPUInfo, info_flags: u32) -> Result<(), ModuleError>
         cpu.cores = cores;
         // Backup to /sys/devices/system/cpu/present for threads too
         // Thanks to https://stackoverflow.com/a/30150409
-        let mut file: File = match File::open("/sys/devices/system/cpu/present") {
+        let mut file: File = match File::open(util::root_path(root, "/sys/devices/system/cpu/present")) {
             Ok(r) => r,
             Err(e) => return Err(ModuleError::new("CPU", format!("Can't read from /sys/devices/system/cpu/present - {e}"))),
         };
@@ -268,9 +317,10 @@ fn get_basic_info(cpu: &mut CPUInfo, info_flags: u32) -> Result<(), ModuleError>
     }
 
     #[cfg(target_arch = "x86_64")]
-    if cpu.name == "Unknown" && is_flag_set_u32(info_flags, CPU_INFOFLAG_MODEL_NAME) {
+    if cpu.name == "Unknown" && is_flag_set_u32(info_flags, CPU_INFOFLAG_MODEL_NAME) && Path::new(root) == Path::new("/") {
         // This **seems** to be the only thing that can be missing, mostly on ARM
         // So, we'll use cpuid to grab it
+        // Only done when reading from the real root, as cpuid always describes this machine's CPU
         // Credit to Emma lol https://mastodon.social/@Livaco/113027370696819081
         backup_to_cpuid(cpu);
     }
@@ -294,7 +344,7 @@ fn get_basic_info(cpu: &mut CPUInfo, info_flags: u32) -> Result<(), ModuleError>
     cpu.current_clock_mhz /= f32::from(cpu_mhz_count);
     Ok(())
 }
-fn get_max_clock(cpu: &mut CPUInfo, info_flags: u32) -> Result<(), ModuleError> {
+fn get_max_clock(cpu: &mut CPUInfo, info_flags: u32, root: &str) -> Result<(), ModuleError> {
     if !is_flag_set_u32(info_flags, CPU_INFOFLAG_MAX_CLOCK) {
         return Ok(())
     }
@@ -308,10 +358,10 @@ fn get_max_clock(cpu: &mut CPUInfo, info_flags: u32) -> Result<(), ModuleError>
     //
     // Source: https://docs.kernel.org/admin-guide/pm/cpufreq.html
 
-    let freq_path: Option<&Path> = util::find_first_path_exists(vec![
-        Path::new("/sys/devices/system/cpu/cpu0/cpufreq/bios_limit"),
-        Path::new("/sys/devices/system/cpu/cpu0/cpufreq/scaling_max_freq"),
-        Path::new("/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq")
+    let freq_path: Option<PathBuf> = util::find_first_pathbuf_exists(vec![
+        util::root_path(root, "/sys/devices/system/cpu/cpu0/cpufreq/bios_limit"),
+        util::root_path(root, "/sys/devices/system/cpu/cpu0/cpufreq/scaling_max_freq"),
+        util::root_path(root, "/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq")
     ]);
 
     if freq_path.is_none() {
@@ -322,7 +372,7 @@ fn get_max_clock(cpu: &mut CPUInfo, info_flags: u32) -> Result<(), ModuleError>
         cpu.max_clock_mhz = cpu.current_clock_mhz;
         return Ok(())
     }
-    let freq_path: &Path = freq_path.unwrap();
+    let freq_path: PathBuf = freq_path.unwrap();
     let mut freq_path_str: String = String::new();
     // sheesh
     for comp in freq_path.components().rev().take(2).collect::<Vec<Component<>>>().iter().rev() {
@@ -331,7 +381,7 @@ fn get_max_clock(cpu: &mut CPUInfo, info_flags: u32) -> Result<(), ModuleError>
     }
     let freq_path: &str = &freq_path_str[1..];
     
-    let dir: ReadDir = match read_dir("/sys/devices/system/cpu/") {
+    let dir: ReadDir = match read_dir(util::root_path(root, "/sys/devices/system/cpu/")) {
         Ok(r) => r,
         Err(e) => return Err(ModuleError::new("CPU", format!("Can't read from /sys/devices/system/cpu - {e}")))
     };
@@ -1,11 +1,18 @@
 use chrono::{DateTime, Local};
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{formatter::CrabFetchColor, config_manager::Configuration, module::Module};
+use crate::{formatter::CrabFetchColor, config_manager::Configuration, module::{self, ConfigSection, Detector, Module, RegisteredModule}};
 
+#[derive(Serialize)]
 pub struct DateTimeInfo {
     datetime: DateTime<Local>,
 }
+impl DateTimeInfo {
+    pub fn datetime(&self) -> DateTime<Local> {
+        self.datetime
+    }
+}
 #[derive(Deserialize)]
 pub struct DateTimeConfiguration {
     pub title: String,
@@ -23,22 +30,24 @@ impl Module for DateTimeInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.datetime.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.datetime.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.datetime.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.datetime.separator.as_ref().unwrap_or(&config.separator);
+        let section: &DateTimeConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.datetime.format, config), config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
-        Self::default_style(config, &config.datetime.title, title_color, title_bold, title_italic, separator, &value)
+        Self::default_style(config, &section.title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.datetime.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.datetime.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.datetime.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.datetime.separator.as_ref().unwrap_or(&config.separator);
+        let section: &DateTimeConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        Self::default_style(config, &config.datetime.title, title_color, title_bold, title_italic, separator, "Unknown")
+        Self::default_style(config, &section.title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
@@ -50,6 +59,24 @@ impl Module for DateTimeInfo {
     }
 }
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "datetime",
+    config: ConfigSection::of::<DateTimeConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|_| module::boxed(Ok(get_date_time()))),
+    unknown_output: DateTimeInfo::unknown_output,
+    // The format is a strftime one instead, see chrono's docs
+    placeholders: &[],
+    volatile: true,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("datetime.title", "Date/Time").unwrap();
+    builder = builder.set_default("datetime.format", "%H:%M:%S on %e %B %G").unwrap();
+
+    builder
+}
+
 pub fn get_date_time() -> DateTimeInfo {
     DateTimeInfo::new() // lol
 }
@@ -1,13 +1,23 @@
 use std::env;
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, util::{self, is_flag_set_u32}, ModuleError};
+use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, util::{self, is_flag_set_u32}, ModuleError};
 
+#[derive(Serialize)]
 pub struct DesktopInfo {
     desktop: String,
     display_type: String
 }
+impl DesktopInfo {
+    pub fn desktop(&self) -> &str {
+        &self.desktop
+    }
+    pub fn display_type(&self) -> &str {
+        &self.display_type
+    }
+}
 #[derive(Deserialize)]
 pub struct DesktopConfiguration {
     pub title: String,
@@ -26,41 +36,45 @@ impl Module for DesktopInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.desktop.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.desktop.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.desktop.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.desktop.separator.as_ref().unwrap_or(&config.separator);
+        let section: &DesktopConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.desktop.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.desktop.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.desktop.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.desktop.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.desktop.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.desktop.separator.as_ref().unwrap_or(&config.separator);
+        let section: &DesktopConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.desktop.title
-            .replace("{desktop}", "Unknown")
-            .replace("{display_type}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{desktop}", &self.desktop)
-            .replace("{display_type}", &self.display_type)
+        template::render(text, &|name| match name {
+            "desktop" => Some(self.desktop.clone()),
+            "display_type" => Some(self.display_type.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
-        if format.contains("{desktop}") {
+        if template.uses("desktop") {
             info_flags |= DESKTOP_INFOFLAG_DESKTOP;
         }
-        if format.contains("{display_type}") {
+        if template.uses("display_type") {
             info_flags |= DESKTOP_INFOFLAG_DISPLAY_TYPE;
         }
 
@@ -71,11 +85,28 @@ impl Module for DesktopInfo {
 const DESKTOP_INFOFLAG_DESKTOP: u32 = 1;
 const DESKTOP_INFOFLAG_DISPLAY_TYPE: u32 = 2;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "desktop",
+    config: ConfigSection::of::<DesktopConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|config| module::boxed(get_desktop(config))),
+    unknown_output: DesktopInfo::unknown_output,
+    placeholders: &["desktop", "display_type"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("desktop.title", "Desktop").unwrap();
+    builder = builder.set_default("desktop.format", "{desktop} ({display_type})").unwrap();
+
+    builder
+}
+
 pub fn get_desktop(config: &Configuration) -> Result<DesktopInfo, ModuleError> {
     let mut desktop: DesktopInfo = DesktopInfo::new();
-    let info_flags: u32 = DesktopInfo::gen_info_flags(&config.desktop.format);
+    let info_flags: u32 = DesktopInfo::gen_info_flags(&config.section::<DesktopConfiguration>().format);
 
-    if util::in_wsl() {
+    if util::in_wsl(&config.root) {
         // WSLG weird shit https://github.com/microsoft/wslg
         desktop.desktop = "WSLG".to_string();
         desktop.display_type = "Wayland".to_string();
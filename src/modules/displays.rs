@@ -1,13 +1,14 @@
 use core::str;
 use std::{collections::HashMap, env, fs::{self, read_dir, ReadDir}};
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 use wayland_client::{protocol::{wl_output::{self, Transform}, wl_registry}, ConnectError, Connection, Dispatch, QueueHandle, WEnum};
 use x11rb::{connection::RequestConnection, protocol::{randr::{self, ConnectionExt, GetCrtcInfoReply, GetOutputInfoReply, GetScreenResourcesCurrentReply, ModeInfo, MonitorInfo, Rotation}, xproto::{self, Screen}}};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, util::{self, is_flag_set_u32}, ModuleError};
+use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, util::{self, is_flag_set_u32}, ModuleError};
 
-#[derive(Clone)]
+#[derive(Clone, Serialize)]
 pub struct DisplayInfo {
     name: String,
     make: String,
@@ -19,6 +20,31 @@ pub struct DisplayInfo {
     rotation: u16,
 }
 impl DisplayInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn make(&self) -> &str {
+        &self.make
+    }
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+    pub fn scale(&self) -> i32 {
+        self.scale
+    }
+    pub fn refresh_rate(&self) -> u16 {
+        self.refresh_rate
+    }
+    pub fn rotation(&self) -> u16 {
+        self.rotation
+    }
+
     fn calc_rotation(&mut self) {
         if self.rotation % 180 != 0 {
             // Swap width/height
@@ -56,63 +82,63 @@ impl Module for DisplayInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.displays.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.displays.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.displays.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.displays.separator.as_ref().unwrap_or(&config.separator);
+        let section: &DisplayConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.displays.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.displays.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.displays.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.displays.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.displays.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.displays.separator.as_ref().unwrap_or(&config.separator);
-
-        let title: String = config.displays.title
-            .replace("{name}", "Unknown")
-            .replace("{make}", "Unknown")
-            .replace("{model}", "Unknown")
-            .replace("{width}", "Unknown")
-            .replace("{height}", "Unknown")
-            .replace("{refresh_rate}", "Unknown");
+        let section: &DisplayConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
+
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{name}", &self.name)
-            .replace("{make}", &self.make)
-            .replace("{model}", &self.model)
-            .replace("{width}", &self.width.to_string())
-            .replace("{height}", &self.height.to_string())
-            .replace("{refresh_rate}", &self.refresh_rate.to_string())
+        template::render(text, &|name| match name {
+            "name" => Some(self.name.clone()),
+            "make" => Some(self.make.clone()),
+            "model" => Some(self.model.clone()),
+            "width" => Some(self.width.to_string()),
+            "height" => Some(self.height.to_string()),
+            "refresh_rate" => Some(self.refresh_rate.to_string()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
-        if format.contains("{name}") {
+        if template.uses("name") {
             info_flags |= DISPLAYS_INFOFLAG_DRM_NAME;
         }
-        if format.contains("{make}") {
+        if template.uses("make") {
             info_flags |= DISPLAYS_INFOFLAG_MAKE;
             info_flags |= DISPLAYS_INFOFLAG_DRM_NAME; // DRM name is required for EDID
         }
-        if format.contains("{model}") {
+        if template.uses("model") {
             info_flags |= DISPLAYS_INFOFLAG_MODEL;
             info_flags |= DISPLAYS_INFOFLAG_DRM_NAME; // DRM name is required for EDID
         }
-        if format.contains("{width}") {
+        if template.uses("width") {
             info_flags |= DISPLAYS_INFOFLAG_WIDTH;
         }
-        if format.contains("{height}") {
+        if template.uses("height") {
             info_flags |= DISPLAYS_INFOFLAG_HEIGHT;
         }
-        if format.contains("{refresh_rate}") {
+        if template.uses("refresh_rate") {
             info_flags |= DISPLAYS_INFOFLAG_REFRESH_RATE;
         }
 
@@ -127,9 +153,28 @@ const DISPLAYS_INFOFLAG_WIDTH: u32 = 8;
 const DISPLAYS_INFOFLAG_HEIGHT: u32 = 16;
 const DISPLAYS_INFOFLAG_REFRESH_RATE: u32 = 32;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "displays",
+    config: ConfigSection::of::<DisplayConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|config| module::boxed(get_displays(config))),
+    unknown_output: DisplayInfo::unknown_output,
+    placeholders: &["name", "make", "model", "width", "height", "refresh_rate"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("displays.title", "Display ({make} {model})").unwrap();
+    builder = builder.set_default("displays.format", "{width}x{height} @ {refresh_rate}Hz ({name})").unwrap();
+    builder = builder.set_default("displays.scale_size", false).unwrap();
+
+    builder
+}
+
 pub fn get_displays(config: &Configuration) -> Result<Vec<DisplayInfo>, ModuleError> {
+    let section: &DisplayConfiguration = config.section();
     // title is tagged onto the end here to account for the title placeholders
-    let info_flags: u32 = DisplayInfo::gen_info_flags(&format!("{}{}", config.displays.format, config.displays.title));
+    let info_flags: u32 = DisplayInfo::gen_info_flags(&format!("{}{}", section.format, section.title));
 
     // Good news, during my college final deadline hell over the past 2 months, I learned how to
     // use a display server connection!
@@ -139,14 +184,14 @@ pub fn get_displays(config: &Configuration) -> Result<Vec<DisplayInfo>, ModuleEr
     if env::var("WAYLAND_DISPLAY").is_ok() {
         fetch_wayland(config, info_flags)
     } else if env::var("DISPLAY").is_ok() {
-        fetch_xorg(info_flags)
+        fetch_xorg(config, info_flags)
     } else {
         Err(ModuleError::new("Display", "Could not identify desktop session type.".to_string()))
     }
 }
 
 
-fn fetch_xorg(info_flags: u32) -> Result<Vec<DisplayInfo>, ModuleError> {
+fn fetch_xorg(config: &Configuration, info_flags: u32) -> Result<Vec<DisplayInfo>, ModuleError> {
     // This has really opened my eyes as to why more pieces of software haven't swapped over to
     // Wayland yet, it's so much more convoluted at times compared to X11
     let (conn, screen_num) = match x11rb::connect(None) {
@@ -183,7 +228,7 @@ fn fetch_xorg(info_flags: u32) -> Result<Vec<DisplayInfo>, ModuleError> {
         // Find the make/model from the EDID
         let (mut make, mut model): (String, String) = ("Unknown".to_string(), "Unknown".to_string());
         if is_flag_set_u32(info_flags, DISPLAYS_INFOFLAG_MAKE) || is_flag_set_u32(info_flags, DISPLAYS_INFOFLAG_MODEL) {
-            (make, model) = match get_edid_makemodel(&drm_name) {
+            (make, model) = match get_edid_makemodel(&config.root, &drm_name) {
                 Ok(r) => r,
                 Err(e) => return Err(ModuleError::new("Display", format!("Failed to get make/model for monitor {}: {e}", monitor.name))),
             };
@@ -248,12 +293,12 @@ fn fetch_xorg(info_flags: u32) -> Result<Vec<DisplayInfo>, ModuleError> {
     Ok(displays)
 }
 
-fn get_edid_makemodel(drm_name: &str) -> Result<(String, String), String> {
+fn get_edid_makemodel(root: &str, drm_name: &str) -> Result<(String, String), String> {
     // Relative to /sys/class/drm
     // Scans the dir until it finds the first directory ending in that drm name
     // This is because we don't know the GPU device index, and from my (limited) knowledge, no 2
     // DRM names should be repeated. If they can, I'll need to revisit this func.
-    let dir: ReadDir = match read_dir("/sys/class/drm") {
+    let dir: ReadDir = match read_dir(util::root_path(root, "/sys/class/drm")) {
         Ok(r) => r,
         Err(e) => return Err(format!("Unable to open /sys/class/drm: {e}")),
     };
@@ -267,16 +312,18 @@ fn get_edid_makemodel(drm_name: &str) -> Result<(String, String), String> {
         let x = x.unwrap();
         let dir_name = x.file_name();
         let dir_name = dir_name.to_str().unwrap();
-        if !dir_name.ends_with(drm_name) {
+        // Matched along with the dash before it, so DP-1 doesn't also match eDP-1
+        let drm_suffix: String = format!("-{drm_name}");
+        if !dir_name.ends_with(&drm_suffix) {
             // Strip the ending -{id} - Can happen with X11 seemingly
-            if !dir_name[..dir_name.len() - 2].ends_with(drm_name) {
+            if dir_name.len() < 2 || !dir_name[..dir_name.len() - 2].ends_with(&drm_suffix) {
                 continue;
             }
         }
 
         // Found it
         // Get the EDID now
-        let edid_bytes: Vec<u8> = match fs::read(format!("/sys/class/drm/{dir_name}/edid")) {
+        let edid_bytes: Vec<u8> = match fs::read(x.path().join("edid")) {
             Ok(r) => r,
             Err(e) => return Err(format!("Unable to open /sys/class/drm/{dir_name}/edid: {e}")),
         };
@@ -294,7 +341,7 @@ fn get_edid_makemodel(drm_name: &str) -> Result<(String, String), String> {
 
         let manuid: u16 = (u16::from(edid_bytes[8]) << 8) | u16::from(edid_bytes[9]);
         // + 64 to convert em to uppercase ascii
-        let char1: char = (((manuid & 0b011111_00000000) >> 10) as u8 + 64) as char;
+        let char1: char = (((manuid & 0b01111100_00000000) >> 10) as u8 + 64) as char;
         let char2: char = (((manuid & 0b00000011_11100000) >> 5) as u8 + 64) as char;
         let char3: char = ((manuid & 0b00000000_00011111) as u8 + 64) as char;
         make = format!("{char1}{char2}{char3}");
@@ -317,8 +364,8 @@ fn get_edid_makemodel(drm_name: &str) -> Result<(String, String), String> {
             }
 
             model = String::new();
-            // Read from byte 5+13 to find the full name
-            for byte in edid_bytes.iter().take(starting_byte + 13).skip(starting_byte + 5) {
+            // Read the 13 bytes from byte 5 onwards to find the full name
+            for byte in edid_bytes.iter().take(starting_byte + 18).skip(starting_byte + 5) {
                 model.push(*byte as char);
             }
             model = model.trim().to_string();
@@ -446,23 +493,23 @@ fn fetch_wayland(config: &Configuration, info_flags: u32) -> Result<Vec<DisplayI
 
     for x in &mut displays {
         x.calc_rotation();
-        if config.displays.scale_size {
+        if config.section::<DisplayConfiguration>().scale_size {
             x.scale_resolution();
         }
 
         if is_flag_set_u32(info_flags, DISPLAYS_INFOFLAG_MAKE) || is_flag_set_u32(info_flags, DISPLAYS_INFOFLAG_MODEL) {
-            if util::in_wsl() {
+            if util::in_wsl(&config.root) {
                 // WSL has no EDID and the compositor would just return weston's weird virtual display thing
                 x.make = "N/A".to_string();
                 x.model = "N/A".to_string();
             } else {
-                (x.make, x.model) = match get_edid_makemodel(&x.name) {
+                (x.make, x.model) = match get_edid_makemodel(&config.root, &x.name) {
                     Ok(r) => r,
                     Err(e) => return Err(ModuleError::new("Display", format!("Cannot parse EDID: {e}")))
                 };
             }
         }
-        if is_flag_set_u32(info_flags, DISPLAYS_INFOFLAG_DRM_NAME) && util::in_wsl() {
+        if is_flag_set_u32(info_flags, DISPLAYS_INFOFLAG_DRM_NAME) && util::in_wsl(&config.root) {
             // WSL also doesn't have any DRM name
             x.name = "N/A".to_string();
         }
@@ -471,3 +518,35 @@ fn fetch_wayland(config: &Configuration, info_flags: u32) -> Result<Vec<DisplayI
     displays.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
     Ok(displays)
 }
+
+mod tests {
+    // EDID parsing, against the blobs captured in tests/fixtures
+    #[test]
+    fn test_edid_makemodel() {
+        use crate::modules::displays::get_edid_makemodel;
+
+        let root: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/arch_laptop");
+        // Monitor with a name descriptor
+        assert_eq!(get_edid_makemodel(root, "DP-1"), Ok(("DEL".to_string(), "DELL U2720Q".to_string())));
+        // Laptop panel with no name, falling back to the product code
+        assert_eq!(get_edid_makemodel(root, "eDP-1"), Ok(("SHP".to_string(), "SHP14D0".to_string())));
+    }
+
+    #[test]
+    fn test_edid_style() {
+        use crate::{config_manager, module::Module, modules::displays::{get_edid_makemodel, DisplayInfo}};
+
+        let mut config = config_manager::parse(&Some("none".to_string()), &None).unwrap();
+        config.root = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/arch_laptop").to_string();
+
+        let mut display: DisplayInfo = DisplayInfo::new();
+        display.name = "DP-1".to_string();
+        display.width = 3840;
+        display.height = 2160;
+        display.refresh_rate = 60;
+        (display.make, display.model) = get_edid_makemodel(&config.root, &display.name).unwrap();
+
+        let (title, value) = display.style(&config);
+        assert_eq!(strip_ansi_escapes::strip_str(format!("{title}{value}")), "Display (DEL DELL U2720Q) > 3840x2160 @ 60Hz (DP-1)");
+    }
+}
@@ -1,14 +1,27 @@
 use std::env;
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, common_sources::package_managers::ManagerInfo, util::is_flag_set_u32, versions, ModuleError};
+use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, common_sources::package_managers::ManagerInfo, util::is_flag_set_u32, versions, ModuleError};
 
+#[derive(Serialize)]
 pub struct EditorInfo {
     name: String,
     path: String,
     version: String
 }
+impl EditorInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+}
 #[derive(Deserialize)]
 pub struct EditorConfiguration {
     pub title: String,
@@ -29,47 +42,50 @@ impl Module for EditorInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.editor.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.editor.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.editor.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.editor.separator.as_ref().unwrap_or(&config.separator);
+        let section: &EditorConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.editor.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.editor.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.editor.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.editor.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.editor.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.editor.separator.as_ref().unwrap_or(&config.separator);
+        let section: &EditorConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.editor.title
-            .replace("{name}", "Unknown")
-            .replace("{path}", "Unknown")
-            .replace("{version}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{name}", &self.name)
-            .replace("{path}", &self.path)
-            .replace("{version}", &self.version)
+        template::render(text, &|name| match name {
+            "name" => Some(self.name.clone()),
+            "path" => Some(self.path.clone()),
+            "version" => Some(self.version.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
-        if format.contains("{name}") {
+        if template.uses("name") {
             info_flags |= EDITOR_INFOFLAG_NAME;
             info_flags |= EDITOR_INFOFLAG_PATH; // deps on path
         }
-        if format.contains("{path}") {
+        if template.uses("path") {
             info_flags |= EDITOR_INFOFLAG_PATH;
         }
-        if format.contains("{version}") {
+        if template.uses("version") {
             // deps on all 3
             info_flags |= EDITOR_INFOFLAG_NAME;
             info_flags |= EDITOR_INFOFLAG_PATH;
@@ -84,9 +100,28 @@ const EDITOR_INFOFLAG_NAME: u32 = 1;
 const EDITOR_INFOFLAG_PATH: u32 = 2;
 const EDITOR_INFOFLAG_VERSION: u32 = 4;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "editor",
+    config: ConfigSection::of::<EditorConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::PackageManagers(|config, package_managers| module::boxed(get_editor(config, package_managers))),
+    unknown_output: EditorInfo::unknown_output,
+    placeholders: &["name", "path", "version"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("editor.title", "Editor").unwrap();
+    builder = builder.set_default("editor.format", "{name} {version}").unwrap();
+    builder = builder.set_default("editor.fancy", true).unwrap();
+
+    builder
+}
+
 pub fn get_editor(config: &Configuration, package_managers: &ManagerInfo) -> Result<EditorInfo, ModuleError> {
+    let section: &EditorConfiguration = config.section();
     let mut editor: EditorInfo = EditorInfo::new();
-    let info_flags: u32 = EditorInfo::gen_info_flags(&config.editor.format);
+    let info_flags: u32 = EditorInfo::gen_info_flags(&section.format);
 
     let env_value: String = match env::var("EDITOR") {
         Ok(r) => r,
@@ -113,7 +148,7 @@ pub fn get_editor(config: &Configuration, package_managers: &ManagerInfo) -> Res
 
     // Convert the name to a fancy variant
     // I don't like hardcoding like this, but otherwise the result looks dumb
-    if config.editor.fancy {
+    if section.fancy {
         editor.name = match editor.name.as_str() {
             "vi" => "VI".to_string(),
             "vim" => "Vim".to_string(),
@@ -1,12 +1,14 @@
 use core::str;
-use std::{fs::{self, DirEntry, File, ReadDir}, io::{BufRead, BufReader}, path::Path};
+use std::{fs::{self, DirEntry, File, ReadDir}, io::{BufRead, BufReader}, path::PathBuf};
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::{self, CrabFetchColor}, module::Module, util::{self, is_flag_set_u32}, ModuleError};
+use crate::{cache::{CachePolicy, CacheSignal}, config_manager::Configuration, formatter::{self, CrabFetchColor}, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, util::{self, is_flag_set_u32}, ModuleError};
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GPUInfo {
+    #[serde(skip)]
     index: Option<u8>,
     vendor: String,
     model: String,
@@ -38,61 +40,91 @@ impl Module for GPUInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.gpu.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.gpu.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.gpu.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.gpu.separator.as_ref().unwrap_or(&config.separator);
+        let section: &GPUConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.gpu.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.gpu.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
 
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.gpu.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.gpu.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.gpu.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.gpu.separator.as_ref().unwrap_or(&config.separator);
+        let section: &GPUConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.gpu.title
-            .replace("{vendor}", "Unknown")
-            .replace("{model}", "Unknown")
-            .replace("{vram}", "Unknown")
-            .replace("{index}", "0").to_string();
+        let title: String = template::render(&section.title, &|name| match name {
+            "index" => Some("0".to_string()),
+            _ => MODULE.placeholders.contains(&name).then(|| template::UNKNOWN.to_string())
+        });
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, config: &Configuration) -> String {
-        let use_ibis: bool = config.gpu.use_ibis.unwrap_or(config.use_ibis);
-
-        text.replace("{vendor}", &self.vendor)
-            .replace("{model}", &self.model)
-            .replace("{vram}", &formatter::auto_format_bytes(u64::from(self.vram_mb * 1000), use_ibis, 0))
-            .replace("{index}", &self.index.unwrap_or(0).to_string())
+        let use_ibis: bool = config.section::<GPUConfiguration>().use_ibis.unwrap_or(config.use_ibis);
+
+        template::render(text, &|name| match name {
+            "vendor" => Some(self.vendor.clone()),
+            "model" => Some(self.model.clone()),
+            "vram" => Some(formatter::auto_format_bytes(u64::from(self.vram_mb * 1000), use_ibis, 0)),
+            "index" => Some(self.index.unwrap_or(0).to_string()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
         // model and vendor are co-dependent
-        if format.contains("{vendor}") {
+        if template.uses("vendor") {
             info_flags |= GPU_INFOFLAG_VENDOR;
             info_flags |= GPU_INFOFLAG_MODEL;
         }
-        if format.contains("{model}") {
+        if template.uses("model") {
             info_flags |= GPU_INFOFLAG_MODEL;
             info_flags |= GPU_INFOFLAG_VENDOR;
         }
-        if format.contains("{vram}") {
+        if template.uses("vram") {
             info_flags |= GPU_INFOFLAG_VRAM;
         }
 
         info_flags
     }
+
+    // GPUs are numbered in the order they're displayed
+    fn style_list(list: &[Self], config: &Configuration) -> Vec<(String, String)> {
+        let mut lines: Vec<(String, String)> = Vec::new();
+        for (index, gpu) in (1..).zip(list) {
+            let mut gpu: GPUInfo = gpu.clone();
+            gpu.set_index(index);
+            lines.push(gpu.style(config));
+        }
+
+        lines
+    }
 }
 impl GPUInfo {
+    pub fn index(&self) -> Option<u8> {
+        self.index
+    }
+    pub fn vendor(&self) -> &str {
+        &self.vendor
+    }
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+    pub fn vram_mb(&self) -> u32 {
+        self.vram_mb
+    }
+
     pub fn set_index(&mut self, index: u8) {
         self.index = Some(index);
     }
@@ -102,17 +134,45 @@ const GPU_INFOFLAG_VENDOR: u32 = 1;
 const GPU_INFOFLAG_MODEL: u32 = 2;
 const GPU_INFOFLAG_VRAM: u32 = 4;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "gpu",
+    config: ConfigSection::of::<GPUConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|config| module::boxed(get_gpus(config))),
+    unknown_output: GPUInfo::unknown_output,
+    placeholders: &["vendor", "model", "vram", "index"],
+    volatile: false,
+    cache: Some(CachePolicy {
+        invalidated_by: CacheSignal::KernelRelease,
+        config_key: |config| {
+            let section: &GPUConfiguration = config.section();
+            Some(format!("{}|{}|{}|{}", section.format, section.amd_accuracy, section.ignore_disabled_gpus, section.detect_through_driver))
+        },
+        restore: module::restore::<Vec<GPUInfo>>,
+    }),
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("gpu.amd_accuracy", true).unwrap();
+    builder = builder.set_default("gpu.ignore_disabled_gpus", true).unwrap();
+    builder = builder.set_default("gpu.detect_through_driver", false).unwrap();
+    builder = builder.set_default("gpu.title", "GPU").unwrap();
+    builder = builder.set_default("gpu.format", "{vendor} {model} ({vram})").unwrap();
+
+    builder
+}
+
 pub fn get_gpus(config: &Configuration) -> Result<Vec<GPUInfo>, ModuleError> {
+    let section: &GPUConfiguration = config.section();
     let mut gpus: Vec<GPUInfo> = Vec::new();
-    let info_flags: u32 = GPUInfo::gen_info_flags(&config.gpu.format);
+    let info_flags: u32 = GPUInfo::gen_info_flags(&section.format);
 
-    if config.gpu.detect_through_driver {
-        match fill_from_drivers(&mut gpus, config.gpu.amd_accuracy, config.gpu.ignore_disabled_gpus, info_flags) {
+    if section.detect_through_driver {
+        match fill_from_drivers(&mut gpus, section.amd_accuracy, section.ignore_disabled_gpus, info_flags, &config.root) {
             Ok(_) => {},
             Err(e) => return Err(e)
         }
     } else {
-        match fill_from_pcisysfile(&mut gpus, config.gpu.amd_accuracy, config.gpu.ignore_disabled_gpus, info_flags) {
+        match fill_from_pcisysfile(&mut gpus, section.amd_accuracy, section.ignore_disabled_gpus, info_flags, &config.root) {
             Ok(_) => {},
             Err(e) => return Err(e)
         }
@@ -133,8 +193,8 @@ const KNOWN_GPU_DRIVERS: &[&str] = &[
     "i915",
     "xe"
 ];
-fn fill_from_drivers(gpus: &mut Vec<GPUInfo>, amd_accuracy: bool, ignore_disabled: bool, info_flags: u32) -> Result<(), ModuleError> {
-    let dir: ReadDir = match fs::read_dir("/sys/bus/pci/drivers") {
+fn fill_from_drivers(gpus: &mut Vec<GPUInfo>, amd_accuracy: bool, ignore_disabled: bool, info_flags: u32, root: &str) -> Result<(), ModuleError> {
+    let dir: ReadDir = match fs::read_dir(util::root_path(root, "/sys/bus/pci/drivers")) {
         Ok(r) => r,
         Err(e) => return Err(ModuleError::new("GPU", format!("Can't read from /sys/bus/pci/drivers: {e}")))
     };
@@ -171,7 +231,7 @@ fn fill_from_drivers(gpus: &mut Vec<GPUInfo>, amd_accuracy: bool, ignore_disable
 
             // So, in *theory*, this is a GPU 
             // Let's scan it :)
-            let Ok(Some(mut gpu)) = scan_pci_dir(&dev, amd_accuracy, ignore_disabled, info_flags) else {
+            let Ok(Some(mut gpu)) = scan_pci_dir(&dev, amd_accuracy, ignore_disabled, info_flags, root) else {
                 continue;
             };
             if let Some(start) = gpu.model.find('[') {
@@ -187,8 +247,8 @@ fn fill_from_drivers(gpus: &mut Vec<GPUInfo>, amd_accuracy: bool, ignore_disable
     Ok(())
 }
 
-fn fill_from_pcisysfile(gpus: &mut Vec<GPUInfo>, amd_accuracy: bool, ignore_disabled: bool, info_flags: u32) -> Result<(), ModuleError> {
-    let dir: ReadDir = match fs::read_dir("/sys/bus/pci/devices") {
+fn fill_from_pcisysfile(gpus: &mut Vec<GPUInfo>, amd_accuracy: bool, ignore_disabled: bool, info_flags: u32, root: &str) -> Result<(), ModuleError> {
+    let dir: ReadDir = match fs::read_dir(util::root_path(root, "/sys/bus/pci/devices")) {
         Ok(r) => r,
         Err(e) => return Err(ModuleError::new("GPU", format!("Can't read from /sys/bus/pci/devices: {e}"))),
     };
@@ -201,7 +261,7 @@ fn fill_from_pcisysfile(gpus: &mut Vec<GPUInfo>, amd_accuracy: bool, ignore_disa
             continue;
         };
 
-        let Ok(Some(mut gpu)) = scan_pci_dir(&d, amd_accuracy, ignore_disabled, info_flags) else {
+        let Ok(Some(mut gpu)) = scan_pci_dir(&d, amd_accuracy, ignore_disabled, info_flags, root) else {
             continue;
         };
         if let Some(start) = gpu.model.find('[') {
@@ -217,7 +277,7 @@ fn fill_from_pcisysfile(gpus: &mut Vec<GPUInfo>, amd_accuracy: bool, ignore_disa
 }
 
 
-fn scan_pci_dir(d: &DirEntry, amd_accuracy: bool, ignore_disabled: bool, info_flags: u32) -> Result<Option<GPUInfo>, ModuleError> {
+fn scan_pci_dir(d: &DirEntry, amd_accuracy: bool, ignore_disabled: bool, info_flags: u32, root: &str) -> Result<Option<GPUInfo>, ModuleError> {
     // Credit: https://wiki.archlinux.org/title/Modalias
     // v - Vendor ID
     // d - Device ID
@@ -267,12 +327,12 @@ fn scan_pci_dir(d: &DirEntry, amd_accuracy: bool, ignore_disabled: bool, info_fl
                 Ok(r) => r[2..].trim().to_string(),
                 Err(e) => return Err(ModuleError::new("GPU", format!("Can't read from file: {e}"))),
             };
-            if let Some(r) = search_amd_model(&device_id, &revision_id)? {
+            if let Some(r) = search_amd_model(&device_id, &revision_id, root)? {
                 gpu.model = r;
             }
         }
         if gpu.model == "Unknown" {
-            (gpu.vendor, gpu.model) = search_pci_ids(&vendor_id, &device_id)?;
+            (gpu.vendor, gpu.model) = search_pci_ids(&vendor_id, &device_id, root)?;
         }
     }
 
@@ -298,17 +358,17 @@ fn scan_pci_dir(d: &DirEntry, amd_accuracy: bool, ignore_disabled: bool, info_fl
 // I'll try to find it in as many places as possible but ultimately can't cover every place. If
 // you know the places, make a PR/Issue and i'll add it in. Fucking hate licences that work
 // like this but oh well.
-fn search_pci_ids(vendor: &str, device: &str) -> Result<(String, String), ModuleError> {
+fn search_pci_ids(vendor: &str, device: &str, root: &str) -> Result<(String, String), ModuleError> {
     // Search all known locations
-    let ids_path: &Path = match util::find_first_path_exists(vec![
-        Path::new("/usr/share/hwdata/pci.ids"),
-        Path::new("/usr/share/misc/pci.ids")
+    let ids_path: PathBuf = match util::find_first_pathbuf_exists(vec![
+        util::root_path(root, "/usr/share/hwdata/pci.ids"),
+        util::root_path(root, "/usr/share/misc/pci.ids")
     ]) {
         Some(r) => r,
         None => return Err(ModuleError::new("GPU", "Could not find an appropriate path for getting PCI ID info.".to_string()))
     };
 
-    let file: File = match File::open(ids_path) {
+    let file: File = match File::open(&ids_path) {
         Ok(r) => r,
         Err(e) => return Err(ModuleError::new("GPU", format!("Can't read from {} - {e}", ids_path.display()))),
     };
@@ -356,15 +416,15 @@ fn search_pci_ids(vendor: &str, device: &str) -> Result<(String, String), Module
 
     Ok((vendor_result.to_string(), device_result.to_string()))
 }
-fn search_amd_model(device: &str, revision: &str) -> Result<Option<String>, ModuleError> {
-    let ids_path: &Path = match util::find_first_path_exists(vec![
-        Path::new("/usr/share/libdrm/amdgpu.ids")
+fn search_amd_model(device: &str, revision: &str, root: &str) -> Result<Option<String>, ModuleError> {
+    let ids_path: PathBuf = match util::find_first_pathbuf_exists(vec![
+        util::root_path(root, "/usr/share/libdrm/amdgpu.ids")
     ]) {
         Some(r) => r,
         None => return Err(ModuleError::new("GPU", "Could not find an appropriate path for getting AMD PCI ID info.".to_string()))
     };
 
-    let file: File = match File::open(ids_path) {
+    let file: File = match File::open(&ids_path) {
         Ok(r) => r,
         Err(e) => return Err(ModuleError::new("GPU", format!("Can't read from {} - {e}", ids_path.display()))),
     };
@@ -419,7 +479,7 @@ mod tests {
         ]);
 
         for test in tests {
-            let result: Result<(String, String), ModuleError> = search_pci_ids(test.0.0, test.0.1);
+            let result: Result<(String, String), ModuleError> = search_pci_ids(test.0.0, test.0.1, "/");
             assert!(result.is_ok());
             let result: (String, String) = result.unwrap();
             assert_eq!(result.0, test.1.0);
@@ -440,7 +500,7 @@ mod tests {
         ]);
 
         for test in tests {
-            let result: Result<Option<String>, ModuleError> = search_amd_model(test.0.0, test.0.1);
+            let result: Result<Option<String>, ModuleError> = search_amd_model(test.0.0, test.0.1, "/");
             assert!(result.is_ok());
             let result: Option<String> = result.unwrap();
             assert!(result.is_some());
@@ -1,12 +1,14 @@
 use core::str;
-use std::path::Path;
+use std::path::PathBuf;
 
 #[cfg(feature = "android")]
 use {android_system_properties::AndroidSystemProperties, std::env};
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, util::{self, is_flag_set_u32}, ModuleError};
+use crate::{cache::{CachePolicy, CacheSignal}, config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, util::{self, is_flag_set_u32}, ModuleError};
 
+#[derive(Serialize, Deserialize)]
 pub struct HostInfo {
     host: String,
     chassis: String
@@ -32,71 +34,118 @@ impl Module for HostInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.host.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.host.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.host.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.host.separator.as_ref().unwrap_or(&config.separator);
+        let section: &HostConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.host.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.host.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.host.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.host.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.host.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.host.separator.as_ref().unwrap_or(&config.separator);
+        let section: &HostConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.host.title
-            .replace("{host}", "Unknown")
-            .replace("{chassis}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{host}", &self.host)
-            .replace("{chassis}", &self.chassis)
+        template::render(text, &|name| match name {
+            "host" => Some(self.host.clone()),
+            "chassis" => Some(self.chassis.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
         // model and vendor are co-dependent
-        if format.contains("{host}") {
+        if template.uses("host") {
             info_flags |= HOST_INFOFLAG_HOST;
         }
-        if format.contains("{chassis}") {
+        if template.uses("chassis") {
             info_flags |= HOST_INFOFLAG_CHASSIS;
         }
 
         info_flags
     }
+
+    fn style_lines(&self, config: &Configuration) -> Vec<(String, String)> {
+        if config.section::<HostConfiguration>().newline_chassis {
+            return vec![self.style(config), self.style_chassis(config)];
+        }
+        vec![self.style(config)]
+    }
 }
 impl HostInfo {
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+    pub fn chassis(&self) -> &str {
+        &self.chassis
+    }
+
     // Identical to the regular style method, but placeholder's in the kernel instead
     pub fn style_chassis(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.host.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.host.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.host.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.host.separator.as_ref().unwrap_or(&config.separator);
+        let section: &HostConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let value: String = self.replace_color_placeholders(&config.host.chassis_format.replace("{chassis}", &self.chassis), config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.chassis_format, config), config);
 
-        Self::default_style(config, &config.host.chassis_title, title_color, title_bold, title_italic, separator, &value)
+        Self::default_style(config, &section.chassis_title, title_color, title_bold, title_italic, separator, &value)
     }
 }
 
 const HOST_INFOFLAG_HOST: u32 = 1;
 const HOST_INFOFLAG_CHASSIS: u32 = 2;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "host",
+    config: ConfigSection::of::<HostConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|config| module::boxed(get_host(config))),
+    unknown_output: HostInfo::unknown_output,
+    placeholders: &["host", "chassis"],
+    volatile: false,
+    cache: Some(CachePolicy {
+        invalidated_by: CacheSignal::KernelRelease,
+        config_key: |config| {
+            let section: &HostConfiguration = config.section();
+            Some(format!("{}|{}|{}", section.format, section.newline_chassis, section.chassis_format))
+        },
+        restore: module::restore::<HostInfo>,
+    }),
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("host.title", "Host").unwrap();
+    builder = builder.set_default("host.format", "{host} ({chassis})").unwrap();
+    builder = builder.set_default("host.newline_chassis", false).unwrap();
+    builder = builder.set_default("host.chassis_title", "Chassis").unwrap();
+    builder = builder.set_default("host.chassis_format", "{chassis}").unwrap();
+
+    builder
+}
+
 pub fn get_host(config: &Configuration) -> Result<HostInfo, ModuleError> {
+    let section: &HostConfiguration = config.section();
     let mut host: HostInfo = HostInfo::new();
 
-    let mut format: String = config.host.format.to_string();
-    if config.host.newline_chassis {
-        format.push_str(&config.host.chassis_format);
+    let mut format: String = section.format.to_string();
+    if section.newline_chassis {
+        format.push_str(&section.chassis_format);
     }
     let info_flags: u32 = HostInfo::gen_info_flags(&format);
 
@@ -119,7 +168,7 @@ pub fn get_host(config: &Configuration) -> Result<HostInfo, ModuleError> {
     }
 
     // WSL
-    if util::in_wsl() {
+    if util::in_wsl(&config.root) {
         host.host = "Windows Subsystem for Linux".to_string();
         host.chassis = "N/A".to_string();
         return Ok(host);
@@ -127,27 +176,28 @@ pub fn get_host(config: &Configuration) -> Result<HostInfo, ModuleError> {
 
     // Prioritises product_name for laptops, then goes to board_name
     if is_flag_set_u32(info_flags, HOST_INFOFLAG_HOST) {
-        let chosen_path: &Path = match util::find_first_path_exists(vec![
-            Path::new("/sys/devices/virtual/dmi/id/product_name"),
-            Path::new("/sys/devices/virtual/dmi/id/board_name"),
-            Path::new("/sys/firmware/devicetree/base/model")
+        let chosen_path: PathBuf = match util::find_first_pathbuf_exists(vec![
+            util::root_path(&config.root, "/sys/devices/virtual/dmi/id/product_name"),
+            util::root_path(&config.root, "/sys/devices/virtual/dmi/id/board_name"),
+            util::root_path(&config.root, "/sys/firmware/devicetree/base/model")
         ]) {
             Some(r) => r,
             None => return Err(ModuleError::new("Host", "Can't find an appropriate path for host.".to_string()))
         };
 
-        host.host = match util::file_read(chosen_path) {
-            Ok(r) => r.trim().to_string(),
+        host.host = match util::file_read(&chosen_path) {
+            // Device tree strings are null terminated
+            Ok(r) => r.trim_end_matches('\0').trim().to_string(),
             Err(e) => return Err(ModuleError::new("Host", format!("Can't read from {} - {}", chosen_path.display(), e))),
         };
     }
 
     // Now the chassis type 
     if is_flag_set_u32(info_flags, HOST_INFOFLAG_CHASSIS) {
-        let p: &Path = Path::new("/sys/devices/virtual/dmi/id/chassis_type");
+        let p: PathBuf = util::root_path(&config.root, "/sys/devices/virtual/dmi/id/chassis_type");
         // The file may not exist on some stuff, e.g raspberry pi's don't have it
         if p.exists() {
-            host.chassis = match util::file_read(Path::new("/sys/devices/virtual/dmi/id/chassis_type")) {
+            host.chassis = match util::file_read(&p) {
                 // http://git.savannah.nongnu.org/cgit/dmidecode.git/tree/dmidecode.c?id=d5af407ae937b0ab26b72e8c250112d5a8543a63#n602
                 // I have no idea if this is meant to be hex or decimal, so I'm taking a gamble that it's
                 // decimal
@@ -1,14 +1,24 @@
 use core::str;
 use std::{env, process::Command};
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, common_sources::syscalls::SyscallCache, util::is_flag_set_u32, ModuleError};
+use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, common_sources::syscalls::SyscallCache, util::is_flag_set_u32, ModuleError};
 
+#[derive(Serialize)]
 pub struct HostnameInfo {
     username: String,
     hostname: String,
 }
+impl HostnameInfo {
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+}
 #[derive(Deserialize)]
 pub struct HostnameConfiguration {
     pub title: String,
@@ -26,43 +36,46 @@ impl Module for HostnameInfo {
         }
     }
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.hostname.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.hostname.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.hostname.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.hostname.separator.as_ref().unwrap_or(&config.separator);
+        let section: &HostnameConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.hostname.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.hostname.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.hostname.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.hostname.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.hostname.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.hostname.separator.as_ref().unwrap_or(&config.separator);
+        let section: &HostnameConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.hostname.title
-            .replace("{username}", "Unknown")
-            .replace("{hostname}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{username}", &self.username)
-            .replace("{hostname}", &self.hostname)
-            .to_string()
+        template::render(text, &|name| match name {
+            "username" => Some(self.username.clone()),
+            "hostname" => Some(self.hostname.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
         // model and vendor are co-dependent
-        if format.contains("{hostname}") {
+        if template.uses("hostname") {
             info_flags |= HOSTNAME_INFOFLAG_HOSTNAME;
         }
-        if format.contains("{username}") {
+        if template.uses("username") {
             info_flags |= HOSTNAME_INFOFLAG_USERNAME;
         }
 
@@ -73,10 +86,27 @@ impl Module for HostnameInfo {
 const HOSTNAME_INFOFLAG_HOSTNAME: u32 = 1;
 const HOSTNAME_INFOFLAG_USERNAME: u32 = 2;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "hostname",
+    config: ConfigSection::of::<HostnameConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Syscalls(|config, syscall_cache| module::boxed(get_hostname(config, syscall_cache))),
+    unknown_output: HostnameInfo::unknown_output,
+    placeholders: &["username", "hostname"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("hostname.title", "").unwrap();
+    builder = builder.set_default("hostname.format", "{color-title}{username}{color-white}@{color-title}{hostname}").unwrap();
+
+    builder
+}
+
 #[allow(clippy::unnecessary_wraps)]
 pub fn get_hostname(config: &Configuration, syscall_cache: &mut SyscallCache) -> Result<HostnameInfo, ModuleError> {
     let mut hostname: HostnameInfo = HostnameInfo::new();
-    let info_flags: u32 = HostnameInfo::gen_info_flags(&config.hostname.format);
+    let info_flags: u32 = HostnameInfo::gen_info_flags(&config.section::<HostnameConfiguration>().format);
 
     // We'll try the safe way first, then the backup way
     // This is purely cus reading that env variable is faster
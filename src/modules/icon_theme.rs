@@ -1,12 +1,25 @@
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{common_sources::gtk::GTKSettingsCache, config_manager::Configuration, formatter::CrabFetchColor, module::Module, ModuleError};
+use crate::{common_sources::gtk::GTKSettingsCache, config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template, ModuleError};
 
+#[derive(Serialize)]
 pub struct IconThemeInfo {
     gtk2: String,
     gtk3: String,
     gtk4: String
 }
+impl IconThemeInfo {
+    pub fn gtk2(&self) -> &str {
+        &self.gtk2
+    }
+    pub fn gtk3(&self) -> &str {
+        &self.gtk3
+    }
+    pub fn gtk4(&self) -> &str {
+        &self.gtk4
+    }
+}
 #[derive(Deserialize)]
 pub struct IconThemeConfiguration {
     pub title: String,
@@ -26,30 +39,35 @@ impl Module for IconThemeInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.icontheme.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.icontheme.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.icontheme.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.icontheme.separator.as_ref().unwrap_or(&config.separator);
+        let section: &IconThemeConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let format: String = config.icontheme.format.clone().unwrap_or("{time}".to_string());
-        let title: String = self.replace_placeholders(&config.icontheme.title, config);
+        let format: String = section.format.clone().unwrap_or("{time}".to_string());
+        let title: String = self.replace_placeholders(&section.title, config);
         let value: String = self.replace_color_placeholders(&self.replace_placeholders(&format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) { 
-        let title_color: &CrabFetchColor = config.icontheme.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.icontheme.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.icontheme.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.icontheme.separator.as_ref().unwrap_or(&config.separator);
+        let section: &IconThemeConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        Self::default_style(config, &config.icontheme.title, title_color, title_bold, title_italic, separator, "Unknown")
+        Self::default_style(config, &section.title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{gtk2}", &self.gtk2)
-            .replace("{gtk3}", &self.gtk3)
-            .replace("{gtk4}", &self.gtk4)
+        template::render(text, &|name| match name {
+            "gtk2" => Some(self.gtk2.clone()),
+            "gtk3" => Some(self.gtk3.clone()),
+            "gtk4" => Some(self.gtk4.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(_: &str) -> u32 {
@@ -57,6 +75,23 @@ impl Module for IconThemeInfo {
     }
 }
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "icontheme",
+    config: ConfigSection::of::<IconThemeConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::GTKSettings(|gtk_settings| module::boxed(get_icon_theme(gtk_settings))),
+    unknown_output: IconThemeInfo::unknown_output,
+    placeholders: &["gtk2", "gtk3", "gtk4"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("icontheme.title", "Icons").unwrap();
+    builder = builder.set_default("icontheme.format", "Gtk3: {gtk3}  Gtk4: {gtk4}").unwrap();
+
+    builder
+}
+
 pub fn get_icon_theme(gtk_settings: &mut GTKSettingsCache) -> Result<IconThemeInfo, ModuleError> {
     let mut icon_theme: IconThemeInfo = IconThemeInfo::new();
 
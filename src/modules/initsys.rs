@@ -1,15 +1,28 @@
 use core::str;
-use std::fs;
+use std::{fs, path::{Path, PathBuf}};
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, common_sources::package_managers::ManagerInfo, proccess_info::ProcessInfo, util::is_flag_set_u32, versions, ModuleError};
+use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, common_sources::package_managers::ManagerInfo, proccess_info::ProcessInfo, util::{self, is_flag_set_u32}, versions, ModuleError};
 
+#[derive(Serialize)]
 pub struct InitSystemInfo {
     name: String,
     path: String,
     version: String
 }
+impl InitSystemInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+}
 #[derive(Deserialize)]
 pub struct InitSystemConfiguration {
     pub title: String,
@@ -29,47 +42,50 @@ impl Module for InitSystemInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.initsys.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.initsys.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.initsys.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.initsys.separator.as_ref().unwrap_or(&config.separator);
+        let section: &InitSystemConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.initsys.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.initsys.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.initsys.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.initsys.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.initsys.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.initsys.separator.as_ref().unwrap_or(&config.separator);
+        let section: &InitSystemConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.initsys.title
-            .replace("{name}", "Unknown")
-            .replace("{path}", "Unknown")
-            .replace("{version}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{name}", &self.name)
-            .replace("{path}", &self.path)
-            .replace("{version}", &self.version)
+        template::render(text, &|name| match name {
+            "name" => Some(self.name.clone()),
+            "path" => Some(self.path.clone()),
+            "version" => Some(self.version.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
-        if format.contains("{name}") {
+        if template.uses("name") {
             info_flags |= INITSYS_INFOFLAG_NAME;
             info_flags |= INITSYS_INFOFLAG_PATH; // deps on path
         }
-        if format.contains("{path}") {
+        if template.uses("path") {
             info_flags |= INITSYS_INFOFLAG_PATH;
         }
-        if format.contains("{version}") {
+        if template.uses("version") {
             // deps on all 3
             info_flags |= INITSYS_INFOFLAG_NAME;
             info_flags |= INITSYS_INFOFLAG_PATH;
@@ -84,23 +100,43 @@ const INITSYS_INFOFLAG_NAME: u32 = 1;
 const INITSYS_INFOFLAG_PATH: u32 = 2;
 const INITSYS_INFOFLAG_VERSION: u32 = 4;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "initsys",
+    config: ConfigSection::of::<InitSystemConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::PackageManagers(|config, package_managers| module::boxed(get_init_system(config, package_managers))),
+    unknown_output: InitSystemInfo::unknown_output,
+    placeholders: &["name", "path", "version"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("initsys.title", "Init System").unwrap();
+    builder = builder.set_default("initsys.format", "{name} {version}").unwrap();
+
+    builder
+}
+
 pub fn get_init_system(config: &Configuration, package_managers: &ManagerInfo) -> Result<InitSystemInfo, ModuleError> {
     let mut initsys: InitSystemInfo = InitSystemInfo::new();
-    let info_flags: u32 = InitSystemInfo::gen_info_flags(&config.initsys.format);
+    let info_flags: u32 = InitSystemInfo::gen_info_flags(&config.section::<InitSystemConfiguration>().format);
 
     // Reads the /cmdline of process 1, either using that or redirecting to it's symlink 
     // Thanks to https://superuser.com/a/1183819
-    let mut process: ProcessInfo = ProcessInfo::new(1);
+    let mut process: ProcessInfo = ProcessInfo::new_in_root(1, &config.root);
 
     if is_flag_set_u32(info_flags, INITSYS_INFOFLAG_PATH) {
         let path: String = match process.get_cmdline() {
             Ok(r) => r[0].to_string(),
             Err(e) => return Err(ModuleError::new("InitSys", format!("Failed to read from root process cmdline: {e}"))),
         };
-        initsys.path = match fs::canonicalize(&path) {
-            Ok(r) => r.display().to_string(),
+        let canonical: PathBuf = match fs::canonicalize(util::root_path(&config.root, &path)) {
+            Ok(r) => r,
             Err(e) => return Err(ModuleError::new("InitSys", format!("Failed to canonicalize {path} symlink: {e}")))
         };
+        // Report the path as it's seen from inside the root, not from our side of it
+        let canonical: &Path = canonical.strip_prefix(&config.root).unwrap_or(&canonical);
+        initsys.path = Path::new("/").join(canonical).display().to_string();
     }
     if is_flag_set_u32(info_flags, INITSYS_INFOFLAG_NAME) {
         initsys.name = initsys.path.split('/')
@@ -113,6 +149,9 @@ pub fn get_init_system(config: &Configuration, package_managers: &ManagerInfo) -
         if initsys.name == "init" {
             // Likely sysvinit, which can't be version detected
             initsys.version = "Unknown".to_string();
+        } else if config.root != "/" {
+            // Finding the version means running it, which would be our own init system rather than the root's
+            initsys.version = "Unknown".to_string();
         } else {
             initsys.version = versions::find_version(&initsys.path, Some(&initsys.name), package_managers).unwrap_or("Unknown".to_string());
         }
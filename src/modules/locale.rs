@@ -1,13 +1,23 @@
 use std::env;
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{formatter::CrabFetchColor, config_manager::Configuration, module::Module, ModuleError};
+use crate::{formatter::CrabFetchColor, config_manager::Configuration, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template, ModuleError};
 
+#[derive(Serialize)]
 pub struct LocaleInfo {
     language: String,
     encoding: String,
 }
+impl LocaleInfo {
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+    pub fn encoding(&self) -> &str {
+        &self.encoding
+    }
+}
 #[derive(Deserialize)]
 pub struct LocaleConfiguration {
     pub title: String,
@@ -26,32 +36,35 @@ impl Module for LocaleInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.locale.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.locale.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.locale.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.locale.separator.as_ref().unwrap_or(&config.separator);
+        let section: &LocaleConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.locale.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.locale.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.locale.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.locale.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.locale.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.locale.separator.as_ref().unwrap_or(&config.separator);
+        let section: &LocaleConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.locale.title
-            .replace("{language}", "Unknown")
-            .replace("{encoding}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{language}", &self.language)
-            .replace("{encoding}", &self.encoding)
+        template::render(text, &|name| match name {
+            "language" => Some(self.language.clone()),
+            "encoding" => Some(self.encoding.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(_: &str) -> u32 {
@@ -59,6 +72,23 @@ impl Module for LocaleInfo {
     }
 }
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "locale",
+    config: ConfigSection::of::<LocaleConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|_| module::boxed(get_locale())),
+    unknown_output: LocaleInfo::unknown_output,
+    placeholders: &["language", "encoding"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("locale.title", "Locale").unwrap();
+    builder = builder.set_default("locale.format", "{language} ({encoding})").unwrap();
+
+    builder
+}
+
 pub fn get_locale() -> Result<LocaleInfo, ModuleError> {
     // no info flags here as it's all from the same source
     let mut locale: LocaleInfo = LocaleInfo::new();
@@ -1,13 +1,23 @@
-use std::{fs::{self, ReadDir}, mem, net::{IpAddr, Ipv4Addr, Ipv6Addr}};
+use std::{fs::{self, ReadDir}, mem, path::PathBuf, net::{IpAddr, Ipv4Addr, Ipv6Addr}};
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, util, ModuleError};
+use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template, util, ModuleError};
 
+#[derive(Serialize)]
 pub struct LocalIPInfo {
     interface: String,
     ip_addr: String,
 }
+impl LocalIPInfo {
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+    pub fn ip_addr(&self) -> &str {
+        &self.ip_addr
+    }
+}
 #[derive(Deserialize)]
 pub struct LocalIPConfiguration {
     pub title: String,
@@ -26,32 +36,35 @@ impl Module for LocalIPInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.localip.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.localip.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.localip.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.localip.separator.as_ref().unwrap_or(&config.separator);
+        let section: &LocalIPConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.localip.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.localip.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.localip.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.localip.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.localip.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.localip.separator.as_ref().unwrap_or(&config.separator);
+        let section: &LocalIPConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.locale.title
-            .replace("{interface}", "Unknown")
-            .replace("{addr}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{interface}", &self.interface)
-            .replace("{addr}", &self.ip_addr)
+        template::render(text, &|name| match name {
+            "interface" => Some(self.interface.clone()),
+            "addr" => Some(self.ip_addr.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(_: &str) -> u32 {
@@ -59,8 +72,25 @@ impl Module for LocalIPInfo {
     }
 }
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "localip",
+    config: ConfigSection::of::<LocalIPConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|config| module::boxed(get_local_ips(config))),
+    unknown_output: LocalIPInfo::unknown_output,
+    placeholders: &["interface", "addr"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("localip.title", "Local IP ({interface})").unwrap();
+    builder = builder.set_default("localip.format", "{addr}").unwrap();
+
+    builder
+}
+
 #[allow(clippy::cast_ptr_alignment)] // i'm really shitty with dealing with pointers, but afaik its fine 
-pub fn get_local_ips() -> Result<Vec<LocalIPInfo>, ModuleError> {
+pub fn get_local_ips(config: &Configuration) -> Result<Vec<LocalIPInfo>, ModuleError> {
     // no info flags here as it's all from the same source
     let mut addrs: Vec<LocalIPInfo> = Vec::new();
 
@@ -68,9 +98,10 @@ pub fn get_local_ips() -> Result<Vec<LocalIPInfo>, ModuleError> {
     // Credit to https://stackoverflow.com/a/52561720 for the initial explanation on how the kernel
     // handles this stuff
     let mut virt_interfaces: Vec<String> = Vec::new();
-    let dir: ReadDir = match fs::read_dir("/sys/devices/virtual/net") {
+    let virt_path: PathBuf = util::root_path(&config.root, "/sys/devices/virtual/net");
+    let dir: ReadDir = match fs::read_dir(&virt_path) {
         Ok(r) => r,
-        Err(e) => return Err(ModuleError::new("LocalIP", format!("Can't read from /sys/devices/virtual/net: {e}"))),
+        Err(e) => return Err(ModuleError::new("LocalIP", format!("Can't read from {}: {e}", virt_path.display()))),
     };
     for dev in dir {
         let d = match dev {
@@ -1,15 +1,28 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{formatter::{self, CrabFetchColor}, config_manager::Configuration, module::Module, ModuleError};
+use crate::{formatter::{self, CrabFetchColor}, config_manager::Configuration, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template, util, ModuleError};
 
+#[derive(Serialize)]
 pub struct MemoryInfo {
     used_kb: u64,
     max_kb: u64,
     percentage: f32
 }
+impl MemoryInfo {
+    pub fn used_kb(&self) -> u64 {
+        self.used_kb
+    }
+    pub fn max_kb(&self) -> u64 {
+        self.max_kb
+    }
+    pub fn percentage(&self) -> f32 {
+        self.percentage
+    }
+}
 #[derive(Deserialize)]
 pub struct MemoryConfiguration {
     pub title: String,
@@ -36,50 +49,51 @@ impl Module for MemoryInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.memory.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.memory.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.memory.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.memory.separator.as_ref().unwrap_or(&config.separator);
+        let section: &MemoryConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.memory.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.memory.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.memory.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.memory.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.memory.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.memory.separator.as_ref().unwrap_or(&config.separator);
+        let section: &MemoryConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.memory.title
-            .replace("{used}", "Unknown")
-            .replace("{max}", "Unknown")
-            .replace("{bar}", "")
-            .replace("{percentage}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, config: &Configuration) -> String {
-        let dec_places: u32 = config.memory.decimal_places.unwrap_or(config.decimal_places);
-        let use_ibis: bool = config.memory.use_ibis.unwrap_or(config.use_ibis);
-
-        let mut bar: String = String::new();
-        if text.contains("{bar}") {
-            let left_border: &str = config.memory.progress_left_border.as_ref().unwrap_or(&config.progress_left_border);
-            let right_border: &str = config.memory.progress_right_border.as_ref().unwrap_or(&config.progress_right_border);
-            let progress: &str = config.memory.progress_progress.as_ref().unwrap_or(&config.progress_progress);
-            let empty: &str = config.memory.progress_empty.as_ref().unwrap_or(&config.progress_empty);
-            let length: u8 = config.memory.progress_target_length.unwrap_or(config.progress_target_length);
-            formatter::make_bar(&mut bar, left_border, right_border, progress, empty, self.percentage, length);
-        }
-
-        #[allow(clippy::cast_possible_truncation)]
-        formatter::process_percentage_placeholder(text, formatter::round(f64::from(self.percentage), dec_places) as f32, config)
-            .replace("{used}", &formatter::auto_format_bytes(self.used_kb, use_ibis, dec_places))
-            .replace("{max}", &formatter::auto_format_bytes(self.max_kb, use_ibis, dec_places))
-            .replace("{bar}", &bar.to_string())
+        let section: &MemoryConfiguration = config.section();
+        let dec_places: u32 = section.decimal_places.unwrap_or(config.decimal_places);
+        let use_ibis: bool = section.use_ibis.unwrap_or(config.use_ibis);
+
+        template::render(text, &|name| match name {
+            #[allow(clippy::cast_possible_truncation)]
+            "percent" => Some(formatter::percentage_string(formatter::round(f64::from(self.percentage), dec_places) as f32, config)),
+            "used" => Some(formatter::auto_format_bytes(self.used_kb, use_ibis, dec_places)),
+            "max" => Some(formatter::auto_format_bytes(self.max_kb, use_ibis, dec_places)),
+            "bar" => {
+                let left_border: &str = section.progress_left_border.as_ref().unwrap_or(&config.progress_left_border);
+                let right_border: &str = section.progress_right_border.as_ref().unwrap_or(&config.progress_right_border);
+                let progress: &str = section.progress_progress.as_ref().unwrap_or(&config.progress_progress);
+                let empty: &str = section.progress_empty.as_ref().unwrap_or(&config.progress_empty);
+                let length: u8 = section.progress_target_length.unwrap_or(config.progress_target_length);
+                let mut bar: String = String::new();
+                formatter::make_bar(&mut bar, left_border, right_border, progress, empty, self.percentage, length);
+                Some(bar)
+            },
+            _ => None
+        })
     }
 
     fn gen_info_flags(_: &str) -> u32 {
@@ -89,14 +103,31 @@ impl Module for MemoryInfo {
 
 // Clippy had a lot of issues with this function, while it's kinda not possible to improve it
 // without just being a pain in the ass to work with
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "memory",
+    config: ConfigSection::of::<MemoryConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|config| module::boxed(get_memory(config))),
+    unknown_output: MemoryInfo::unknown_output,
+    placeholders: &["used", "max", "percent", "bar"],
+    volatile: true,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("memory.title", "Memory").unwrap();
+    builder = builder.set_default("memory.format", "{used} / {max} ({percent})").unwrap();
+
+    builder
+}
+
 #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation, clippy::cast_precision_loss)]
-pub fn get_memory() -> Result<MemoryInfo, ModuleError> {
+pub fn get_memory(config: &Configuration) -> Result<MemoryInfo, ModuleError> {
     // no info flags here as while it would've had a slight benefit, all the info requires eachother anyway so
     // it's hardly worth it
     let mut memory: MemoryInfo = MemoryInfo::new();
 
     // Fetches from /proc/meminfo
-    let file: File = match File::open("/proc/meminfo") {
+    let file: File = match File::open(util::root_path(&config.root, "/proc/meminfo")) {
         Ok(r) => r,
         Err(e) => return Err(ModuleError::new("Memory", format!("Can't read from /proc/meminfo - {e}"))),
     };
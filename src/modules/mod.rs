@@ -1,3 +1,5 @@
+use crate::module::RegisteredModule;
+
 pub mod cpu;
 pub mod gpu;
 pub mod os;
@@ -23,3 +25,39 @@ pub mod datetime;
 pub mod localip;
 pub mod theme;
 pub mod icon_theme;
+
+// Every module CrabFetch knows about, in the form of each module's registry entry
+// Anything in here can be detected and displayed from the config's module list
+pub static MODULES: &[&RegisteredModule] = &[
+    &hostname::MODULE,
+    &cpu::MODULE,
+    &gpu::MODULE,
+    &memory::MODULE,
+    &swap::MODULE,
+    &mounts::MODULE,
+    &host::MODULE,
+    &displays::MODULE,
+    &os::MODULE,
+    &packages::MODULE,
+    &desktop::MODULE,
+    &terminal::MODULE,
+    &shell::MODULE,
+    &battery::MODULE,
+    &uptime::MODULE,
+    &locale::MODULE,
+    #[cfg(feature = "player")]
+    &player::MODULE,
+    &editor::MODULE,
+    &initsys::MODULE,
+    &processes::MODULE,
+    &datetime::MODULE,
+    &localip::MODULE,
+    &theme::MODULE,
+    &icon_theme::MODULE,
+];
+
+pub fn find_module(name: &str) -> Option<&'static RegisteredModule> {
+    MODULES.iter()
+        .find(|x| x.name == name)
+        .copied()
+}
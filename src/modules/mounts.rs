@@ -1,14 +1,16 @@
-use std::{fs::{self, File}, io::{BufRead, BufReader, Error}, path::{Path, PathBuf}};
+use std::{fs::{self, File}, io::{BufRead, BufReader, Error}, os::unix::ffi::OsStrExt, path::{Path, PathBuf}};
 use std::mem;
 
 #[cfg(feature = "android")]
 use std::env;
 
 use libc::statfs;
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::{self, CrabFetchColor}, module::Module, util::{self, is_flag_set_u32}, ModuleError};
+use crate::{config_manager::Configuration, formatter::{self, CrabFetchColor}, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, util::{self, is_flag_set_u32}, ModuleError};
 
+#[derive(Serialize)]
 pub struct MountInfo {
     device: String,     // /dev/sda
     mount: String,      // /hdd
@@ -47,81 +49,106 @@ impl Module for MountInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.mounts.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.mounts.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.mounts.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.mounts.separator.as_ref().unwrap_or(&config.separator);
+        let section: &MountConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.mounts.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.mounts.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.mounts.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.mounts.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.mounts.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.mounts.separator.as_ref().unwrap_or(&config.separator);
-
-        let title: String = config.mounts.title
-            .replace("{device}", "Unknown")
-            .replace("{mount}", "Unknown")
-            .replace("{filesystem}", "Unknown")
-            .replace("{space_used}", "Unknown")
-            .replace("{space_avail}", "Unknown")
-            .replace("{space_total}", "Unknown")
-            .replace("{bar}", " ");
+        let section: &MountConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
+
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, config: &Configuration) -> String {
-        let dec_places: u32 = config.mounts.decimal_places.unwrap_or(config.decimal_places);
-        let use_ibis: bool = config.mounts.use_ibis.unwrap_or(config.use_ibis);
-
-        let mut bar: String = String::new();
-        if text.contains("{bar}") {
-            let left_border: &str = config.mounts.progress_left_border.as_ref().unwrap_or(&config.progress_left_border);
-            let right_border: &str = config.mounts.progress_right_border.as_ref().unwrap_or(&config.progress_right_border);
-            let progress: &str = config.mounts.progress_progress.as_ref().unwrap_or(&config.progress_progress);
-            let empty: &str = config.mounts.progress_empty.as_ref().unwrap_or(&config.progress_empty);
-            let length: u8 = config.mounts.progress_target_length.unwrap_or(config.progress_target_length);
-            formatter::make_bar(&mut bar, left_border, right_border, progress, empty, self.percent, length);
-        }
-
-        #[allow(clippy::cast_possible_truncation)]
-        formatter::process_percentage_placeholder(text, formatter::round(f64::from(self.percent), dec_places) as f32, config)
-            .replace("{device}", &self.device)
-            .replace("{mount}", &self.mount)
-            .replace("{filesystem}", &self.filesystem)
-            .replace("{space_used}", &formatter::auto_format_bytes(self.space_total_kb - self.space_avail_kb, use_ibis, dec_places))
-            .replace("{space_avail}", &formatter::auto_format_bytes(self.space_avail_kb, use_ibis, dec_places))
-            .replace("{space_total}", &formatter::auto_format_bytes(self.space_total_kb, use_ibis, dec_places))
-            .replace("{bar}", &bar.to_string())
+        let section: &MountConfiguration = config.section();
+        let dec_places: u32 = section.decimal_places.unwrap_or(config.decimal_places);
+        let use_ibis: bool = section.use_ibis.unwrap_or(config.use_ibis);
+
+        template::render(text, &|name| match name {
+            #[allow(clippy::cast_possible_truncation)]
+            "percent" => Some(formatter::percentage_string(formatter::round(f64::from(self.percent), dec_places) as f32, config)),
+            "device" => Some(self.device.clone()),
+            "mount" => Some(self.mount.clone()),
+            "filesystem" => Some(self.filesystem.clone()),
+            "space_used" => Some(formatter::auto_format_bytes(self.space_total_kb - self.space_avail_kb, use_ibis, dec_places)),
+            "space_avail" => Some(formatter::auto_format_bytes(self.space_avail_kb, use_ibis, dec_places)),
+            "space_total" => Some(formatter::auto_format_bytes(self.space_total_kb, use_ibis, dec_places)),
+            "bar" => {
+                let left_border: &str = section.progress_left_border.as_ref().unwrap_or(&config.progress_left_border);
+                let right_border: &str = section.progress_right_border.as_ref().unwrap_or(&config.progress_right_border);
+                let progress: &str = section.progress_progress.as_ref().unwrap_or(&config.progress_progress);
+                let empty: &str = section.progress_empty.as_ref().unwrap_or(&config.progress_empty);
+                let length: u8 = section.progress_target_length.unwrap_or(config.progress_target_length);
+                let mut bar: String = String::new();
+                formatter::make_bar(&mut bar, left_border, right_border, progress, empty, self.percent, length);
+                Some(bar)
+            },
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
-        if format.contains("{device}") {
+        if template.uses("device") {
             info_flags |= MOUNTS_INFOFLAG_DEVICE;
         }
-        if format.contains("{space_used}") || format.contains("bar") {
+        if template.uses("space_used") || template.uses("bar") {
             info_flags |= MOUNTS_INFOFLAG_SPACE_USED;
         }
-        if format.contains("{space_avail}") {
+        if template.uses("space_avail") {
             info_flags |= MOUNTS_INFOFLAG_SPACE_AVAIL;
         }
-        if format.contains("{space_total}") || format.contains("bar") {
+        if template.uses("space_total") || template.uses("bar") {
             info_flags |= MOUNTS_INFOFLAG_SPACE_TOTAL;
         }
 
         info_flags
     }
+
+    fn style_lines(&self, config: &Configuration) -> Vec<(String, String)> {
+        if self.is_ignored(config) {
+            return Vec::new();
+        }
+        vec![self.style(config)]
+    }
 }
 impl MountInfo {
+    pub fn device(&self) -> &str {
+        &self.device
+    }
+    pub fn mount(&self) -> &str {
+        &self.mount
+    }
+    pub fn filesystem(&self) -> &str {
+        &self.filesystem
+    }
+    pub fn space_avail_kb(&self) -> u64 {
+        self.space_avail_kb
+    }
+    pub fn space_total_kb(&self) -> u64 {
+        self.space_total_kb
+    }
+    pub fn percent(&self) -> f32 {
+        self.percent
+    }
+
     pub fn is_ignored(&self, config: &Configuration) -> bool {
-        for x in &config.mounts.ignore {
+        for x in &config.section::<MountConfiguration>().ignore {
             if x.is_empty() {
                 continue;
             }
@@ -139,10 +166,29 @@ const MOUNTS_INFOFLAG_SPACE_USED: u32 = 4;
 const MOUNTS_INFOFLAG_SPACE_TOTAL: u32 = 8;
 const MOUNTS_INFOFLAG_SPACE_AVAIL: u32 = 16;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "mounts",
+    config: ConfigSection::of::<MountConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|config| module::boxed(get_mounted_drives(config))),
+    unknown_output: MountInfo::unknown_output,
+    placeholders: &["device", "mount", "filesystem", "space_used", "space_avail", "space_total", "percent", "bar"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("mounts.title", "Disk ({mount})").unwrap();
+    builder = builder.set_default("mounts.format", "{space_used} used of {space_total} ({percent}) [{filesystem}]").unwrap();
+    builder = builder.set_default("mounts.ignore", vec![""]).unwrap();
+
+    builder
+}
+
 pub fn get_mounted_drives(config: &Configuration) -> Result<Vec<MountInfo>, ModuleError> {
+    let section: &MountConfiguration = config.section();
     let mut mounts: Vec<MountInfo> = Vec::new();
     // title is tagged onto the end here to account for the title placeholders
-    let info_flags: u32 = MountInfo::gen_info_flags(&format!("{}{}", config.mounts.format, config.mounts.title));
+    let info_flags: u32 = MountInfo::gen_info_flags(&format!("{}{}", section.format, section.title));
 
     #[cfg(not(feature = "android"))]
     let path: &str = "/etc/mtab";
@@ -154,9 +200,9 @@ pub fn get_mounted_drives(config: &Configuration) -> Result<Vec<MountInfo>, Modu
         path = "/proc/mounts";
     }
 
-    let file: File = match File::open(path) {
+    let file: File = match File::open(util::root_path(&config.root, path)) {
         Ok(r) => r,
-        Err(e) => return Err(ModuleError::new("Mounts", format!("Unable to read from /etc/mtab: {e}"))),
+        Err(e) => return Err(ModuleError::new("Mounts", format!("Unable to read from {path}: {e}"))),
     };
     let buffer: BufReader<File> = BufReader::new(file);
     let mut device_cache: Vec<String> = Vec::new();
@@ -176,7 +222,7 @@ pub fn get_mounted_drives(config: &Configuration) -> Result<Vec<MountInfo>, Modu
     
         let mut mount: MountInfo = MountInfo::new();
         let device_name: &str = entries[0];
-        mount.device = match get_device_name(device_name) {
+        mount.device = match get_device_name(&config.root, device_name) {
             Some(r) => r,
             None => continue, // Invalid device or not a device we want
         };
@@ -187,7 +233,7 @@ pub fn get_mounted_drives(config: &Configuration) -> Result<Vec<MountInfo>, Modu
         }
         device_cache.push(device_name.to_string());
 
-        if !is_device_wanted(&mount.device) {
+        if !is_device_wanted(&config.root, &mount.device) {
             continue; // bullshit
         }
 
@@ -210,7 +256,7 @@ pub fn get_mounted_drives(config: &Configuration) -> Result<Vec<MountInfo>, Modu
 
         // statfs to get space data
         if is_flag_set_u32(info_flags, MOUNTS_INFOFLAG_SPACE_AVAIL | MOUNTS_INFOFLAG_SPACE_USED | MOUNTS_INFOFLAG_SPACE_TOTAL) {
-            call_statfs(&mount_point, &mut mount)?;
+            call_statfs(&util::root_path(&config.root, &mount_point), &mut mount)?;
         }
 
         mounts.push(mount);
@@ -223,8 +269,8 @@ pub fn get_mounted_drives(config: &Configuration) -> Result<Vec<MountInfo>, Modu
 // pasted i swear)
 // https://github.com/GuillaumeGomez/sysinfo/blob/master/src/unix/linux/disk.rs#L96
 #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation, clippy::cast_precision_loss)]
-fn call_statfs(path: &str, mount: &mut MountInfo) -> Result<(), ModuleError> {
-    let mut bytes: Vec<u8> = path.as_bytes().to_vec();
+fn call_statfs(path: &Path, mount: &mut MountInfo) -> Result<(), ModuleError> {
+    let mut bytes: Vec<u8> = path.as_os_str().as_bytes().to_vec();
     bytes.push(0);
     unsafe { // spooky
         let mut buffer: statfs = mem::zeroed();
@@ -234,7 +280,7 @@ fn call_statfs(path: &str, mount: &mut MountInfo) -> Result<(), ModuleError> {
                 Some(r) => r.to_string(),
                 None => "N/A".to_string(),
             };
-            return Err(ModuleError::new("Mounts", format!("'statfs' syscall failed for mount point {path} (code {c})")))
+            return Err(ModuleError::new("Mounts", format!("'statfs' syscall failed for mount point {} (code {c})", path.display())))
         }
 
         mount.space_total_kb = (buffer.f_blocks * buffer.f_bsize as u64) / 1000;
@@ -244,43 +290,42 @@ fn call_statfs(path: &str, mount: &mut MountInfo) -> Result<(), ModuleError> {
     Ok(())
 }
 
-fn get_device_name(device_name: &str) -> Option<String> {
+fn get_device_name(root: &str, device_name: &str) -> Option<String> {
     // This method is also responsible for filtering out any devices we don't want
-    let dev: String;
+    let link_dir: &str;
+    let link_name: &str;
     if let Some(uuid) = device_name.strip_prefix("UUID=") {
-        // UUID
-        let uuid_path: PathBuf = Path::new("/dev/disk/by-uuid/").join(uuid);
-        if !uuid_path.is_symlink() {
-            return None; // ???
-        }
-        let Ok(device) = fs::canonicalize(uuid_path) else { return None };
-        dev = device.to_str().unwrap().to_string();
+        link_dir = "/dev/disk/by-uuid/";
+        link_name = uuid;
     } else if let Some(label) = device_name.strip_prefix("LABEL=") {
-        let label_path: PathBuf = Path::new("/dev/disk/by-label/").join(label);
-        if !label_path.is_symlink() {
-            return None; // ???
-        }
-        let Ok(device) = fs::canonicalize(label_path) else { return None };
-        dev = device.to_str().unwrap().to_string();
+        link_dir = "/dev/disk/by-label/";
+        link_name = label;
     } else if let Some(partlabel) = device_name.strip_prefix("PARTLABEL=") {
-        let label_path: PathBuf = Path::new("/dev/disk/by-partlabel/").join(partlabel);
-        if !label_path.is_symlink() {
-            return None; // ???
-        }
-        let Ok(device) = fs::canonicalize(label_path) else { return None };
-        dev = device.to_str().unwrap().to_string();
+        link_dir = "/dev/disk/by-partlabel/";
+        link_name = partlabel;
     } else {
         // regular old devices
-        dev = device_name.to_string();
+        return Some(device_name.to_string());
+    }
+
+    let link_path: PathBuf = util::root_path(root, link_dir).join(link_name);
+    if !link_path.is_symlink() {
+        return None; // ???
     }
+    let Ok(device) = fs::canonicalize(link_path) else { return None };
+    // Report the device as it's seen from inside the root, not from our side of it
+    let device: &Path = match device.strip_prefix(root) {
+        Ok(r) => r,
+        Err(_) => &device,
+    };
 
-    Some(dev)
+    Some(Path::new("/").join(device).to_str()?.to_string())
 }
 
-fn is_device_wanted(device_name: &str) -> bool {
+fn is_device_wanted(root: &str, device_name: &str) -> bool {
     // WSL
     // FIXME: This is INCREDIBLY hacky
-    if util::in_wsl() && &device_name[1..3] == ":\\" {
+    if util::in_wsl(root) && &device_name[1..3] == ":\\" {
         return true;
     }
 
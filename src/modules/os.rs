@@ -1,16 +1,20 @@
 use core::str;
-use std::path::Path;
 
 #[cfg(feature = "android")]
 use {android_system_properties::AndroidSystemProperties, std::env};
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, common_sources::syscalls::SyscallCache, util::{self, is_flag_set_u32}, ModuleError};
+use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, common_sources::syscalls::SyscallCache, util::{self, is_flag_set_u32}, ModuleError};
 
+#[derive(Serialize)]
 pub struct OSInfo {
     distro: String,
-    pub distro_id: String,
+    distro_id: String,
+    distro_id_like: Vec<String>,
+    ansi_color: Option<String>,
+    logo: Option<String>,
     kernel: String,
 }
 #[derive(Deserialize)]
@@ -30,75 +34,149 @@ impl Module for OSInfo {
         OSInfo {
             distro: "Unknown".to_string(),
             distro_id: "Unknown".to_string(),
+            distro_id_like: Vec::new(),
+            ansi_color: None,
+            logo: None,
             kernel: "Unknown".to_string(),
         }
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.os.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.os.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.os.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.os.separator.as_ref().unwrap_or(&config.separator);
+        let section: &OSConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.os.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.os.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.os.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.os.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.os.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.os.separator.as_ref().unwrap_or(&config.separator);
+        let section: &OSConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.os.title
-            .replace("{distro}", "Unknown")
-            .replace("{kernel}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{distro}", &self.distro)
-            .replace("{kernel}", &self.kernel)
+        template::render(text, &|name| match name {
+            "distro" => Some(self.distro.clone()),
+            "kernel" => Some(self.kernel.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
-        if format.contains("{distro}") {
+        if template.uses("distro") {
            info_flags |= OS_INFOFLAG_DISTRO;
         }
-        if format.contains("{kernel}") {
+        if template.uses("kernel") {
             info_flags |= OS_INFOFLAG_KERNEL;
         }
 
         info_flags
     }
+
+    fn style_lines(&self, config: &Configuration) -> Vec<(String, String)> {
+        if config.section::<OSConfiguration>().newline_kernel {
+            return vec![self.style(config), self.style_kernel(config)];
+        }
+        vec![self.style(config)]
+    }
 }
 impl OSInfo {
+    pub fn distro(&self) -> &str {
+        &self.distro
+    }
+    pub fn distro_id(&self) -> &str {
+        &self.distro_id
+    }
+    // The distros this one is based on, from ID_LIKE, closest first
+    pub fn distro_id_like(&self) -> &[String] {
+        &self.distro_id_like
+    }
+    // The SGR parameters of the distro's color, e.g "0;38;2;23;147;209"
+    pub fn ansi_color(&self) -> Option<&str> {
+        self.ansi_color.as_deref()
+    }
+    // The name of the distro's logo icon, e.g "archlinux-logo"
+    pub fn logo(&self) -> Option<&str> {
+        self.logo.as_deref()
+    }
+    // The IDs to look the distro's ASCII and color up by, most specific first
+    // The logo goes before ID_LIKE, as derivatives sometimes have their own icon named after them
+    // when their ID isn't
+    pub fn distro_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = vec![&self.distro_id];
+        if let Some(logo) = &self.logo {
+            let logo: &str = logo.trim_start_matches("distributor-logo-");
+            let logo: &str = logo.strip_suffix("-icon").unwrap_or(logo);
+            let logo: &str = logo.strip_suffix("-logo").unwrap_or(logo);
+            if logo != self.distro_id {
+                ids.push(logo);
+            }
+        }
+        ids.extend(self.distro_id_like.iter().map(String::as_str));
+        ids
+    }
+    pub fn kernel(&self) -> &str {
+        &self.kernel
+    }
+
     // Identical to the regular style method, but placeholder's in the kernel instead
     pub fn style_kernel(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.os.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.os.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.os.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.os.separator.as_ref().unwrap_or(&config.separator);
+        let section: &OSConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let value: String = self.replace_color_placeholders(&config.os.kernel_format.replace("{kernel}", &self.kernel), config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.kernel_format, config), config);
 
-        Self::default_style(config, &config.os.kernel_title, title_color, title_bold, title_italic, separator, &value)
+        Self::default_style(config, &section.kernel_title, title_color, title_bold, title_italic, separator, &value)
     }
 }
 
 const OS_INFOFLAG_DISTRO: u32 = 1;
 const OS_INFOFLAG_KERNEL: u32 = 2;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "os",
+    config: ConfigSection::of::<OSConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Syscalls(|config, syscall_cache| module::boxed(get_os(config, syscall_cache))),
+    unknown_output: OSInfo::unknown_output,
+    placeholders: &["distro", "kernel"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("os.title", "Operating System").unwrap();
+    builder = builder.set_default("os.format", "{distro} ({kernel})").unwrap();
+    builder = builder.set_default("os.newline_kernel", false).unwrap();
+    builder = builder.set_default("os.kernel_title", "Kernel").unwrap();
+    builder = builder.set_default("os.kernel_format", "Linux {kernel}").unwrap();
+
+    builder
+}
+
 pub fn get_os(config: &Configuration, syscall_cache: &mut SyscallCache) -> Result<OSInfo, ModuleError> {
+    let section: &OSConfiguration = config.section();
     let mut os: OSInfo = OSInfo::new();
 
-    let mut format: String = config.os.format.to_string();
-    if config.os.newline_kernel {
-        format.push_str(&config.os.kernel_format);
+    let mut format: String = section.format.to_string();
+    if section.newline_kernel {
+        format.push_str(&section.kernel_format);
     }
     let info_flags: u32 = OSInfo::gen_info_flags(&format);
 
@@ -119,11 +197,11 @@ pub fn get_os(config: &Configuration, syscall_cache: &mut SyscallCache) -> Resul
             }
             os.distro_id = "android".to_string();
         } else {
-            parse_os_release(&mut os)?;
+            parse_os_release(&config.root, &mut os)?;
         }
 
         #[cfg(not(feature = "android"))]
-        parse_os_release(&mut os)?;
+        parse_os_release(&config.root, &mut os)?;
     }
 
     // Kernel
@@ -134,21 +212,49 @@ pub fn get_os(config: &Configuration, syscall_cache: &mut SyscallCache) -> Resul
     Ok(os)
 }
 
-fn parse_os_release(os: &mut OSInfo) -> Result<(), ModuleError> {
-    let contents = match util::file_read(Path::new("/etc/os-release")) {
+fn parse_os_release(root: &str, os: &mut OSInfo) -> Result<(), ModuleError> {
+    let contents = match util::file_read(&util::root_path(root, "/etc/os-release")) {
         Ok(r) => r,
         Err(e) => return Err(ModuleError::new("OS", format!("Can't read from /etc/os-release - {e}"))),
     };
-    for line in contents.lines().collect::<Vec<&str>>() {
-        if line.starts_with("PRETTY_NAME=") {
-            os.distro = line[13..line.len() - 1].to_string();
-            continue;
-        }
-        if line.starts_with("ID=") {
-            os.distro_id = line[3..line.len()].trim().to_string();
-            continue;
+    read_os_release(&contents, os);
+
+    Ok(())
+}
+fn read_os_release(contents: &str, os: &mut OSInfo) {
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue
+        };
+        // Values can be quoted or not
+        let value: &str = value.trim().trim_matches(|x| x == '"' || x == '\'');
+        match key {
+            "PRETTY_NAME" => os.distro = value.to_string(),
+            "ID" => os.distro_id = value.to_string(),
+            "ID_LIKE" => os.distro_id_like = value.split_whitespace().map(str::to_string).collect(),
+            "ANSI_COLOR" => os.ansi_color = Some(value.to_string()),
+            "LOGO" => os.logo = Some(value.to_string()),
+            _ => {}
         }
     }
+}
 
-    Ok(())
+mod tests {
+    #[test]
+    fn test_os_release() {
+        use crate::{module::Module, modules::os::{read_os_release, OSInfo}};
+
+        let mut os: OSInfo = OSInfo::new();
+        read_os_release("NAME=\"CachyOS Linux\"\nPRETTY_NAME=\"CachyOS\"\nID=cachyos\nID_LIKE=\"arch\"\nANSI_COLOR=\"38;2;23;147;209\"\nLOGO=cachyos\n", &mut os);
+        assert_eq!(os.distro(), "CachyOS");
+        assert_eq!(os.distro_id(), "cachyos");
+        assert_eq!(os.ansi_color(), Some("38;2;23;147;209"));
+        assert_eq!(os.distro_ids(), vec!["cachyos", "arch"]);
+
+        let mut os: OSInfo = OSInfo::new();
+        read_os_release("PRETTY_NAME='Kubuntu 24.04'\nID=\"kubuntu\"\nID_LIKE=\"ubuntu debian\"\nLOGO=distributor-logo-kubuntu\n", &mut os);
+        assert_eq!(os.distro(), "Kubuntu 24.04");
+        assert_eq!(os.distro_ids(), vec!["kubuntu", "ubuntu", "debian"]);
+        assert_eq!(os.logo(), Some("distributor-logo-kubuntu"));
+    }
 }
@@ -2,13 +2,20 @@ use core::str;
 use std::fs::{read_dir, ReadDir};
 
 use colored::{ColoredString, Colorize};
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, common_sources::package_managers::{self, MANAGER_DPKG, MANAGER_HOMEBREW, MANAGER_PACMAN, MANAGER_XBPS}};
+use crate::{cache::{CachePolicy, CacheSignal}, config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::Template, common_sources::package_managers::{self, MANAGER_DPKG, MANAGER_HOMEBREW, MANAGER_PACMAN, MANAGER_XBPS}, util};
 
+#[derive(Serialize, Deserialize)]
 pub struct PackagesInfo {
     packages: Vec<ManagerInfo>
 }
+impl PackagesInfo {
+    pub fn packages(&self) -> &[ManagerInfo] {
+        &self.packages
+    }
+}
 #[derive(Deserialize)]
 pub struct PackagesConfiguration {
     pub title: String,
@@ -27,17 +34,18 @@ impl Module for PackagesInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.packages.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.packages.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.packages.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.packages.separator.as_ref().unwrap_or(&config.separator);
+        let section: &PackagesConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
         // Full style
         let mut title_final: String = String::new();
 
         // Title
-        if !config.packages.title.trim().is_empty() {
-            let mut title: ColoredString = title_color.color_string(&config.packages.title);
+        if !section.title.trim().is_empty() {
+            let mut title: ColoredString = title_color.color_string(&section.title);
             if title_bold {
                 title = title.bold();
             }
@@ -48,9 +56,10 @@ impl Module for PackagesInfo {
             title_final.push_str(&title.to_string());
         }
 
+        let format: Template = Template::parse(&section.format);
         let mut value: String = String::new();
         for manager in &self.packages {
-            if config.packages.ignore.contains(&manager.manager_name) {
+            if section.ignore.contains(&manager.manager_name) {
                 continue
             }
             if manager.package_count == 0 {
@@ -60,9 +69,11 @@ impl Module for PackagesInfo {
             if !value.is_empty() {
                 value.push_str(", ");
             }
-            // :(
-            value.push_str(&config.packages.format.replace("{manager}", &manager.manager_name)
-                .replace("{count}", &manager.package_count.to_string()));
+            value.push_str(&format.render(&|name| match name {
+                "manager" => Some(manager.manager_name.clone()),
+                "count" => Some(manager.package_count.to_string()),
+                _ => None
+            }));
         }
 
         let mut format_final: String = separator.to_string();
@@ -76,10 +87,15 @@ impl Module for PackagesInfo {
         unimplemented!()
     }
 
-    fn unknown_output(_config: &Configuration) -> (String, String) {
-        // get_packages can't fail, so this isn't implemented
-        // if it does, your fucked, and panic time ensures
-        panic!("Packages should never fail, something's wrong. Report this to my GitHub please.");
+    fn unknown_output(config: &Configuration) -> (String, String) {
+        // get_packages can't fail, so this is only shown if it times out
+        let section: &PackagesConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
+
+        Self::default_style(config, &section.title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn gen_info_flags(_: &str) -> u32 {
@@ -87,11 +103,19 @@ impl Module for PackagesInfo {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct ManagerInfo {
     manager_name: String,
     package_count: u64
 }
 impl ManagerInfo {
+    pub fn manager_name(&self) -> &str {
+        &self.manager_name
+    }
+    pub fn package_count(&self) -> u64 {
+        self.package_count
+    }
+
     fn fill(manager_name: &str, package_count: u64) -> ManagerInfo {
         ManagerInfo {
             manager_name: manager_name.to_string(),
@@ -100,7 +124,30 @@ impl ManagerInfo {
     }
 }
 
-pub fn get_packages(package_managers: &package_managers::ManagerInfo) -> PackagesInfo {
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "packages",
+    config: ConfigSection::of::<PackagesConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::PackageManagers(|config, package_managers| module::boxed(Ok(get_packages(config, package_managers)))),
+    unknown_output: PackagesInfo::unknown_output,
+    placeholders: &["manager", "count"],
+    volatile: false,
+    cache: Some(CachePolicy {
+        invalidated_by: CacheSignal::PackageDatabases,
+        // The ignore list is only used when styling, so nothing here changes what's detected
+        config_key: |_| Some(String::new()),
+        restore: module::restore::<PackagesInfo>,
+    }),
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("packages.title", "Packages").unwrap();
+    builder = builder.set_default("packages.format", "{count} ({manager})").unwrap();
+    builder = builder.set_default("packages.ignore", Vec::<String>::new()).unwrap();
+
+    builder
+}
+
+pub fn get_packages(config: &Configuration, package_managers: &package_managers::ManagerInfo) -> PackagesInfo {
     let mut packages: PackagesInfo = PackagesInfo::new();
 
     packages.packages.push(ManagerInfo::fill("pacman", package_managers.find_all_packages_from(MANAGER_PACMAN).values().len() as u64));
@@ -108,12 +155,12 @@ pub fn get_packages(package_managers: &package_managers::ManagerInfo) -> Package
     packages.packages.push(ManagerInfo::fill("xbps", package_managers.find_all_packages_from(MANAGER_XBPS).values().len() as u64));
     packages.packages.push(ManagerInfo::fill("brew", package_managers.find_all_packages_from(MANAGER_HOMEBREW).values().len() as u64));
 
-    if let Some(r) = process_flatpak_packages() {
+    if let Some(r) = process_flatpak_packages(&config.root) {
         packages.packages.push(ManagerInfo::fill("flatpak", r));
     }
 
     #[cfg(feature = "rpm_packages")]
-    if let Some(r) = process_rpm_packages() {
+    if let Some(r) = process_rpm_packages(&config.root) {
         packages.packages.push(ManagerInfo::fill("rpm", r));
     }
 
@@ -121,12 +168,12 @@ pub fn get_packages(package_managers: &package_managers::ManagerInfo) -> Package
 }
 
 #[cfg(feature = "rpm_packages")]
-fn process_rpm_packages() -> Option<u64> {
+fn process_rpm_packages(root: &str) -> Option<u64> {
     let mut result: u64 = 0;
 
     // Expected in my test env: 1981
     // Grabs from /var/lib/rpm/rpmdb.sqlite
-    let db: sqlite::Connection = match sqlite::open("/var/lib/rpm/rpmdb.sqlite") {
+    let db: sqlite::Connection = match sqlite::open(util::root_path(root, "/var/lib/rpm/rpmdb.sqlite")) {
         Ok(r) => r,
         Err(_) => return None,
     };
@@ -147,19 +194,19 @@ fn process_rpm_packages() -> Option<u64> {
     }
 }
 
-pub fn process_flatpak_packages() -> Option<u64> {
+pub fn process_flatpak_packages(root: &str) -> Option<u64> {
     // This counts everything in /app and /runtime
     // This does NOT get full information, as I don't care enough about flatpak to figure out
     // how to process it. It's simply used in the packages module and nowhere else for now
     let mut result: usize = 0;
 
-    let flatpak_apps_dir: ReadDir = match read_dir("/var/lib/flatpak/app") {
+    let flatpak_apps_dir: ReadDir = match read_dir(util::root_path(root, "/var/lib/flatpak/app")) {
         Ok(r) => r,
         Err(_) => return None,
     };
     result += flatpak_apps_dir.count();
 
-    let flatpak_runtime_dir: ReadDir = match read_dir("/var/lib/flatpak/runtime") {
+    let flatpak_runtime_dir: ReadDir = match read_dir(util::root_path(root, "/var/lib/flatpak/runtime")) {
         Ok(r) => r,
         Err(_) => return None,
     };
@@ -1,10 +1,12 @@
 use std::time::Duration;
 
 use dbus::{arg, blocking::{stdintf::org_freedesktop_dbus::Properties, Connection, Proxy}};
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, util::is_flag_set_u32, ModuleError};
+use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, util::is_flag_set_u32, ModuleError};
 
+#[derive(Serialize)]
 pub struct PlayerInfo {
     player: String,
     album: String,
@@ -13,6 +15,26 @@ pub struct PlayerInfo {
     track_artists: Vec<String>,
     status: String,
 }
+impl PlayerInfo {
+    pub fn player(&self) -> &str {
+        &self.player
+    }
+    pub fn album(&self) -> &str {
+        &self.album
+    }
+    pub fn album_artists(&self) -> &[String] {
+        &self.album_artists
+    }
+    pub fn track(&self) -> &str {
+        &self.track
+    }
+    pub fn track_artists(&self) -> &[String] {
+        &self.track_artists
+    }
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+}
 #[derive(Deserialize)]
 pub struct PlayerConfiguration {
     pub title: String,
@@ -37,52 +59,52 @@ impl Module for PlayerInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.player.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.player.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.player.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.player.separator.as_ref().unwrap_or(&config.separator);
+        let section: &PlayerConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.player.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.player.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.player.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.player.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.player.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.player.separator.as_ref().unwrap_or(&config.separator);
-
-        let title: String = config.player.title
-            .replace("{track}", "Unknown")
-            .replace("{album}", "Unknown")
-            .replace("{album_artists}", "Unknown")
-            .replace("{track_artists}", "Unknown")
-            .replace("{player}", "Unknown")
-            .replace("{status}", "Unknown");
+        let section: &PlayerConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
+
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{track}", &self.track)
-            .replace("{album}", &self.album)
-            .replace("{album_artists}", &self.album_artists.join(" "))
-            .replace("{track_artists}", &self.track_artists.join(" "))
-            .replace("{player}", &self.player)
-            .replace("{status}", &self.status)
+        template::render(text, &|name| match name {
+            "track" => Some(self.track.clone()),
+            "album" => Some(self.album.clone()),
+            "album_artists" => Some(self.album_artists.join(" ")),
+            "track_artists" => Some(self.track_artists.join(" ")),
+            "player" => Some(self.player.clone()),
+            "status" => Some(self.status.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
-        if format.contains("{track}") || format.contains("{album}") || format.contains("{track_artists}") || format.contains("{album_artists}") {
+        if template.uses("track") || template.uses("album") || template.uses("track_artists") || template.uses("album_artists") {
             info_flags |= PLAYER_INFOFLAG_METADATA;
         }
-        if format.contains("{player}") {
+        if template.uses("player") {
             info_flags |= PLAYER_INFOFLAG_PLAYER;
         }
-        if format.contains("{status}") {
+        if template.uses("status") {
             info_flags |= PLAYER_INFOFLAG_STATUS;
         }
 
@@ -94,10 +116,29 @@ const PLAYER_INFOFLAG_METADATA: u32 = 1;
 const PLAYER_INFOFLAG_PLAYER: u32 = 2;
 const PLAYER_INFOFLAG_STATUS: u32 = 4;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "player",
+    config: ConfigSection::of::<PlayerConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|config| module::boxed(get_players(config))),
+    unknown_output: PlayerInfo::unknown_output,
+    placeholders: &["track", "album", "album_artists", "track_artists", "player", "status"],
+    volatile: true,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("player.title", "Player ({player})").unwrap();
+    builder = builder.set_default("player.format", "{track} by {track_artists} ({album}) [{status}]").unwrap();
+    builder = builder.set_default("player.ignore", Vec::<String>::new()).unwrap();
+
+    builder
+}
+
 pub fn get_players(config: &Configuration) -> Result<Vec<PlayerInfo>, ModuleError> {
+    let section: &PlayerConfiguration = config.section();
     let mut players: Vec<PlayerInfo> = Vec::new();
     // title is tagged onto the end here to account for the title placeholders
-    let info_flags: u32 = PlayerInfo::gen_info_flags(&format!("{}{}", config.player.format, config.player.title));
+    let info_flags: u32 = PlayerInfo::gen_info_flags(&format!("{}{}", section.format, section.title));
 
     let conn: Connection = match Connection::new_session() {
         Ok(r) => r,
@@ -112,7 +153,7 @@ pub fn get_players(config: &Configuration) -> Result<Vec<PlayerInfo>, ModuleErro
     for player in found_players {
         let name: String = player.split('.').skip(3).collect::<Vec<&str>>().join(".");
         // this looks like a dubious use of inversions, but actually that's only because it is.
-        if !config.player.ignore.iter().all(|x| !name.starts_with(x)) {
+        if !section.ignore.iter().all(|x| !name.starts_with(x)) {
             continue // ignored
         }
 
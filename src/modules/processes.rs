@@ -1,12 +1,19 @@
 use std::fs::{read_dir, ReadDir};
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{formatter::CrabFetchColor, config_manager::Configuration, module::Module, ModuleError};
+use crate::{formatter::CrabFetchColor, config_manager::Configuration, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template, util, ModuleError};
 
+#[derive(Serialize)]
 pub struct ProcessesInfo {
     count: u32 // god forbid someone manages to hit this limit
 }
+impl ProcessesInfo {
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
 #[derive(Deserialize)]
 pub struct ProcessesConfiguration {
     pub title: String,
@@ -24,30 +31,35 @@ impl Module for ProcessesInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.processes.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.processes.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.processes.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.processes.separator.as_ref().unwrap_or(&config.separator);
+        let section: &ProcessesConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.processes.title, config);
-        let format: String = config.processes.format.clone().unwrap_or("{count}".to_string());
+        let title: String = self.replace_placeholders(&section.title, config);
+        let format: String = section.format.clone().unwrap_or("{count}".to_string());
         let value: String = self.replace_color_placeholders(&self.replace_placeholders(&format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.processes.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.processes.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.processes.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.processes.separator.as_ref().unwrap_or(&config.separator);
+        let section: &ProcessesConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.processes.title.replace("{count}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{count}", &self.count.to_string())
+        template::render(text, &|name| match name {
+            "count" => Some(self.count.to_string()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(_: &str) -> u32 {
@@ -55,11 +67,27 @@ impl Module for ProcessesInfo {
     }
 }
 
-pub fn get_process_count() -> Result<ProcessesInfo, ModuleError> {
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "processes",
+    config: ConfigSection::of::<ProcessesConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Independent(|config| module::boxed(get_process_count(config))),
+    unknown_output: ProcessesInfo::unknown_output,
+    placeholders: &["count"],
+    volatile: true,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("processes.title", "Total Processes").unwrap();
+
+    builder
+}
+
+pub fn get_process_count(config: &Configuration) -> Result<ProcessesInfo, ModuleError> {
     let mut process_info: ProcessesInfo = ProcessesInfo::new();
 
     // Scans /proc and simply checks if it's a number 
-    let dir: ReadDir = match read_dir("/proc") {
+    let dir: ReadDir = match read_dir(util::root_path(&config.root, "/proc")) {
         Ok(r) => r,
         Err(e) => return Err(ModuleError::new("Processes", format!("Failed to read /proc: {e}")))
     };
@@ -1,14 +1,27 @@
 use std::env;
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, common_sources::package_managers::ManagerInfo, proccess_info::ProcessInfo, util::is_flag_set_u32, versions, ModuleError};
+use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, common_sources::package_managers::ManagerInfo, proccess_info::ProcessInfo, util::is_flag_set_u32, versions, ModuleError};
 
+#[derive(Serialize)]
 pub struct ShellInfo {
     name: String,
     path: String,
     version: String,
 }
+impl ShellInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+}
 #[derive(Deserialize)]
 pub struct ShellConfiguration {
     pub title: String,
@@ -29,48 +42,50 @@ impl Module for ShellInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.shell.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.shell.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.shell.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.shell.separator.as_ref().unwrap_or(&config.separator);
+        let section: &ShellConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.shell.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.shell.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.shell.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.shell.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.shell.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.shell.separator.as_ref().unwrap_or(&config.separator);
+        let section: &ShellConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.shell.title
-            .replace("{name}", "Unknown")
-            .replace("{path}", "Unknown")
-            .replace("{version}", "Unknown")
-            .replace("{percentage}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{name}", &self.name)
-            .replace("{path}", &self.path)
-            .replace("{version}", &self.version)
+        template::render(text, &|name| match name {
+            "name" => Some(self.name.clone()),
+            "path" => Some(self.path.clone()),
+            "version" => Some(self.version.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
-        if format.contains("{name}") {
+        if template.uses("name") {
             info_flags |= SHELL_INFOFLAG_NAME;
             info_flags |= SHELL_INFOFLAG_PATH; // deps on path
         }
-        if format.contains("{path}") {
+        if template.uses("path") {
             info_flags |= SHELL_INFOFLAG_PATH;
         }
-        if format.contains("{version}") {
+        if template.uses("version") {
             // deps on all 3
             info_flags |= SHELL_INFOFLAG_NAME;
             info_flags |= SHELL_INFOFLAG_PATH;
@@ -111,11 +126,30 @@ pub const KNOWN_SHELLS: &[&str] = &[
     "xonsh"
 ];
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "shell",
+    config: ConfigSection::of::<ShellConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::PackageManagers(|config, package_managers| module::boxed(get_shell(config, package_managers))),
+    unknown_output: ShellInfo::unknown_output,
+    placeholders: &["name", "path", "version"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("shell.title", "Shell").unwrap();
+    builder = builder.set_default("shell.format", "{name} {version}").unwrap();
+    builder = builder.set_default("shell.show_default_shell", "false").unwrap();
+
+    builder
+}
+
 pub fn get_shell(config: &Configuration, package_managers: &ManagerInfo) -> Result<ShellInfo, ModuleError> {
+    let section: &ShellConfiguration = config.section();
     let mut shell: ShellInfo = ShellInfo::new();
-    let info_flags: u32 = ShellInfo::gen_info_flags(&config.shell.format);
+    let info_flags: u32 = ShellInfo::gen_info_flags(&section.format);
 
-    if config.shell.show_default_shell {
+    if section.show_default_shell {
         return get_default_shell(info_flags, package_managers);
     }
 
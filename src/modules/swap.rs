@@ -1,14 +1,27 @@
 use core::str;
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::{self, CrabFetchColor}, module::Module, common_sources::syscalls::SyscallCache, ModuleError};
+use crate::{config_manager::Configuration, formatter::{self, CrabFetchColor}, module::{self, ConfigSection, Detector, Module, RegisteredModule}, modules::uptime::UptimeConfiguration, template, common_sources::syscalls::SyscallCache, ModuleError};
 
+#[derive(Serialize)]
 pub struct SwapInfo {
     used_kb: u64,
     total_kb: u64,
     percent: f32
 }
+impl SwapInfo {
+    pub fn used_kb(&self) -> u64 {
+        self.used_kb
+    }
+    pub fn total_kb(&self) -> u64 {
+        self.total_kb
+    }
+    pub fn percent(&self) -> f32 {
+        self.percent
+    }
+}
 #[derive(Deserialize)]
 pub struct SwapConfiguration {
     pub title: String,
@@ -35,49 +48,50 @@ impl Module for SwapInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.swap.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.swap.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.swap.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.swap.separator.as_ref().unwrap_or(&config.separator);
+        let section: &SwapConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.swap.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.swap.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) { 
-        let title_color: &CrabFetchColor = config.swap.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.swap.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.swap.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.swap.separator.as_ref().unwrap_or(&config.separator);
+        let title_color: &CrabFetchColor = config.section::<SwapConfiguration>().title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = config.section::<SwapConfiguration>().title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = config.section::<SwapConfiguration>().title_italic.unwrap_or(config.title_italic);
+        let separator: &str = config.section::<SwapConfiguration>().separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.uptime.title
-            .replace("{used}", "Unknown")
-            .replace("{total}", "Unknown")
-            .replace("{bar}", "Unknown");
+        let title: String = template::render_unknown(&config.section::<UptimeConfiguration>().title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, config: &Configuration) -> String {
-        let dec_places: u32 = config.swap.decimal_places.unwrap_or(config.decimal_places);
-        let use_ibis: bool = config.swap.use_ibis.unwrap_or(config.use_ibis);
-
-        let mut bar: String = String::new();
-        if text.contains("{bar}") {
-            let left_border: &str = config.swap.progress_left_border.as_ref().unwrap_or(&config.progress_left_border);
-            let right_border: &str = config.swap.progress_right_border.as_ref().unwrap_or(&config.progress_right_border);
-            let progress: &str = config.swap.progress_progress.as_ref().unwrap_or(&config.progress_progress);
-            let empty: &str = config.swap.progress_empty.as_ref().unwrap_or(&config.progress_empty);
-            let length: u8 = config.swap.progress_target_length.unwrap_or(config.progress_target_length);
-            formatter::make_bar(&mut bar, left_border, right_border, progress, empty, self.percent, length);
-        }
-
-        #[allow(clippy::cast_possible_truncation)]
-        formatter::process_percentage_placeholder(text, formatter::round(f64::from(self.percent), dec_places) as f32, config)
-            .replace("{used}", &formatter::auto_format_bytes(self.used_kb, use_ibis, dec_places))
-            .replace("{total}", &formatter::auto_format_bytes(self.total_kb, use_ibis, dec_places))
-            .replace("{bar}", &bar)
+        let section: &SwapConfiguration = config.section();
+        let dec_places: u32 = section.decimal_places.unwrap_or(config.decimal_places);
+        let use_ibis: bool = section.use_ibis.unwrap_or(config.use_ibis);
+
+        template::render(text, &|name| match name {
+            #[allow(clippy::cast_possible_truncation)]
+            "percent" => Some(formatter::percentage_string(formatter::round(f64::from(self.percent), dec_places) as f32, config)),
+            "used" => Some(formatter::auto_format_bytes(self.used_kb, use_ibis, dec_places)),
+            "total" => Some(formatter::auto_format_bytes(self.total_kb, use_ibis, dec_places)),
+            "bar" => {
+                let left_border: &str = section.progress_left_border.as_ref().unwrap_or(&config.progress_left_border);
+                let right_border: &str = section.progress_right_border.as_ref().unwrap_or(&config.progress_right_border);
+                let progress: &str = section.progress_progress.as_ref().unwrap_or(&config.progress_progress);
+                let empty: &str = section.progress_empty.as_ref().unwrap_or(&config.progress_empty);
+                let length: u8 = section.progress_target_length.unwrap_or(config.progress_target_length);
+                let mut bar: String = String::new();
+                formatter::make_bar(&mut bar, left_border, right_border, progress, empty, self.percent, length);
+                Some(bar)
+            },
+            _ => None
+        })
     }
 
     fn gen_info_flags(_: &str) -> u32 {
@@ -85,6 +99,23 @@ impl Module for SwapInfo {
     }
 }
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "swap",
+    config: ConfigSection::of::<SwapConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Syscalls(|_, syscall_cache| module::boxed(get_swap(syscall_cache))),
+    unknown_output: SwapInfo::unknown_output,
+    placeholders: &["used", "total", "percent", "bar"],
+    volatile: true,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("swap.title", "Swap").unwrap();
+    builder = builder.set_default("swap.format", "{used} / {total} ({percent})").unwrap();
+
+    builder
+}
+
 #[allow(clippy::unnecessary_wraps, clippy::cast_precision_loss)]
 pub fn get_swap(syscall_cache: &mut SyscallCache) -> Result<SwapInfo, ModuleError> {
     let mut swap: SwapInfo = SwapInfo::new();
@@ -3,15 +3,28 @@ use std::env;
 #[cfg(feature = "android")]
 use std::path::Path;
 
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, common_sources::package_managers::ManagerInfo, proccess_info::ProcessInfo, util::{self, is_flag_set_u32}, versions, ModuleError};
+use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template::{self, Template}, common_sources::package_managers::ManagerInfo, proccess_info::ProcessInfo, util::{self, is_flag_set_u32}, versions, ModuleError};
 
+#[derive(Serialize)]
 pub struct TerminalInfo {
     name: String,
     path: String,
     version: String
 }
+impl TerminalInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+}
 #[derive(Deserialize)]
 pub struct TerminalConfiguration {
     pub title: String,
@@ -31,47 +44,50 @@ impl Module for TerminalInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.terminal.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.terminal.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.terminal.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.terminal.separator.as_ref().unwrap_or(&config.separator);
+        let section: &TerminalConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = self.replace_placeholders(&config.terminal.title, config);
-        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&config.terminal.format, config), config);
+        let title: String = self.replace_placeholders(&section.title, config);
+        let value: String = self.replace_color_placeholders(&self.replace_placeholders(&section.format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) { 
-        let title_color: &CrabFetchColor = config.terminal.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.terminal.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.terminal.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.terminal.separator.as_ref().unwrap_or(&config.separator);
+        let section: &TerminalConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.terminal.title
-            .replace("{name}", "Unknown")
-            .replace("{path}", "Unknown")
-            .replace("{version}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{name}", &self.name)
-            .replace("{path}", &self.path)
-            .replace("{version}", &self.version)
+        template::render(text, &|name| match name {
+            "name" => Some(self.name.clone()),
+            "path" => Some(self.path.clone()),
+            "version" => Some(self.version.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(format: &str) -> u32 {
         let mut info_flags: u32 = 0;
+        let template: Template = Template::parse(format);
 
-        if format.contains("{name}") {
+        if template.uses("name") {
             info_flags |= TERM_INFOFLAG_NAME;
             info_flags |= TERM_INFOFLAG_PATH; // deps on path
         }
-        if format.contains("{path}") {
+        if template.uses("path") {
             info_flags |= TERM_INFOFLAG_PATH;
         }
-        if format.contains("{version}") {
+        if template.uses("version") {
             // deps on all 3
             info_flags |= TERM_INFOFLAG_NAME;
             info_flags |= TERM_INFOFLAG_PATH;
@@ -114,9 +130,26 @@ const TERM_INFOFLAG_NAME: u32 = 1;
 const TERM_INFOFLAG_PATH: u32 = 2;
 const TERM_INFOFLAG_VERSION: u32 = 4;
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "terminal",
+    config: ConfigSection::of::<TerminalConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::PackageManagers(|config, package_managers| module::boxed(get_terminal(config, package_managers))),
+    unknown_output: TerminalInfo::unknown_output,
+    placeholders: &["name", "path", "version"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("terminal.title", "Terminal").unwrap();
+    builder = builder.set_default("terminal.format", "{name} {version}").unwrap();
+
+    builder
+}
+
 pub fn get_terminal(config: &Configuration, package_managers: &ManagerInfo) -> Result<TerminalInfo, ModuleError> {
     let mut terminal: TerminalInfo = TerminalInfo::new();
-    let info_flags: u32 = TerminalInfo::gen_info_flags(&config.terminal.format);
+    let info_flags: u32 = TerminalInfo::gen_info_flags(&config.section::<TerminalConfiguration>().format);
 
     #[cfg(feature = "android")]
     if env::consts::OS == "android" && Path::new("/data/data/com.termux/files/").exists() { // TODO: Does this still work in other emulators?
@@ -128,7 +161,7 @@ pub fn get_terminal(config: &Configuration, package_managers: &ManagerInfo) -> R
         return Ok(terminal);
     }
 
-    if util::in_wsl() {
+    if util::in_wsl(&config.root) {
         // We're in WSL
         terminal.name = "Windows Terminal".to_string();
         terminal.path = "N/A".to_string();
@@ -1,12 +1,25 @@
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{common_sources::gtk::GTKSettingsCache, config_manager::Configuration, formatter::CrabFetchColor, module::Module, ModuleError};
+use crate::{common_sources::gtk::GTKSettingsCache, config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template, ModuleError};
 
+#[derive(Serialize)]
 pub struct ThemeInfo {
     gtk2: String,
     gtk3: String,
     gtk4: String
 }
+impl ThemeInfo {
+    pub fn gtk2(&self) -> &str {
+        &self.gtk2
+    }
+    pub fn gtk3(&self) -> &str {
+        &self.gtk3
+    }
+    pub fn gtk4(&self) -> &str {
+        &self.gtk4
+    }
+}
 #[derive(Deserialize)]
 pub struct ThemeConfiguration {
     pub title: String,
@@ -26,30 +39,35 @@ impl Module for ThemeInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.theme.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.theme.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.theme.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.theme.separator.as_ref().unwrap_or(&config.separator);
+        let section: &ThemeConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let format: String = config.theme.format.clone().unwrap_or("{time}".to_string());
-        let title: String = self.replace_placeholders(&config.theme.title, config);
+        let format: String = section.format.clone().unwrap_or("{time}".to_string());
+        let title: String = self.replace_placeholders(&section.title, config);
         let value: String = self.replace_color_placeholders(&self.replace_placeholders(&format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) { 
-        let title_color: &CrabFetchColor = config.theme.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.theme.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.theme.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.theme.separator.as_ref().unwrap_or(&config.separator);
+        let section: &ThemeConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        Self::default_style(config, &config.theme.title, title_color, title_bold, title_italic, separator, "Unknown")
+        Self::default_style(config, &section.title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{gtk2}", &self.gtk2)
-            .replace("{gtk3}", &self.gtk3)
-            .replace("{gtk4}", &self.gtk4)
+        template::render(text, &|name| match name {
+            "gtk2" => Some(self.gtk2.clone()),
+            "gtk3" => Some(self.gtk3.clone()),
+            "gtk4" => Some(self.gtk4.clone()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(_: &str) -> u32 {
@@ -57,6 +75,23 @@ impl Module for ThemeInfo {
     }
 }
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "theme",
+    config: ConfigSection::of::<ThemeConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::GTKSettings(|gtk_settings| module::boxed(get_theme(gtk_settings))),
+    unknown_output: ThemeInfo::unknown_output,
+    placeholders: &["gtk2", "gtk3", "gtk4"],
+    volatile: false,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("theme.title", "Theme").unwrap();
+    builder = builder.set_default("theme.format", "Gtk3: {gtk3}  Gtk4: {gtk4}").unwrap();
+
+    builder
+}
+
 pub fn get_theme(gtk_settings: &mut GTKSettingsCache) -> Result<ThemeInfo, ModuleError> {
     let mut theme: ThemeInfo = ThemeInfo::new();
 
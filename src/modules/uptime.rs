@@ -1,13 +1,20 @@
-use std::{path::Path, time::Duration};
+use std::time::Duration;
 
 use humantime::format_duration;
-use serde::Deserialize;
+use config::{builder::DefaultState, ConfigBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::Module, common_sources::syscalls::SyscallCache, util, ModuleError};
+use crate::{config_manager::Configuration, formatter::CrabFetchColor, module::{self, ConfigSection, Detector, Module, RegisteredModule}, template, common_sources::syscalls::SyscallCache, util, ModuleError};
 
+#[derive(Serialize)]
 pub struct UptimeInfo {
     uptime: Duration,
 }
+impl UptimeInfo {
+    pub fn uptime(&self) -> Duration {
+        self.uptime
+    }
+}
 #[derive(Deserialize)]
 pub struct UptimeConfiguration {
     pub title: String,
@@ -25,30 +32,35 @@ impl Module for UptimeInfo {
     }
 
     fn style(&self, config: &Configuration) -> (String, String) {
-        let title_color: &CrabFetchColor = config.uptime.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.uptime.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.uptime.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.uptime.separator.as_ref().unwrap_or(&config.separator);
+        let section: &UptimeConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let format: String = config.uptime.format.clone().unwrap_or("{time}".to_string());
-        let title: String = self.replace_placeholders(&config.uptime.title, config);
+        let format: String = section.format.clone().unwrap_or("{time}".to_string());
+        let title: String = self.replace_placeholders(&section.title, config);
         let value: String = self.replace_color_placeholders(&self.replace_placeholders(&format, config), config);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, &value)
     }
     fn unknown_output(config: &Configuration) -> (String, String) { 
-        let title_color: &CrabFetchColor = config.uptime.title_color.as_ref().unwrap_or(&config.title_color);
-        let title_bold: bool = config.uptime.title_bold.unwrap_or(config.title_bold);
-        let title_italic: bool = config.uptime.title_italic.unwrap_or(config.title_italic);
-        let separator: &str = config.uptime.separator.as_ref().unwrap_or(&config.separator);
+        let section: &UptimeConfiguration = config.section();
+        let title_color: &CrabFetchColor = section.title_color.as_ref().unwrap_or(&config.title_color);
+        let title_bold: bool = section.title_bold.unwrap_or(config.title_bold);
+        let title_italic: bool = section.title_italic.unwrap_or(config.title_italic);
+        let separator: &str = section.separator.as_ref().unwrap_or(&config.separator);
 
-        let title: String = config.uptime.title.replace("{time}", "Unknown");
+        let title: String = template::render_unknown(&section.title, MODULE.placeholders);
 
         Self::default_style(config, &title, title_color, title_bold, title_italic, separator, "Unknown")
     }
 
     fn replace_placeholders(&self, text: &str, _: &Configuration) -> String {
-        text.replace("{time}", &format_duration(self.uptime).to_string())
+        template::render(text, &|name| match name {
+            "time" => Some(format_duration(self.uptime).to_string()),
+            _ => None
+        })
     }
 
     fn gen_info_flags(_: &str) -> u32 {
@@ -56,12 +68,28 @@ impl Module for UptimeInfo {
     }
 }
 
+pub const MODULE: RegisteredModule = RegisteredModule {
+    name: "uptime",
+    config: ConfigSection::of::<UptimeConfiguration>(),
+    fill_builder_defaults,
+    detect: Detector::Syscalls(|config, syscall_cache| module::boxed(get_uptime(config, syscall_cache))),
+    unknown_output: UptimeInfo::unknown_output,
+    placeholders: &["time"],
+    volatile: true,
+    cache: None,
+};
+fn fill_builder_defaults(mut builder: ConfigBuilder<DefaultState>) -> ConfigBuilder<DefaultState> {
+    builder = builder.set_default("uptime.title", "Uptime").unwrap();
+
+    builder
+}
+
 #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-pub fn get_uptime(syscall_cache: &mut SyscallCache) -> Result<UptimeInfo, ModuleError> {
+pub fn get_uptime(config: &Configuration, syscall_cache: &mut SyscallCache) -> Result<UptimeInfo, ModuleError> {
     let mut uptime: UptimeInfo = UptimeInfo::new();
 
     // Grabs from /proc/uptime
-    let Ok(contents) = util::file_read(Path::new("/proc/uptime")) else {
+    let Ok(contents) = util::file_read(&util::root_path(&config.root, "/proc/uptime")) else {
         // Backup to the sysinfo call
         use_syscall(syscall_cache, &mut uptime);
         return Ok(uptime);
@@ -1,34 +1,25 @@
 use config::{builder::DefaultState, Config, ConfigBuilder, FileFormat};
 
-use crate::config_manager::Configuration;
+use crate::config_manager::{self, Configuration};
 
 const BASIC_PRESET: &str = include_str!("../presets/basic.toml");
 const NEOFETCH_PRESET: &str = include_str!("../presets/neofetch.toml");
 const FULL_PRESET: &str = include_str!("../presets/full.toml");
 
-pub fn preset_full() -> Configuration {
-    let mut builder: ConfigBuilder<DefaultState> = Config::builder();
-    builder = builder.add_source(config::File::from_str(FULL_PRESET, FileFormat::Toml).required(true));
-
-    let config: Config = builder.build().expect("Failed to build configuration.");
-    let deserialized: Configuration = config.try_deserialize::<Configuration>().expect("Failed to deserialize configuration.");
-    deserialized
-}
-
-pub fn preset_neofetch() -> Configuration {
-    let mut builder: ConfigBuilder<DefaultState> = Config::builder();
-    builder = builder.add_source(config::File::from_str(NEOFETCH_PRESET, FileFormat::Toml).required(true));
-
-    let config: Config = builder.build().expect("Failed to build configuration.");
-    let deserialized: Configuration = config.try_deserialize::<Configuration>().expect("Failed to deserialize configuration.");
-    deserialized
+// The contents of a preset built into CrabFetch, by its name
+pub fn find_preset(name: &str) -> Option<&'static str> {
+    match name {
+        "full" => Some(FULL_PRESET),
+        "neofetch" => Some(NEOFETCH_PRESET),
+        "basic" => Some(BASIC_PRESET),
+        _ => None,
+    }
 }
 
-pub fn preset_basic() -> Configuration {
+pub fn parse_preset(contents: &str) -> Configuration {
     let mut builder: ConfigBuilder<DefaultState> = Config::builder();
-    builder = builder.add_source(config::File::from_str(BASIC_PRESET, FileFormat::Toml).required(true));
+    builder = builder.add_source(config::File::from_str(contents, FileFormat::Toml).required(true));
 
     let config: Config = builder.build().expect("Failed to build configuration.");
-    let deserialized: Configuration = config.try_deserialize::<Configuration>().expect("Failed to deserialize configuration.");
-    deserialized
+    config_manager::deserialize(config).expect("Failed to deserialize configuration.")
 }
@@ -1,6 +1,8 @@
 // Fetch info from a process from /proc 
 
-use std::{fs::{self, File}, io::Read, path::{Path, PathBuf}, os::unix::process::parent_id};
+use std::{fs::{self, File}, io::Read, path::PathBuf, os::unix::process::parent_id};
+
+use crate::util;
 
 // https://man7.org/linux/man-pages/man5/proc_pid_stat.5.html
 #[derive(Clone)]
@@ -40,6 +42,10 @@ pub struct ProcessInfo {
 }
 impl ProcessInfo {
     pub fn new(pid: u32) -> Self {
+        Self::new_in_root(pid, "/")
+    }
+    // Same as new, but reads from the /proc inside of the given root directory
+    pub fn new_in_root(pid: u32, root: &str) -> Self {
         ProcessInfo {
             pid,
             process_name: None,
@@ -47,7 +53,7 @@ impl ProcessInfo {
             cmdline: None,
             stat: None,
 
-            path: Path::new(&format!("/proc/{pid}")).to_owned()
+            path: util::root_path(root, &format!("/proc/{pid}"))
         }
     }
     pub fn new_from_parent() -> Self {
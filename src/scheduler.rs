@@ -0,0 +1,373 @@
+// Runs module detection across worker threads, so that one slow module doesn't hold up the rest
+//
+// Modules that don't share anything get a thread of their own. Modules that share a cache either
+// run one after the other on the same thread (GTK), share it between their threads with it being
+// filled by whichever needs it first (package managers) or are left to the main thread if the
+// cache can't be sent between threads at all (syscalls)
+//
+// Modules on a worker thread can be given a timeout, after which they're given up on and shown as
+// timed out. Timeouts are counted from the start of detection, as that's what holds up the output.
+// Anything on the main thread can't be given up on, but those are only ever quick syscalls and reads
+// Anything given up on is still tracked until its thread finishes, so --watch doesn't start it again
+// while it's stuck and leave another thread behind every refresh
+
+use std::{collections::HashMap, process::Command, sync::{mpsc::{self, RecvTimeoutError, Sender}, Arc, Mutex}, thread::{self, JoinHandle}, time::{Duration, Instant}};
+
+use serde::Deserialize;
+
+use crate::{cache::DetectionCache, common_sources::{gtk::GTKSettingsCache, package_managers::ManagerInfo, syscalls::SyscallCache}, config_manager::Configuration, formatter, module::{Detected, Detector, ModuleError, ModuleOutputs, RegisteredModule}, modules, util};
+
+type SyscallDetector = fn(&Configuration, &mut SyscallCache) -> Detected;
+type GTKDetector = fn(&mut GTKSettingsCache) -> Detected;
+type PackageManagerDetector = fn(&Configuration, &ManagerInfo) -> Detected;
+
+#[derive(Deserialize)]
+pub struct TimeoutConfiguration {
+    // The most time detection can take as a whole in milliseconds, or 0 to wait for everything
+    pub deadline_ms: u64,
+    // Shown in place of a timed out module, with {module} being the module's name
+    // The module's unknown output is used when this isn't set
+    pub text: Option<String>,
+    // The most time each module can take in milliseconds, keyed by the module's name
+    // These come from the timeout_ms in each module's own section, see config_manager::deserialize_sections
+    #[serde(skip)]
+    pub modules: HashMap<String, u64>
+}
+impl TimeoutConfiguration {
+    // The time a module has to finish in, being whichever's shorter out of its own timeout and the deadline
+    pub fn module_timeout(&self, name: &str) -> Option<Duration> {
+        let module: Option<u64> = self.modules.get(name).copied().filter(|x| *x != 0);
+        let deadline: Option<u64> = Some(self.deadline_ms).filter(|x| *x != 0);
+        module.into_iter()
+            .chain(deadline)
+            .min()
+            .map(Duration::from_millis)
+    }
+}
+
+// Modules that are being detected on another thread, including ones given up on that are still going
+static IN_FLIGHT: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+// Marks a module as in flight until it's dropped, which is when its thread is done with it
+struct InFlight(&'static str);
+impl InFlight {
+    fn start(name: &'static str) -> InFlight {
+        if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+            in_flight.push(name);
+        }
+        InFlight(name)
+    }
+    fn running(name: &str) -> bool {
+        IN_FLIGHT.lock().is_ok_and(|x| x.contains(&name))
+    }
+}
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let Ok(mut in_flight) = IN_FLIGHT.lock() else {
+            return;
+        };
+        if let Some(index) = in_flight.iter().position(|x| *x == self.0) {
+            in_flight.swap_remove(index);
+        }
+    }
+}
+
+struct FinishedModule {
+    name: &'static str,
+    time: Duration,
+    detected: Detected
+}
+
+// How long each module took to detect, along with the wall-clock time of the whole step
+pub struct DetectionTimes {
+    pub modules: Vec<(&'static str, Duration)>,
+    pub wall_clock: Duration
+}
+impl DetectionTimes {
+    // Time saved compared to running every module one after another
+    pub fn saving(&self) -> Duration {
+        let sequential: Duration = self.modules.iter().map(|x| x.1).sum();
+        sequential.saturating_sub(self.wall_clock)
+    }
+}
+
+// Detects every registered module in the config that isn't already known
+// Results are stored into the outputs as they come in, rendering order is left to the caller
+// If caching is enabled, anything still cached is used as-is and anything newly detected is stored
+// Anything that times out is stored as a timed out error, see TimeoutConfiguration
+pub fn detect_modules(config: &Arc<Configuration>, outputs: &mut ModuleOutputs, syscall_cache: &mut SyscallCache) -> DetectionTimes {
+    let cache: Option<Arc<DetectionCache>> = DetectionCache::open(config, syscall_cache).map(Arc::new);
+    detect(config, outputs, syscall_cache, cache.as_ref())
+}
+
+// Re-detects every volatile module along with anything that timed out last time, used by --watch
+// Anything that timed out and still hasn't finished is left timed out until it does, rather than started again
+// The cache is left alone, as it'd only be handing back the info that's being refreshed
+pub fn refresh_modules(config: &Arc<Configuration>, outputs: &mut ModuleOutputs, syscall_cache: &mut SyscallCache) -> DetectionTimes {
+    outputs.retain(|name, detected| {
+        if InFlight::running(name) {
+            return true;
+        }
+        let volatile: bool = modules::find_module(name).is_some_and(|x| x.volatile);
+        let timed_out: bool = detected.as_ref().is_err_and(ModuleError::is_timeout);
+        !volatile && !timed_out
+    });
+    // The syscalls are only cached for a single run
+    *syscall_cache = SyscallCache::new();
+    detect(config, outputs, syscall_cache, None)
+}
+
+fn detect(config: &Arc<Configuration>, outputs: &mut ModuleOutputs, syscall_cache: &mut SyscallCache, cache: Option<&Arc<DetectionCache>>) -> DetectionTimes {
+    let start: Instant = Instant::now();
+    let mut wanted: Vec<&'static RegisteredModule> = Vec::new();
+    for module in &config.modules {
+        let module_name: &str = module.split(':').next().unwrap_or(module);
+        if let Some(registered) = modules::find_module(module_name) {
+            if !outputs.contains(registered.name) && !wanted.iter().any(|x| x.name == registered.name) {
+                wanted.push(registered);
+            }
+        }
+    }
+
+    // Independent modules get their own thread straight away, everything else gets grouped by what it shares
+    let (sender, receiver) = mpsc::channel::<FinishedModule>();
+    let mut syscall_modules: Vec<(&'static str, SyscallDetector)> = Vec::new();
+    let mut gtk_modules: Vec<(InFlight, GTKDetector)> = Vec::new();
+    let mut package_modules: Vec<(InFlight, PackageManagerDetector)> = Vec::new();
+    let mut modules: Vec<(&'static str, Duration)> = Vec::new();
+    // Modules sent off to another thread, along with how long they have to finish
+    let mut pending: Vec<(&'static str, Option<Duration>)> = Vec::new();
+    for module in wanted {
+        if let Some(cache) = cache {
+            let cache_start: Instant = Instant::now();
+            if let Some(cached) = cache.load(config, module) {
+                outputs.insert(module.name, Ok(cached));
+                modules.push((module.name, cache_start.elapsed()));
+                continue;
+            }
+        }
+
+        if !matches!(module.detect, Detector::Syscalls(_)) {
+            pending.push((module.name, config.timeout.module_timeout(module.name)));
+        }
+        match module.detect {
+            Detector::Independent(detect) => {
+                let config: Arc<Configuration> = Arc::clone(config);
+                spawn_worker(sender.clone(), InFlight::start(module.name), move || detect(&config));
+            },
+            Detector::Syscalls(detect) => syscall_modules.push((module.name, detect)),
+            Detector::GTKSettings(detect) => gtk_modules.push((InFlight::start(module.name), detect)),
+            Detector::PackageManagers(detect) => package_modules.push((InFlight::start(module.name), detect)),
+        }
+    }
+
+    // GTK; these share the same settings cache so they run one after another on the same thread
+    if !gtk_modules.is_empty() {
+        let sender: Sender<FinishedModule> = sender.clone();
+        thread::spawn(move || {
+            let mut gtk_settings_cache: GTKSettingsCache = GTKSettingsCache::default();
+            for (in_flight, detect) in gtk_modules {
+                let module_start: Instant = Instant::now();
+                let detected: Detected = detect(&mut gtk_settings_cache);
+                send_finished(&sender, in_flight.0, module_start, detected);
+            }
+        });
+    }
+
+    // Package managers; these share the probed package managers, along with the version cache
+    if !package_modules.is_empty() {
+        let config: Arc<Configuration> = Arc::clone(config);
+        let cache: Option<Arc<DetectionCache>> = cache.cloned();
+        let sender: Sender<FinishedModule> = sender.clone();
+        thread::spawn(move || {
+            let mut package_managers: ManagerInfo = ManagerInfo::new(&config);
+            package_managers.version_cache = cache.and_then(|x| x.versions(&config));
+            let package_managers: Arc<ManagerInfo> = Arc::new(package_managers);
+
+            let mut workers: Vec<JoinHandle<()>> = Vec::new();
+            for (in_flight, detect) in package_modules {
+                let config: Arc<Configuration> = Arc::clone(&config);
+                let package_managers: Arc<ManagerInfo> = Arc::clone(&package_managers);
+                workers.push(spawn_worker(sender.clone(), in_flight, move || detect(&config, &package_managers)));
+            }
+
+            // Any versions they found are saved once they're all done
+            // This thread still holds a sender, so detection isn't finished until this is
+            for worker in workers {
+                let _ = worker.join();
+            }
+            if let Some(version_cache) = &package_managers.version_cache {
+                version_cache.save();
+            }
+        });
+    }
+
+    // Anything using the syscall cache stays on this thread, and runs while the workers do
+    for (name, detect) in syscall_modules {
+        let module_start: Instant = Instant::now();
+        let detected: Detected = detect(config, syscall_cache);
+        store_in_cache(cache.map(Arc::as_ref), config, name, &detected);
+        outputs.insert(name, detected);
+        modules.push((name, module_start.elapsed()));
+    }
+
+    // Collect the workers as they finish, giving up on any that go past their timeout
+    // Anything given up on is left to finish in the background, and whatever it sends is ignored
+    drop(sender);
+    while !pending.is_empty() {
+        let received: Result<FinishedModule, RecvTimeoutError> = match pending.iter().filter_map(|x| x.1).min() {
+            Some(timeout) => receiver.recv_timeout(timeout.saturating_sub(start.elapsed())),
+            None => receiver.recv().map_err(RecvTimeoutError::from),
+        };
+
+        match received {
+            Ok(finished) => {
+                if !pending.iter().any(|x| x.0 == finished.name) {
+                    continue;
+                }
+                pending.retain(|x| x.0 != finished.name);
+                store_in_cache(cache.map(Arc::as_ref), config, finished.name, &finished.detected);
+                outputs.insert(finished.name, finished.detected);
+                modules.push((finished.name, finished.time));
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                let elapsed: Duration = start.elapsed();
+                pending.retain(|(name, timeout)| {
+                    let Some(timeout) = timeout.filter(|x| *x <= elapsed) else {
+                        return true;
+                    };
+                    outputs.insert(name, Err(ModuleError::timed_out(name, timeout)));
+                    modules.push((name, elapsed));
+                    false
+                });
+            },
+            // Every worker is gone, which only happens early if one of them panicked
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    DetectionTimes {
+        modules,
+        wall_clock: start.elapsed()
+    }
+}
+
+fn spawn_worker<F>(sender: Sender<FinishedModule>, in_flight: InFlight, task: F) -> JoinHandle<()> where F: FnOnce() -> Detected + Send + 'static {
+    thread::spawn(move || {
+        // Moved in whole, so the module's only taken off the in flight list once this thread's done with it
+        let in_flight: InFlight = in_flight;
+        let start: Instant = Instant::now();
+        let detected: Detected = task();
+        send_finished(&sender, in_flight.0, start, detected);
+    })
+}
+
+fn store_in_cache(cache: Option<&DetectionCache>, config: &Configuration, name: &str, detected: &Detected) {
+    // Errors aren't cached, so the module gets another go next time
+    if let (Some(cache), Ok(output)) = (cache, detected) {
+        cache.store(config, name, output.as_ref());
+    }
+}
+
+fn send_finished(sender: &Sender<FinishedModule>, name: &'static str, start: Instant, detected: Detected) {
+    // Only fails if the main thread has stopped listening, in which case nobody wants the result
+    let _ = sender.send(FinishedModule {
+        name,
+        time: start.elapsed(),
+        detected
+    });
+}
+
+// What's shown in place of a module that timed out, see TimeoutConfiguration::text
+pub fn timed_out_output(config: &Configuration, name: &str, unknown_output: fn(&Configuration) -> (String, String)) -> (String, String) {
+    timed_out_text(config, name).unwrap_or_else(|| unknown_output(config))
+}
+fn timed_out_text(config: &Configuration, name: &str) -> Option<(String, String)> {
+    let text: &str = config.timeout.text.as_ref()?;
+    Some((String::new(), formatter::replace_color_placeholders(&text.replace("{module}", name), config)))
+}
+
+// Runs the command of a "command:" module, under the "command" timeout
+// These are ran as the output's styled rather than during detection, so they're given up on here instead
+pub fn run_command_module(config: &Configuration, command: &str) -> (String, String) {
+    let timeout: Option<Duration> = config.timeout.module_timeout("command");
+    let output = match util::command_output_timeout(Command::new("sh").arg("-c").arg(command), timeout) {
+        Ok(Some(r)) => r,
+        Ok(None) => return timed_out_text(config, "command").unwrap_or_else(|| (String::new(), format!("Command timed out: {command}"))),
+        Err(e) => return (String::new(), format!("Command failed: {e}")),
+    };
+
+    if output.status.success() {
+        let result = String::from_utf8(output.stdout).unwrap_or("Command failed: Unable to parse output.".to_string());
+        (String::new(), result)
+    } else {
+        (String::new(), format!("Command failed: {}", String::from_utf8(output.stderr).unwrap_or("Unknown command.".to_string())))
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_module_timeout() {
+        use config::{Config, FileFormat};
+        use crate::config_manager;
+        use std::time::Duration;
+
+        let mut config = config_manager::parse(&Some("none".to_string()), &None).unwrap();
+        config.timeout.deadline_ms = 2000;
+        config.timeout.modules.insert("player".to_string(), 500);
+        config.timeout.modules.insert("terminal".to_string(), 3000);
+        config.timeout.modules.insert("shell".to_string(), 0);
+        // Whichever's shorter, with 0 meaning no limit
+        assert_eq!(config.timeout.module_timeout("player"), Some(Duration::from_millis(500)));
+        assert_eq!(config.timeout.module_timeout("terminal"), Some(Duration::from_secs(2)));
+        assert_eq!(config.timeout.module_timeout("shell"), Some(Duration::from_secs(2)));
+        config.timeout.deadline_ms = 0;
+        assert_eq!(config.timeout.module_timeout("cpu"), None);
+
+        // Taken from the timeout_ms in each module's own section
+        let contents: &str = "[terminal]\ntimeout_ms = 500\n[command]\ntimeout_ms = 100\n";
+        let builder = config_manager::fill_builder_defaults(Config::builder().add_source(config::File::from_str(contents, FileFormat::Toml)));
+        let config = config_manager::deserialize(builder.build().unwrap()).unwrap();
+        assert_eq!(config.timeout.module_timeout("terminal"), Some(Duration::from_millis(500)));
+        assert_eq!(config.timeout.module_timeout("command"), Some(Duration::from_millis(100)));
+        assert_eq!(config.timeout.module_timeout("shell"), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_command_timeout() {
+        use crate::{config_manager, scheduler::run_command_module};
+
+        let mut config = config_manager::parse(&Some("none".to_string()), &None).unwrap();
+        config.timeout.modules.insert("command".to_string(), 100);
+        assert_eq!(run_command_module(&config, "echo hi"), (String::new(), "hi\n".to_string()));
+        assert_eq!(run_command_module(&config, "sleep 5"), (String::new(), "Command timed out: sleep 5".to_string()));
+
+        config.timeout.text = Some("{module} took too long".to_string());
+        assert_eq!(run_command_module(&config, "sleep 5"), (String::new(), "command took too long".to_string()));
+    }
+
+    #[test]
+    fn test_refresh_modules() {
+        use crate::{common_sources::syscalls::SyscallCache, config_manager, module::{ModuleError, ModuleOutputs}, scheduler::{refresh_modules, InFlight}};
+        use std::{sync::Arc, time::Duration};
+
+        let config = Arc::new(config_manager::parse(&Some("none".to_string()), &Some("os,uptime,hostname,datetime".to_string())).unwrap());
+        let mut outputs: ModuleOutputs = ModuleOutputs::new();
+        outputs.insert("os", Err(ModuleError::new("OS", "Kept as-is".to_string())));
+        outputs.insert("uptime", Err(ModuleError::new("Uptime", "Volatile".to_string())));
+        outputs.insert("hostname", Err(ModuleError::timed_out("hostname", Duration::from_millis(1))));
+        outputs.insert("datetime", Err(ModuleError::timed_out("datetime", Duration::from_millis(1))));
+        let stuck: InFlight = InFlight::start("datetime");
+
+        // Only the static module that didn't time out is left alone, along with the one that's still stuck
+        refresh_modules(&config, &mut outputs, &mut SyscallCache::new());
+        assert!(outputs.get("os").unwrap().is_err());
+        assert!(outputs.get("uptime").unwrap().is_ok());
+        assert!(outputs.get("hostname").unwrap().is_ok());
+        assert!(outputs.get("datetime").unwrap().is_err());
+
+        // Once it finishes it's detected again
+        drop(stuck);
+        refresh_modules(&config, &mut outputs, &mut SyscallCache::new());
+        assert!(outputs.get("datetime").unwrap().is_ok());
+    }
+}
@@ -0,0 +1,410 @@
+// The template language used by every module's title and format
+//
+// {name}                   - The placeholder's value
+// {name|fallback}          - The fallback is used when the value is unknown, and can hold placeholders itself
+// {name:spec}              - Aligns, pads or truncates the value, e.g {name:<20}, {name:>8}, {name:-^30}, {name:.16}
+// {?name:text}             - Only shows the text when name's value is known
+// {?name:text|otherwise}   - Same as above, but with something to show when it isn't
+// {{ and }}                - Literal braces
+//
+// Anything that doesn't parse as one of the above, or isn't a placeholder of the module, is kept as it was
+// written. This is what lets the color placeholders through, as they're replaced afterwards
+use crate::formatter;
+
+// What a module's info is set to when it couldn't be found, which along with empty values is treated as unknown
+pub const UNKNOWN: &str = "Unknown";
+
+pub struct Template<'a> {
+    parts: Vec<Part<'a>>
+}
+enum Part<'a> {
+    Text(&'a str),
+    Placeholder {
+        // The placeholder as it was written, for when it isn't one of the module's
+        source: &'a str,
+        name: &'a str,
+        spec: Spec,
+        fallback: Option<Template<'a>>
+    },
+    Conditional {
+        source: &'a str,
+        name: &'a str,
+        then: Template<'a>,
+        otherwise: Option<Template<'a>>
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Right,
+    Center
+}
+struct Spec {
+    fill: char,
+    align: Align,
+    width: usize,
+    max_width: Option<usize>
+}
+impl Default for Spec {
+    fn default() -> Self {
+        Spec {
+            fill: ' ',
+            align: Align::Left,
+            width: 0,
+            max_width: None
+        }
+    }
+}
+impl Spec {
+    // [[fill]align][width][.max_width]
+    fn parse(spec: &str) -> Option<Spec> {
+        let mut result: Spec = Spec::default();
+        let chars: Vec<char> = spec.chars().collect();
+        let mut i: usize = 0;
+        if chars.len() >= 2 && align_from(chars[1]).is_some() {
+            result.fill = chars[0];
+            result.align = align_from(chars[1])?;
+            i = 2;
+        } else if let Some(align) = chars.first().and_then(|x| align_from(*x)) {
+            result.align = align;
+            i = 1;
+        }
+
+        let width: String = chars[i..].iter().take_while(|x| x.is_ascii_digit()).collect();
+        i += width.len();
+        if !width.is_empty() {
+            result.width = width.parse().ok()?;
+        }
+        if chars.get(i) == Some(&'.') {
+            let max_width: String = chars[i + 1..].iter().take_while(|x| x.is_ascii_digit()).collect();
+            if max_width.is_empty() {
+                return None;
+            }
+            i += max_width.len() + 1;
+            result.max_width = Some(max_width.parse().ok()?);
+        }
+
+        if i != chars.len() {
+            return None;
+        }
+        Some(result)
+    }
+
+    fn apply(&self, value: String) -> String {
+        let mut value: String = value;
+        if let Some(max_width) = self.max_width {
+            value = truncate(&value, max_width);
+        }
+
+        let width: usize = visible_width(&value);
+        if width >= self.width {
+            return value;
+        }
+        let padding: usize = self.width - width;
+        let (left, right): (usize, usize) = match self.align {
+            Align::Left => (0, padding),
+            Align::Right => (padding, 0),
+            Align::Center => (padding / 2, padding - padding / 2),
+        };
+
+        let fill: String = self.fill.to_string();
+        format!("{}{value}{}", fill.repeat(left), fill.repeat(right))
+    }
+}
+fn align_from(c: char) -> Option<Align> {
+    match c {
+        '<' => Some(Align::Left),
+        '>' => Some(Align::Right),
+        '^' => Some(Align::Center),
+        _ => None
+    }
+}
+
+impl<'a> Template<'a> {
+    pub fn parse(text: &'a str) -> Template<'a> {
+        let mut parser: Parser = Parser {
+            text,
+            pos: 0
+        };
+        parser.parse(&[])
+    }
+
+    // Whether the placeholder is used anywhere in the template, including in fallbacks and conditions
+    pub fn uses(&self, name: &str) -> bool {
+        self.parts.iter().any(|part| match part {
+            Part::Text(_) => false,
+            Part::Placeholder { name: x, fallback, .. } => *x == name || fallback.as_ref().is_some_and(|x| x.uses(name)),
+            Part::Conditional { name: x, then, otherwise, .. } => *x == name || then.uses(name) || otherwise.as_ref().is_some_and(|x| x.uses(name)),
+        })
+    }
+    // Every placeholder used in the template, in the order they're written
+    pub fn placeholders(&self) -> Vec<&'a str> {
+        let mut placeholders: Vec<&'a str> = Vec::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(_) => {},
+                Part::Placeholder { name, fallback, .. } => {
+                    placeholders.push(name);
+                    if let Some(fallback) = fallback {
+                        placeholders.extend(fallback.placeholders());
+                    }
+                },
+                Part::Conditional { name, then, otherwise, .. } => {
+                    placeholders.push(name);
+                    placeholders.extend(then.placeholders());
+                    if let Some(otherwise) = otherwise {
+                        placeholders.extend(otherwise.placeholders());
+                    }
+                },
+            }
+        }
+
+        placeholders
+    }
+
+    // Fills in the template, with value giving back the placeholder's value or None if it isn't one
+    pub fn render(&self, value: &dyn Fn(&str) -> Option<String>) -> String {
+        let mut result: String = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(x) => result.push_str(x),
+                Part::Placeholder { source, name, spec, fallback } => {
+                    let Some(mut text) = value(name) else {
+                        result.push_str(source);
+                        continue;
+                    };
+                    if let Some(fallback) = fallback.as_ref().filter(|_| is_unknown(&text)) {
+                        text = fallback.render(value);
+                    }
+                    result.push_str(&spec.apply(text));
+                },
+                Part::Conditional { source, name, then, otherwise } => {
+                    match value(name) {
+                        Some(x) if !is_unknown(&x) => result.push_str(&then.render(value)),
+                        Some(_) => if let Some(otherwise) = otherwise {
+                            result.push_str(&otherwise.render(value));
+                        },
+                        None => result.push_str(source),
+                    }
+                },
+            }
+        }
+
+        result
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize
+}
+impl<'a> Parser<'a> {
+    // Parses until the end of the text, or until one of the stop characters at this level
+    fn parse(&mut self, stops: &[char]) -> Template<'a> {
+        let mut parts: Vec<Part<'a>> = Vec::new();
+        let nested: bool = !stops.is_empty();
+        let mut text_start: usize = self.pos;
+
+        while let Some(c) = self.peek() {
+            if stops.contains(&c) {
+                break;
+            }
+
+            let start: usize = self.pos;
+            let part: Option<Part<'a>> = match c {
+                '{' if self.text[start..].starts_with("{{") => {
+                    self.pos += 2;
+                    Some(Part::Text(&self.text[start..=start]))
+                },
+                // Braces only close placeholders when nested, so the fallback in {a|{b}} ends where it should
+                '}' if !nested && self.text[start..].starts_with("}}") => {
+                    self.pos += 2;
+                    Some(Part::Text(&self.text[start..=start]))
+                },
+                '{' => self.parse_placeholder(),
+                _ => None,
+            };
+            match part {
+                Some(part) => {
+                    if text_start < start {
+                        parts.push(Part::Text(&self.text[text_start..start]));
+                    }
+                    parts.push(part);
+                    text_start = self.pos;
+                },
+                None => self.pos = start + c.len_utf8(),
+            }
+        }
+        if text_start < self.pos {
+            parts.push(Part::Text(&self.text[text_start..self.pos]));
+        }
+
+        Template {
+            parts
+        }
+    }
+
+    // Tries to parse a placeholder at the current position, leaving it as it was if it can't
+    fn parse_placeholder(&mut self) -> Option<Part<'a>> {
+        let start: usize = self.pos;
+        let part: Option<Part<'a>> = self.parse_placeholder_inner(start);
+        if part.is_none() {
+            self.pos = start;
+        }
+
+        part
+    }
+    fn parse_placeholder_inner(&mut self, start: usize) -> Option<Part<'a>> {
+        self.pos += 1;
+        let conditional: bool = self.eat('?');
+        let name_start: usize = self.pos;
+        while self.peek().is_some_and(|x| x.is_ascii_alphanumeric() || x == '_' || x == '-') {
+            self.pos += 1;
+        }
+        let name: &'a str = &self.text[name_start..self.pos];
+        if name.is_empty() {
+            return None;
+        }
+
+        if conditional {
+            if !self.eat(':') {
+                return None;
+            }
+            let then: Template<'a> = self.parse(&['|', '}']);
+            let otherwise: Option<Template<'a>> = if self.eat('|') {
+                Some(self.parse(&['}']))
+            } else {
+                None
+            };
+            if !self.eat('}') {
+                return None;
+            }
+            return Some(Part::Conditional {
+                source: &self.text[start..self.pos],
+                name,
+                then,
+                otherwise
+            });
+        }
+
+        let mut spec: Spec = Spec::default();
+        if self.eat(':') {
+            let spec_start: usize = self.pos;
+            while self.peek().is_some_and(|x| x != '|' && x != '}') {
+                self.pos += self.peek()?.len_utf8();
+            }
+            spec = Spec::parse(&self.text[spec_start..self.pos])?;
+        }
+        let fallback: Option<Template<'a>> = if self.eat('|') {
+            Some(self.parse(&['}']))
+        } else {
+            None
+        };
+        if !self.eat('}') {
+            return None;
+        }
+
+        Some(Part::Placeholder {
+            source: &self.text[start..self.pos],
+            name,
+            spec,
+            fallback
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            return true;
+        }
+        false
+    }
+}
+
+// Parses and fills in the template in one go
+pub fn render(text: &str, value: &dyn Fn(&str) -> Option<String>) -> String {
+    Template::parse(text).render(value)
+}
+// Fills in every one of the module's placeholders as unknown, for when the module failed
+pub fn render_unknown(text: &str, placeholders: &[&str]) -> String {
+    render(text, &|name| placeholders.contains(&name).then(|| UNKNOWN.to_string()))
+}
+
+pub fn is_unknown(value: &str) -> bool {
+    value.is_empty() || value == UNKNOWN
+}
+
+fn visible_width(text: &str) -> usize {
+    formatter::display_width(text)
+}
+// Cuts the text down to the width, skipping over any color codes so they aren't cut in half
+fn truncate(text: &str, max_width: usize) -> String {
+    formatter::truncate(text, max_width, "")
+}
+
+mod tests {
+    #[allow(dead_code)]
+    fn value(name: &str) -> Option<String> {
+        match name {
+            "name" => Some("Firefox".to_string()),
+            "version" => Some("Unknown".to_string()),
+            "path" => Some(String::new()),
+            "long" => Some("NVIDIA GeForce RTX 4090".to_string()),
+            _ => None
+        }
+    }
+
+    // Check the basics still work like the old replace chains did
+    #[test]
+    fn test_placeholders() {
+        use super::render;
+
+        assert_eq!(render("{name} {version}", &super::tests::value), "Firefox Unknown");
+        assert_eq!(render("{color-red}{name}", &super::tests::value), "{color-red}Firefox");
+        assert_eq!(render("{missing} {name", &super::tests::value), "{missing} {name");
+        assert_eq!(render("{{name}} }{ {}", &super::tests::value), "{name} }{ {}");
+    }
+
+    #[test]
+    fn test_fallbacks_and_conditionals() {
+        use super::render;
+
+        assert_eq!(render("{version|?}", &super::tests::value), "?");
+        assert_eq!(render("{name|?}", &super::tests::value), "Firefox");
+        assert_eq!(render("{path|{name} (no path)}", &super::tests::value), "Firefox (no path)");
+        assert_eq!(render("{name}{?version: ({version})}", &super::tests::value), "Firefox");
+        assert_eq!(render("{?name:[{name}]|none}", &super::tests::value), "[Firefox]");
+        assert_eq!(render("{?path:{path}|no path}", &super::tests::value), "no path");
+        assert_eq!(render("{?missing:x}", &super::tests::value), "{?missing:x}");
+    }
+
+    #[test]
+    fn test_specs() {
+        use super::render;
+
+        assert_eq!(render("[{name:<10}]", &super::tests::value), "[Firefox   ]");
+        assert_eq!(render("[{name:>10}]", &super::tests::value), "[   Firefox]");
+        assert_eq!(render("[{name:-^11}]", &super::tests::value), "[--Firefox--]");
+        assert_eq!(render("[{long:.14}]", &super::tests::value), "[NVIDIA GeForce]");
+        assert_eq!(render("[{long:>8.4}]", &super::tests::value), "[    NVID]");
+        assert_eq!(render("[{version:>3|?}]", &super::tests::value), "[  ?]");
+        // Not a valid spec, so it's left alone
+        assert_eq!(render("{name:abc}", &super::tests::value), "{name:abc}");
+        // Color codes don't count towards the width
+        assert_eq!(super::truncate("\x1b[31mabcdef\x1b[0m", 3), "\x1b[31mabc\x1b[0m");
+    }
+
+    #[test]
+    fn test_uses() {
+        use super::Template;
+
+        let template: Template = Template::parse("{a:>4} {?b:{c}|{d|{e}}} {{f}}");
+        assert!(["a", "b", "c", "d", "e"].iter().all(|x| template.uses(x)));
+        assert!(!template.uses("f"));
+        assert_eq!(template.placeholders(), vec!["a", "b", "c", "d", "e"]);
+    }
+}
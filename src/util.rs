@@ -1,6 +1,6 @@
 // Some utility functions
 
-use std::{ffi::{c_char, CStr}, fs::File, io::Read, path::{Path, PathBuf}};
+use std::{ffi::{c_char, CStr}, fs::File, io::Read, path::{Path, PathBuf}, process::{Command, Output, Stdio}, thread::{self, JoinHandle}, time::{Duration, Instant}};
 
 /// Quickly reads the full contents of a specified file using [`File::open`] and [`File::read_to_string`]
 /// Don't use this for medium to large sized files, for performance reasons please use a buffer instead.
@@ -19,23 +19,15 @@ pub fn file_read(path: &Path) -> Result<String, String> {
     Ok(contents)
 }
 
-/// Checks each [`Path`] in `paths` for existance, and returns the first one that exists.
-/// If none of the paths exists, returns [`None`]
-/// For an owned version, see [`find_first_pathbuf_exists`]
-pub fn find_first_path_exists(paths: Vec<&Path>) -> Option<&Path> {
-    for p in paths {
-        if !p.exists() {
-            continue
-        }
-
-        return Some(p);
-    }
-
-    None
+/// Joins the absolute system `path` onto `root`, so that file based sources can be read from
+/// somewhere else, e.g a mounted chroot or a captured fixture tree.
+/// With the default root of `/` the path is returned as-is.
+pub fn root_path(root: &str, path: &str) -> PathBuf {
+    Path::new(root).join(path.trim_start_matches('/'))
 }
+
 /// Checks each [`PathBuf`] in `paths` for existance, and returns the first one that exists.
 /// If none of the paths exists, returns [`None`]
-/// For a borrowed version, see [`find_first_path_exists`]
 pub fn find_first_pathbuf_exists(paths: Vec<PathBuf>) -> Option<PathBuf> {
     for p in paths {
         if !p.exists() {
@@ -70,9 +62,72 @@ pub fn cstr_from_ptr(ptr: *const c_char) -> Result<String, String> {
     }
 }
 
+/// Runs `command` and collects its output the same as [`Command::output`], but kills it if it's
+/// still running once `timeout` has passed, returning [`None`] instead.
+/// [`Err<String>`] is returned if the command couldn't be ran, with the string being the error message.
+pub fn command_output_timeout(command: &mut Command, timeout: Option<Duration>) -> Result<Option<Output>, String> {
+    let Some(timeout) = timeout else {
+        return command.output().map(Some).map_err(|e| e.to_string());
+    };
+
+    let start: Instant = Instant::now();
+    let mut child = match command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn() {
+        Ok(r) => r,
+        Err(e) => return Err(e.to_string()),
+    };
+    // Read on their own threads, so a command filling up its pipe doesn't block waiting for us
+    let stdout: Option<JoinHandle<Vec<u8>>> = child.stdout.take().map(read_pipe);
+    let stderr: Option<JoinHandle<Vec<u8>>> = child.stderr.take().map(read_pipe);
+
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                return Ok(Some(Output {
+                    status,
+                    stdout: stdout.and_then(|x| x.join().ok()).unwrap_or_default(),
+                    stderr: stderr.and_then(|x| x.join().ok()).unwrap_or_default()
+                }));
+            },
+            Ok(None) => {},
+            Err(e) => return Err(e.to_string()),
+        }
+
+        let remaining: Duration = timeout.saturating_sub(start.elapsed());
+        if remaining.is_zero() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(remaining.min(Duration::from_millis(5)));
+    }
+}
+fn read_pipe<R: Read + Send + 'static>(mut pipe: R) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut contents: Vec<u8> = Vec::new();
+        let _ = pipe.read_to_end(&mut contents);
+        contents
+    })
+}
+
 /// Returns `true` if we're running under Window's WSL
-pub fn in_wsl() -> bool {
+pub fn in_wsl(root: &str) -> bool {
     // Credit: https://superuser.com/a/1749811
     // Using the first method
-    Path::new("/proc/sys/fs/binfmt_misc/WSLInterop").exists()
+    root_path(root, "/proc/sys/fs/binfmt_misc/WSLInterop").exists()
+}
+
+/// Returns the size of the terminal attached to stdout, or [`None`] if it isn't going to one
+/// The pixel sizes are left as 0 by some terminals.
+pub fn terminal_size() -> Option<libc::winsize> {
+    let mut size: libc::winsize = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0
+    };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } != 0 || size.ws_col == 0 || size.ws_row == 0 {
+        return None;
+    }
+
+    Some(size)
 }
@@ -4,6 +4,18 @@ use std::{env, process::Command};
 use crate::{modules::shell::KNOWN_SHELLS, common_sources::package_managers::ManagerInfo, proccess_info::ProcessInfo};
 
 pub fn find_version(exe_path: &str, name: Option<&str>, package_managers: &ManagerInfo) -> Option<String> {
+    if let Some(version) = package_managers.version_cache.as_ref().and_then(|x| x.get(exe_path)) {
+        return Some(version);
+    }
+
+    let version: Option<String> = detect_version(exe_path, name, package_managers);
+    if let (Some(cache), Some(version)) = (&package_managers.version_cache, &version) {
+        cache.insert(exe_path, version);
+    }
+    version
+}
+
+fn detect_version(exe_path: &str, name: Option<&str>, package_managers: &ManagerInfo) -> Option<String> {
     // Steps;
     // If it's located in /usr/bin, go to the package manager caches and search for it
     // If not (or not found), check the known checksums 
@@ -39,12 +51,12 @@ pub fn find_version(exe_path: &str, name: Option<&str>, package_managers: &Manag
 }
 
 fn use_package_manager(name: &str, package_managers: &ManagerInfo) -> Option<String> {
-    if let Some(package) = package_managers.packages.get(name) {
+    if let Some(package) = package_managers.packages().get(name) {
         return Some(package.version.to_string());
     }
 
     if name == "weston-terminal" {
-        if let Some(package) = package_managers.packages.get("weston") {
+        if let Some(package) = package_managers.packages().get("weston") {
             return Some(package.version.to_string());
         }
     }
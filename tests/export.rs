@@ -0,0 +1,27 @@
+// Exporting changes global color state (colored's override and the forced 24-bit colors), so it's
+// tested in it's own binary rather than alongside the rest
+
+use std::{env, fs};
+
+use colored::Colorize;
+use crab_fetch::{export::{self, ExportFormat}, formatter::{self, CrabFetchColor}};
+
+#[test]
+fn export_keeps_truecolor() {
+    // The same as main does for --export, on a terminal that doesn't say it supports 24-bit colors
+    env::remove_var("COLORTERM");
+    colored::control::set_override(true);
+    formatter::force_truecolor();
+
+    let frame: String = format!("{} > {}\n",
+        CrabFetchColor::Rgb(0, 255, 136).color_string("CPU").bold(),
+        CrabFetchColor::Rgb(233, 84, 32).color_string("Ryzen")
+    );
+    let path = env::temp_dir().join(format!("crabfetch-export-{}.svg", std::process::id()));
+    export::export(&frame, &ExportFormat::Svg, path.to_str().unwrap()).unwrap();
+    let svg: String = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(svg.contains("fill=\"#00ff88\" font-weight=\"bold\">CPU</text>"), "{svg}");
+    assert!(svg.contains("fill=\"#e95420\">Ryzen</text>"), "{svg}");
+}
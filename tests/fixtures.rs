@@ -0,0 +1,183 @@
+// Runs each module's detection against the captured system trees in tests/fixtures, by pointing
+// the root option at them, and checks what the module renders
+//
+// Each fixture is named after the machine it was captured from;
+// - arch_laptop   - x86 laptop with a battery, a couple of displays and pacman
+// - debian_server - x86 desktop board running a headless Debian with dpkg
+// - void_rpi      - Raspberry Pi 4 running Void with xbps
+//
+// When adding a new fixture only copy over what the modules read, and strip out anything personal
+
+use crab_fetch::{common_sources::{package_managers::ManagerInfo, syscalls::SyscallCache}, config_manager, modules::{battery::{self, BatteryInfo}, cpu::{self, CPUConfiguration}, host, initsys::{self, InitSystemConfiguration}, memory, mounts::{self, MountConfiguration, MountInfo}, os::{self, OSConfiguration}, packages, processes, uptime}, Configuration, Module};
+
+fn fixture_config(fixture: &str) -> Configuration {
+    let mut config: Configuration = config_manager::parse(&Some("none".to_string()), &None).unwrap();
+    config.root = format!("{}/tests/fixtures/{fixture}", env!("CARGO_MANIFEST_DIR"));
+    config
+}
+
+// Renders a single line the same way it'd show up in the terminal, minus the colors
+fn render(line: (String, String)) -> String {
+    strip_ansi_escapes::strip_str(format!("{}{}", line.0, line.1))
+}
+fn render_all(lines: Vec<(String, String)>) -> Vec<String> {
+    lines.into_iter()
+        .map(render)
+        .collect()
+}
+
+// CPU
+#[test]
+fn cpu_x86_intel() {
+    let config: Configuration = fixture_config("arch_laptop");
+    let cpu = cpu::get_cpu(&config).unwrap();
+    assert_eq!(render(cpu.style(&config)), "CPU > 11th Gen Intel(R) Core(TM) i7-1165G7 @ 2.80GHz (4c 8t) @ 4.7 GHz");
+}
+#[test]
+fn cpu_x86_amd() {
+    // No cpufreq in this one, so the max clock falls back to /proc/cpuinfo
+    let config: Configuration = fixture_config("debian_server");
+    let cpu = cpu::get_cpu(&config).unwrap();
+    assert_eq!(render(cpu.style(&config)), "CPU > AMD Ryzen 5 3600 (6c 12t) @ 3.6 GHz");
+}
+#[test]
+fn cpu_arm() {
+    // No model name, cores or threads in ARM's cpuinfo
+    let config: Configuration = fixture_config("void_rpi");
+    let cpu = cpu::get_cpu(&config).unwrap();
+    #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+    assert_eq!(render(cpu.style(&config)), "CPU > ARM Cortex-A72 (4c 4t) @ 1.8 GHz");
+    // The part lookup is only compiled in for ARM builds
+    #[cfg(not(any(target_arch = "arm", target_arch = "aarch64")))]
+    assert_eq!(render(cpu.style(&config)), "CPU > Unknown (4c 4t) @ 1.8 GHz");
+}
+#[test]
+fn cpu_current_clock_uncached() {
+    let mut config: Configuration = fixture_config("arch_laptop");
+    let config_key = cpu::MODULE.cache.unwrap().config_key;
+    assert!(config_key(&config).is_some());
+    config.section_mut::<CPUConfiguration>().format = "{name} @ {current_clock_ghz} GHz".to_string();
+    assert!(config_key(&config).is_none());
+}
+
+// Battery
+#[test]
+fn battery() {
+    let config: Configuration = fixture_config("arch_laptop");
+    let batteries: Vec<BatteryInfo> = battery::get_batteries(&config).unwrap();
+    assert_eq!(render_all(BatteryInfo::style_list(&batteries, &config)), vec!["Battery 0 > 87%"]);
+}
+
+// Packages
+fn render_packages(config: &Configuration) -> String {
+    let package_managers: ManagerInfo = ManagerInfo::new(config);
+    render(packages::get_packages(config, &package_managers).style(config))
+}
+#[test]
+fn packages_pacman() {
+    assert_eq!(render_packages(&fixture_config("arch_laptop")), "Packages > 6 (pacman)");
+}
+#[test]
+#[cfg_attr(feature = "android", ignore = "android builds read Android's own paths instead")]
+fn packages_dpkg() {
+    assert_eq!(render_packages(&fixture_config("debian_server")), "Packages > 5 (dpkg)");
+}
+#[test]
+fn packages_xbps() {
+    assert_eq!(render_packages(&fixture_config("void_rpi")), "Packages > 4 (xbps)");
+}
+
+// Host
+#[test]
+fn host_dmi() {
+    let config: Configuration = fixture_config("arch_laptop");
+    assert_eq!(render(host::get_host(&config).unwrap().style(&config)), "Host > XPS 13 9310 (Notebook)");
+}
+#[test]
+fn host_board_name() {
+    // Desktop boards only fill in board_name
+    let config: Configuration = fixture_config("debian_server");
+    assert_eq!(render(host::get_host(&config).unwrap().style(&config)), "Host > B450M DS3H (Desktop)");
+}
+#[test]
+fn host_devicetree() {
+    let config: Configuration = fixture_config("void_rpi");
+    let host = host::get_host(&config).unwrap();
+    assert_eq!(host.host(), "Raspberry Pi 4 Model B Rev 1.4");
+    assert_eq!(render(host.style(&config)), "Host > Raspberry Pi 4 Model B Rev 1.4 (Unknown)");
+}
+
+// OS
+#[test]
+fn os_release() {
+    // The kernel comes from uname, so that's left out
+    let mut config: Configuration = fixture_config("arch_laptop");
+    config.section_mut::<OSConfiguration>().format = "{distro}".to_string();
+    let mut syscall_cache: SyscallCache = SyscallCache::new();
+    assert_eq!(render(os::get_os(&config, &mut syscall_cache).unwrap().style(&config)), "Operating System > Arch Linux");
+
+    config.root = fixture_config("debian_server").root;
+    assert_eq!(render(os::get_os(&config, &mut syscall_cache).unwrap().style(&config)), "Operating System > Debian GNU/Linux 12 (bookworm)");
+}
+#[test]
+fn os_release_extras() {
+    // Void quotes it's values, Arch doesn't
+    let config: Configuration = fixture_config("void_rpi");
+    let os = os::get_os(&config, &mut SyscallCache::new()).unwrap();
+    assert_eq!(os.distro_id(), "void");
+    assert_eq!(os.ansi_color(), Some("0;38;2;71;128;97"));
+    assert_eq!(os.logo(), Some("void-logo"));
+    assert_eq!(os.distro_ids(), vec!["void"]);
+
+    let config: Configuration = fixture_config("arch_laptop");
+    let os = os::get_os(&config, &mut SyscallCache::new()).unwrap();
+    assert_eq!(os.distro_ids(), vec!["arch", "archlinux"]);
+    assert!(os.distro_id_like().is_empty());
+}
+
+// Memory
+#[test]
+fn memory() {
+    let config: Configuration = fixture_config("arch_laptop");
+    assert_eq!(render(memory::get_memory(&config).unwrap().style(&config)), "Memory > 6.36 GB / 16.47 GB (38.62%)");
+}
+
+// Mounts
+#[test]
+#[cfg_attr(feature = "android", ignore = "android builds read Android's own paths instead")]
+fn mounts() {
+    // Space is left out as statfs would be reporting the fixture's own disk
+    let mut config: Configuration = fixture_config("arch_laptop");
+    config.section_mut::<MountConfiguration>().format = "{device} [{filesystem}]".to_string();
+    let mounts: Vec<MountInfo> = mounts::get_mounted_drives(&config).unwrap();
+    assert_eq!(render_all(MountInfo::style_list(&mounts, &config)), vec![
+        "Disk (/) > /dev/nvme0n1p2 [ext4]",
+        "Disk (/boot) > /dev/nvme0n1p1 [vfat]",
+        "Disk (/home) > /dev/nvme0n1p3 [ext4]",
+    ]);
+}
+
+// Init System
+#[test]
+fn initsys() {
+    // The version's left unknown, as it'd have to run our own init system to find it
+    let mut config: Configuration = fixture_config("arch_laptop");
+    config.section_mut::<InitSystemConfiguration>().format = "{name} {version} ({path})".to_string();
+    let package_managers: ManagerInfo = ManagerInfo::new(&config);
+    assert_eq!(render(initsys::get_init_system(&config, &package_managers).unwrap().style(&config)), "Init System > systemd Unknown (/usr/lib/systemd/systemd)");
+}
+
+// Uptime
+#[test]
+fn uptime() {
+    let config: Configuration = fixture_config("debian_server");
+    let mut syscall_cache: SyscallCache = SyscallCache::new();
+    assert_eq!(render(uptime::get_uptime(&config, &mut syscall_cache).unwrap().style(&config)), "Uptime > 1month 4days 13h 39m 15s");
+}
+
+// Processes
+#[test]
+fn processes() {
+    let config: Configuration = fixture_config("arch_laptop");
+    assert_eq!(render(processes::get_process_count(&config).unwrap().style(&config)), "Total Processes > 5");
+}